@@ -1,4 +1,8 @@
-use emerald::{ent::EntLoadConfig, *};
+use emerald::{
+    ent::{EntLoadConfig, EntSaveConfig},
+    *,
+};
+use serde::{Deserialize, Serialize};
 
 pub fn main() {
     emerald::start(
@@ -9,39 +13,12 @@ pub fn main() {
     )
 }
 
+#[derive(Serialize, Deserialize)]
 struct PlayerData {
     pub name: String,
     pub max_hp: i64,
 }
 
-fn custom_component_loader(
-    loader: &mut AssetLoader<'_>,
-    entity: Entity,
-    world: &mut World,
-    toml_value: toml::Value,
-    toml_key: String,
-) -> Result<(), EmeraldError> {
-    // We want to match here because in a real game we'll probably have many custom components
-    match toml_key.as_str() {
-        "my_custom_player_component" => {
-            let name = toml_value
-                .get("name")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let max_hp = toml_value.get("max_hp").unwrap().as_integer().unwrap();
-
-            world
-                .insert_one(entity, PlayerData { max_hp, name })
-                .unwrap();
-        }
-        _ => {}
-    }
-
-    Ok(())
-}
-
 pub struct EntLoadingExample {
     world: World,
 }
@@ -49,18 +26,29 @@ impl Game for EntLoadingExample {
     fn initialize(&mut self, mut emd: Emerald) {
         emd.set_asset_folder_root("./examples/assets/".to_string());
 
-        let config = EntLoadConfig {
-            custom_component_loader: Some(&custom_component_loader),
-            ..Default::default()
-        };
+        // Components are registered once per game, every ent loaded afterwards can use them.
+        emd.loader()
+            .ent_components()
+            .register::<PlayerData>("my_custom_player_component")
+            .unwrap();
 
         let entity = emd
             .loader()
-            .ent(&mut self.world, config, "bunny.ent")
+            .ent(&mut self.world, EntLoadConfig::default(), "bunny.ent")
             .unwrap();
 
         // assert that we've successfully loaded a user defined component
         assert!(self.world.get::<PlayerData>(entity).is_ok());
+
+        // Save the entity back out, this file can be loaded again via `emd.loader().ent`.
+        let config = EntSaveConfig {
+            components: Some(emd.ent_components()),
+            ..Default::default()
+        };
+        let ent_toml = self.world.save_ent(entity, config).unwrap();
+        emd.writer()
+            .write_to_user_file(ent_toml.as_bytes(), "bunny_saved.ent")
+            .unwrap();
     }
 
     fn update(&mut self, emd: Emerald) {
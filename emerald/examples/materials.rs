@@ -0,0 +1,97 @@
+use emerald::*;
+
+const FLASH_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform lowp float intensity;
+uniform lowp vec4 flash_color;
+
+void main() {
+    lowp vec4 texel = texture2D(tex, uv) * color;
+    gl_FragColor = vec4(mix(texel.rgb, flash_color.rgb, intensity), texel.a);
+}"#;
+
+pub fn main() {
+    emerald::start(
+        Box::new(MaterialsExample {
+            world: World::new(),
+            flash: None,
+            intensity: 0.0,
+        }),
+        GameSettings::default(),
+    )
+}
+
+pub struct MaterialsExample {
+    world: World,
+    flash: Option<MaterialKey>,
+    intensity: f32,
+}
+impl Game for MaterialsExample {
+    fn initialize(&mut self, mut emd: Emerald) {
+        emd.set_asset_folder_root(String::from("./examples/assets/"));
+
+        let shader = emd
+            .graphics()
+            .create_shader(
+                "flash",
+                DEFAULT_VERTEX_SHADER,
+                FLASH_FRAGMENT,
+                &[
+                    ("intensity", UniformType::Float1),
+                    ("flash_color", UniformType::Float4),
+                ],
+            )
+            .unwrap();
+        let flash = emd.graphics().create_material(&shader).unwrap();
+        emd.graphics()
+            .get_material_mut(flash)
+            .unwrap()
+            .set_uniform(
+                "flash_color",
+                UniformValue::Vec4(glam::Vec4::new(1.0, 1.0, 1.0, 1.0)),
+            )
+            .unwrap();
+        self.flash = Some(flash);
+
+        let mut sprite = emd.loader().sprite("bunny.png").unwrap();
+        sprite.scale = Vector2::new(4.0, 4.0);
+        sprite.material = Some(flash);
+        self.world
+            .spawn((sprite, Transform::from_translation((0.0, 0.0))));
+
+        // The same texture drawn with the default shader, for comparison.
+        let mut sprite = emd.loader().sprite("bunny.png").unwrap();
+        sprite.scale = Vector2::new(4.0, 4.0);
+        self.world
+            .spawn((sprite, Transform::from_translation((-128.0, 0.0))));
+    }
+
+    fn update(&mut self, mut emd: Emerald) {
+        if emd.input().is_key_just_pressed(KeyCode::Space) {
+            self.intensity = 1.0;
+        }
+
+        self.intensity = (self.intensity - emd.delta() * 4.0).max(0.0);
+    }
+
+    fn draw(&mut self, mut emd: Emerald) {
+        let mut graphics = emd.graphics();
+        if let Some(material) = self
+            .flash
+            .and_then(|flash| graphics.get_material_mut(flash))
+        {
+            material
+                .set_uniform("intensity", UniformValue::Float(self.intensity))
+                .unwrap();
+        }
+
+        graphics.begin().unwrap();
+        graphics.draw_world(&mut self.world).unwrap();
+        graphics.render().unwrap();
+    }
+}
@@ -21,9 +21,6 @@ pub fn main() {
     )
 }
 
-const GROUP_ONE: InteractionGroups = InteractionGroups::new(1, 1);
-const GROUP_TWO: InteractionGroups = InteractionGroups::new(2, 2);
-
 #[derive(Clone, Debug)]
 pub struct Velocity {
     pub dx: f32,
@@ -43,6 +40,15 @@ impl Game for PhysicsGroupsExample {
     fn initialize(&mut self, mut emd: Emerald) {
         emd.set_asset_folder_root(String::from("./examples/assets/"));
 
+        // The blue box goes through the green ones.
+        let mut layers = CollisionLayers::new();
+        layers.add_layer("blue").unwrap();
+        layers.add_layer("green").unwrap();
+        layers.set_collides("blue", "green", false).unwrap();
+        let blue = layers.groups("blue").unwrap();
+        let green = layers.groups("green").unwrap();
+        self.world.physics().set_collision_layers(layers);
+
         let (entity1, body1) = self
             .world
             .spawn_with_body(
@@ -56,13 +62,13 @@ impl Game for PhysicsGroupsExample {
 
         self.world.physics().build_collider(
             body1,
-            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(GROUP_ONE),
+            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(blue),
         );
 
         self.world.physics().build_collider(
             body1,
             ColliderBuilder::cuboid(16.0, 8.0)
-                .collision_groups(GROUP_ONE)
+                .collision_groups(blue)
                 .sensor(true),
         );
 
@@ -80,7 +86,7 @@ impl Game for PhysicsGroupsExample {
 
         self.world.physics().build_collider(
             body2,
-            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(GROUP_TWO),
+            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(green),
         );
 
         let (entity3, body3) = self
@@ -96,7 +102,7 @@ impl Game for PhysicsGroupsExample {
 
         self.world.physics().build_collider(
             body3,
-            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(GROUP_TWO),
+            ColliderBuilder::cuboid(16.0, 8.0).collision_groups(green),
         );
 
         self.e1 = Some(entity1);
@@ -0,0 +1,74 @@
+use emerald::*;
+
+pub fn main() {
+    emerald::start(
+        Box::new(PostProcessingExample {
+            world: World::new(),
+            effects: Vec::new(),
+        }),
+        GameSettings::default(),
+    )
+}
+
+pub struct PostProcessingExample {
+    world: World,
+    effects: Vec<(KeyCode, MaterialKey, bool)>,
+}
+impl Game for PostProcessingExample {
+    fn initialize(&mut self, mut emd: Emerald) {
+        emd.set_asset_folder_root(String::from("./examples/assets/"));
+
+        let mut sprite = emd.loader().sprite("bunny.png").unwrap();
+        sprite.scale = Vector2::new(4.0, 4.0);
+        for x in -2..=2 {
+            self.world.spawn((
+                sprite.clone(),
+                Transform::from_translation((x as f32 * 128.0, 0.0)),
+            ));
+        }
+
+        let effects = [
+            (KeyCode::Key1, PostProcessEffect::ColorGrading),
+            (KeyCode::Key2, PostProcessEffect::Bloom),
+            (KeyCode::Key3, PostProcessEffect::Crt),
+            (KeyCode::Key4, PostProcessEffect::Vignette),
+        ];
+        for (key, effect) in effects.iter() {
+            let material = emd.graphics().create_post_process_effect(*effect).unwrap();
+            self.effects.push((*key, material, false));
+        }
+
+        if let Some(material) = emd.graphics().get_material_mut(self.effects[0].1) {
+            material
+                .set_uniform("saturation", UniformValue::Float(0.2))
+                .unwrap();
+        }
+    }
+
+    fn update(&mut self, mut emd: Emerald) {
+        let mut changed = false;
+        for (key, _, enabled) in self.effects.iter_mut() {
+            if emd.input().is_key_just_pressed(*key) {
+                *enabled = !*enabled;
+                changed = true;
+            }
+        }
+
+        // Rebuild the stack so the effects keep a stable order.
+        if changed {
+            let mut graphics = emd.graphics();
+            graphics.clear_post_process();
+            for (_, material, enabled) in &self.effects {
+                if *enabled {
+                    graphics.add_post_process(*material);
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, mut emd: Emerald) {
+        emd.graphics().begin().unwrap();
+        emd.graphics().draw_world(&mut self.world).unwrap();
+        emd.graphics().render().unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use emerald::*;
+
+pub fn main() {
+    emerald::start(
+        Box::new(PrimitivesExample { elapsed: 0.0 }),
+        GameSettings::default(),
+    )
+}
+
+pub struct PrimitivesExample {
+    elapsed: f32,
+}
+impl Game for PrimitivesExample {
+    fn update(&mut self, emd: Emerald) {
+        self.elapsed += emd.delta();
+    }
+
+    fn draw(&mut self, mut emd: Emerald) {
+        let mut graphics = emd.graphics();
+        graphics.begin().unwrap();
+
+        // Health bar
+        let health = (self.elapsed.sin() + 1.0) / 2.0;
+        graphics
+            .draw_color_rect(
+                &ColorRect {
+                    width: (200.0 * health) as u32,
+                    height: 16,
+                    centered: false,
+                    color: Color::new(200, 40, 40, 255),
+                    ..Default::default()
+                },
+                &Transform::from_translation((40.0, 540.0)),
+            )
+            .unwrap();
+        graphics
+            .draw_rect_outline(&Rectangle::new(40.0, 540.0, 200.0, 16.0), 2.0, WHITE, 1.0)
+            .unwrap();
+
+        // Laser beam
+        let end = Vector2::new(400.0 + self.elapsed.cos() * 300.0, 300.0);
+        graphics
+            .draw_line(
+                Vector2::new(400.0, 50.0),
+                end,
+                4.0,
+                Color::new(80, 255, 120, 255),
+                0.0,
+            )
+            .unwrap();
+        graphics
+            .draw_circle(end, 12.0, Color::new(80, 255, 120, 255), 0.0)
+            .unwrap();
+
+        // Selection box
+        graphics
+            .draw_polyline(
+                &[
+                    Vector2::new(500.0, 400.0),
+                    Vector2::new(700.0, 400.0),
+                    Vector2::new(700.0, 500.0),
+                    Vector2::new(500.0, 500.0),
+                ],
+                1.0,
+                true,
+                WHITE,
+                0.0,
+            )
+            .unwrap();
+
+        // Concave star
+        let star = (0..10)
+            .map(|i| {
+                let angle = i as f32 / 10.0 * std::f32::consts::PI * 2.0 + self.elapsed;
+                let radius = if i % 2 == 0 { 60.0 } else { 25.0 };
+                Vector2::new(150.0 + angle.cos() * radius, 250.0 + angle.sin() * radius)
+            })
+            .collect::<Vec<_>>();
+        graphics
+            .draw_polygon(&star, Color::new(255, 220, 60, 255), 0.0)
+            .unwrap();
+
+        graphics.render().unwrap();
+    }
+}
@@ -89,13 +89,13 @@ impl Game for RaycastExample {
         }
 
         if let Some(ray) = ray {
-            let entity = self.world.physics().cast_ray(RayCastQuery {
+            let hit = self.world.physics().cast_ray(RayCastQuery {
                 ray,
                 ..RayCastQuery::default()
             });
 
-            if let Some(e) = entity {
-                if let Ok(s) = self.world.get_mut::<String>(e) {
+            if let Ok(Some(hit)) = hit {
+                if let Ok(s) = self.world.get_mut::<String>(hit.entity) {
                     println!("Found {}", s.clone());
                 }
             }
@@ -112,7 +112,7 @@ impl Game for RaycastExample {
         if let Some(vel) = vel {
             let shape = Cuboid::new(Vector2::new(40.0, 10.0));
 
-            let entity = self.world.physics().cast_shape(
+            let hit = self.world.physics().cast_shape(
                 &shape,
                 ShapeCastQuery {
                     velocity: vel,
@@ -122,8 +122,8 @@ impl Game for RaycastExample {
                 },
             );
 
-            if let Some(e) = entity {
-                if let Ok(s) = self.world.get_mut::<String>(e) {
+            if let Ok(Some(hit)) = hit {
+                if let Ok(s) = self.world.get_mut::<String>(hit.entity) {
                     println!("Found {}", s.clone());
                 }
             }
@@ -0,0 +1,48 @@
+use emerald::{wrld::WorldLoadConfig, *};
+use serde::{Deserialize, Serialize};
+
+pub fn main() {
+    emerald::start(
+        Box::new(WorldLoadingExample {
+            world: World::new(),
+        }),
+        GameSettings::default(),
+    )
+}
+
+/// The custom component of bunny.ent.
+#[derive(Serialize, Deserialize)]
+struct PlayerData {
+    pub name: String,
+    pub max_hp: i64,
+}
+
+pub struct WorldLoadingExample {
+    world: World,
+}
+impl Game for WorldLoadingExample {
+    fn initialize(&mut self, mut emd: Emerald) {
+        emd.set_asset_folder_root("./examples/assets/".to_string());
+
+        emd.loader()
+            .ent_components()
+            .register::<PlayerData>("my_custom_player_component")
+            .unwrap();
+
+        self.world = emd
+            .loader()
+            .world(WorldLoadConfig::default(), "level.wrld")
+            .unwrap();
+    }
+
+    fn update(&mut self, emd: Emerald) {
+        aseprite_update_system(&mut self.world, emd.delta());
+        self.world.physics().step(emd.delta());
+    }
+
+    fn draw(&mut self, mut emd: Emerald<'_>) {
+        emd.graphics().begin().unwrap();
+        emd.graphics().draw_world(&mut self.world).unwrap();
+        emd.graphics().render().unwrap();
+    }
+}
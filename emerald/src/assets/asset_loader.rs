@@ -1,21 +1,24 @@
 use crate::assets::*;
 use crate::audio::*;
 use crate::ent::load_ent;
-use crate::ent::EntLoadConfig;
+use crate::ent::{EntComponentRegistry, EntLoadConfig};
 use crate::rendering::*;
+#[cfg(feature = "physics")]
+use crate::wrld::load_physics_settings;
+use crate::wrld::{load_wrld, WorldLoadConfig};
 use crate::*;
 
 use std::ffi::OsStr;
 
 pub struct AssetLoader<'c> {
-    pub(crate) quad_ctx: &'c mut miniquad::Context,
+    pub(crate) quad_ctx: Option<&'c mut miniquad::Context>,
     pub(crate) asset_store: &'c mut AssetStore,
     rendering_engine: &'c mut RenderingEngine,
     _audio_engine: &'c mut AudioEngine,
 }
 impl<'c> AssetLoader<'c> {
     pub(crate) fn new(
-        quad_ctx: &'c mut miniquad::Context,
+        quad_ctx: Option<&'c mut miniquad::Context>,
         asset_store: &'c mut AssetStore,
         rendering_engine: &'c mut RenderingEngine,
         _audio_engine: &'c mut AudioEngine,
@@ -74,7 +77,7 @@ impl<'c> AssetLoader<'c> {
         let font_image = FontImage::gen_image_color(512, 512, Color::new(0, 0, 0, 0));
         let font_texture_key = TextureKey::new(key.0.clone());
         let font_texture = Texture::from_rgba8(
-            &mut self.quad_ctx,
+            self.quad_ctx.as_deref_mut(),
             font_texture_key.clone(),
             font_image.width,
             font_image.height,
@@ -94,11 +97,16 @@ impl<'c> AssetLoader<'c> {
         self.asset_store
             .insert_fontdue_font(key.clone(), inner_font);
         self.asset_store
-            .insert_font(&mut self.quad_ctx, key.clone(), font)?;
+            .insert_font(self.quad_ctx.as_deref_mut(), key.clone(), font)?;
 
         Ok(key)
     }
 
+    /// The custom components of the game's ent and world files, see [`EntComponentRegistry`].
+    pub fn ent_components(&mut self) -> &mut EntComponentRegistry {
+        &mut self.asset_store.ent_components
+    }
+
     pub fn ent<T: AsRef<str>>(
         &mut self,
         world: &mut World,
@@ -109,12 +117,33 @@ impl<'c> AssetLoader<'c> {
         load_ent(self, world, toml, config)
     }
 
+    /// Loads a `.wrld` file into a new world, see [`WorldLoadConfig`].
+    pub fn world<T: AsRef<str>>(
+        &mut self,
+        config: WorldLoadConfig<'_>,
+        path: T,
+    ) -> Result<World, EmeraldError> {
+        let toml = self.string(path)?;
+        load_wrld(self, toml, config)
+    }
+
+    /// Loads physics settings from a toml file, using the schema of the `[physics]` table of a world file.
+    /// Settings missing from the file keep their default value.
+    #[cfg(feature = "physics")]
+    pub fn physics_settings<T: AsRef<str>>(
+        &mut self,
+        path: T,
+    ) -> Result<PhysicsSettings, EmeraldError> {
+        let toml = self.string(path)?.parse::<toml::Value>()?;
+        load_physics_settings(&toml, PhysicsSettings::default())
+    }
+
     /// Loads a `.aseprite` file.
     #[cfg(feature = "aseprite")]
     pub fn aseprite<T: AsRef<str>>(&mut self, path: T) -> Result<Aseprite, EmeraldError> {
         let path = path.as_ref();
         let data = self.asset_bytes(path)?;
-        Aseprite::new(self.quad_ctx, self.asset_store, path, data)
+        Aseprite::new(self.quad_ctx.as_deref_mut(), self.asset_store, path, data)
     }
 
     /// Loads an exported Aseprite sprite sheet. The animations json file should
@@ -131,7 +160,7 @@ impl<'c> AssetLoader<'c> {
         let aseprite_data = self.asset_bytes(animation_path)?;
 
         let sprite = self.sprite(texture_path)?;
-        let aseprite = Aseprite::from_exported(sprite, aseprite_data)?;
+        let aseprite = Aseprite::from_exported(sprite, animation_path, aseprite_data)?;
 
         Ok(aseprite)
     }
@@ -145,7 +174,7 @@ impl<'c> AssetLoader<'c> {
         }
 
         let data = self.asset_bytes(path)?;
-        let texture = Texture::new(&mut self.quad_ctx, key.clone(), data)?;
+        let texture = Texture::new(self.quad_ctx.as_deref_mut(), key.clone(), data)?;
         self.asset_store.insert_texture(key.clone(), texture);
 
         Ok(key)
@@ -155,8 +184,12 @@ impl<'c> AssetLoader<'c> {
     /// Please re-use render textures you've created before if possible.
     /// If you need a render texture with a new size, you should create a new render texture.
     pub fn render_texture(&mut self, w: usize, h: usize) -> Result<TextureKey, EmeraldError> {
-        self.rendering_engine
-            .create_render_texture(w, h, &mut self.quad_ctx, &mut self.asset_store)
+        self.rendering_engine.create_render_texture(
+            w,
+            h,
+            self.quad_ctx.as_deref_mut(),
+            &mut self.asset_store,
+        )
     }
 
     pub fn sprite<T: AsRef<str>>(&mut self, path: T) -> Result<Sprite, EmeraldError> {
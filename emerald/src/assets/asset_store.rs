@@ -1,3 +1,4 @@
+use crate::ent::EntComponentRegistry;
 use crate::rendering::*;
 use crate::{EmeraldError, Sound, SoundKey};
 
@@ -36,12 +37,14 @@ pub(crate) struct AssetStore {
     asset_folder_root: String,
     user_data_folder_root: String,
 
+    pub ent_components: EntComponentRegistry,
+
     #[cfg(feature = "hotreload")]
     pub(crate) file_hot_reload_metadata:
         HashMap<String, crate::assets::hotreload::HotReloadMetadata>,
 }
 impl AssetStore {
-    pub fn new(ctx: &mut Context, _game_name: String) -> Result<Self, EmeraldError> {
+    pub fn new(ctx: Option<&mut Context>, _game_name: String) -> Result<Self, EmeraldError> {
         let mut texture_key_map = HashMap::new();
         let default_texture = Texture::default(ctx).unwrap();
         texture_key_map.insert(TextureKey::default(), 0);
@@ -77,6 +80,8 @@ impl AssetStore {
             asset_folder_root,
             user_data_folder_root,
 
+            ent_components: EntComponentRegistry::new(),
+
             #[cfg(feature = "hotreload")]
             file_hot_reload_metadata: HashMap::new(),
         })
@@ -153,7 +158,7 @@ impl AssetStore {
 
     pub fn insert_font(
         &mut self,
-        _ctx: &mut Context,
+        _ctx: Option<&mut Context>,
         key: FontKey,
         font: Font,
     ) -> Result<(), EmeraldError> {
@@ -240,7 +245,7 @@ impl AssetStore {
         None
     }
 
-    pub fn _get_texture_mut(&mut self, key: &TextureKey) -> Option<&mut Texture> {
+    pub fn get_texture_mut(&mut self, key: &TextureKey) -> Option<&mut Texture> {
         if let Some(index) = self.texture_key_map.get(key) {
             return self.textures.get_mut(*index);
         }
@@ -261,7 +266,7 @@ impl AssetStore {
             self.texture_key_map.remove(&key);
             let texture = self.textures.remove(i as _);
 
-            if delete {
+            if delete && !texture.is_headless() {
                 texture.inner.delete();
             }
 
@@ -288,12 +293,12 @@ impl AssetStore {
     }
 
     #[inline]
-    pub fn update_font_texture(&mut self, mut ctx: &mut Context, key: &FontKey) {
+    pub fn update_font_texture(&mut self, ctx: Option<&mut Context>, key: &FontKey) {
         if let Some(index) = self.font_key_map.get(key) {
             if let Some(font) = self.fonts.get_mut(*index) {
                 if let Some(index) = self.texture_key_map.get(&font.font_texture_key) {
                     if let Some(font_texture) = self.textures.get_mut(*index) {
-                        font_texture.update(&mut ctx, &font.font_image);
+                        font_texture.update(ctx, &font.font_image);
                     }
                 }
             }
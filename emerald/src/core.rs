@@ -3,16 +3,24 @@ pub mod engine;
 pub mod error;
 pub mod game;
 pub mod game_settings;
+#[cfg(feature = "headless")]
+pub mod headless;
+mod time;
 
+pub use components::hierarchy::*;
 pub use components::transform::*;
 pub use components::*;
 pub use engine::GameEngine;
 pub use error::*;
 pub use game::*;
 pub use game_settings::*;
+#[cfg(feature = "headless")]
+pub use headless::*;
+pub(crate) use time::*;
 
 use crate::assets::*;
 use crate::audio::*;
+use crate::ent::EntComponentRegistry;
 use crate::input::*;
 use crate::logging::*;
 use crate::profiling::profile_cache::ProfileCache;
@@ -23,10 +31,12 @@ pub struct Emerald<'c> {
     delta: f32,
     fps: f64,
     audio_engine: &'c mut AudioEngine,
-    quad_ctx: &'c mut miniquad::Context,
+    /// `None` when running headless.
+    quad_ctx: Option<&'c mut miniquad::Context>,
     rendering_engine: &'c mut RenderingEngine,
     logging_engine: &'c mut LoggingEngine,
     input_engine: &'c mut InputEngine,
+    time_engine: &'c mut TimeEngine,
     pub(crate) asset_store: &'c mut AssetStore,
     profile_cache: &'c mut ProfileCache,
 }
@@ -35,11 +45,12 @@ impl<'c> Emerald<'c> {
     pub(crate) fn new(
         delta: f32,
         fps: f64,
-        quad_ctx: &'c mut miniquad::Context,
+        quad_ctx: Option<&'c mut miniquad::Context>,
         audio_engine: &'c mut AudioEngine,
         input_engine: &'c mut InputEngine,
         logging_engine: &'c mut LoggingEngine,
         rendering_engine: &'c mut RenderingEngine,
+        time_engine: &'c mut TimeEngine,
         asset_store: &'c mut AssetStore,
         profile_cache: &'c mut ProfileCache,
     ) -> Self {
@@ -50,6 +61,7 @@ impl<'c> Emerald<'c> {
             quad_ctx,
             rendering_engine,
             input_engine,
+            time_engine,
             logging_engine,
             asset_store,
             profile_cache,
@@ -72,6 +84,12 @@ impl<'c> Emerald<'c> {
         self.asset_store.get_user_data_folder_root()
     }
 
+    /// The custom ent components registered with `emd.loader().ent_components()`, to be given to
+    /// [`EntSaveConfig`](crate::ent::EntSaveConfig) and [`WorldSaveConfig`](crate::wrld::WorldSaveConfig).
+    pub fn ent_components(&self) -> &EntComponentRegistry {
+        &self.asset_store.ent_components
+    }
+
     // ************* General API ***************
     #[inline]
     pub fn delta(&self) -> f32 {
@@ -84,6 +102,51 @@ impl<'c> Emerald<'c> {
         self.delta = delta;
     }
 
+    /// Duration of every fixed step in seconds, the delta given to `Game::fixed_update`.
+    #[inline]
+    pub fn fixed_timestep(&self) -> f32 {
+        self.time_engine.fixed_timestep
+    }
+
+    #[inline]
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: f32) {
+        self.time_engine.fixed_timestep = fixed_timestep;
+    }
+
+    #[inline]
+    pub fn time_scale(&self) -> f32 {
+        self.time_engine.time_scale
+    }
+
+    /// Multiplies the delta of every following frame, 0.5 runs the game at half speed.
+    #[inline]
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_engine.time_scale = time_scale;
+    }
+
+    /// Stops time, `Game::update` keeps running with a delta of 0 while `Game::fixed_update` doesn't run at all.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.time_engine.paused = true;
+    }
+
+    #[inline]
+    pub fn resume(&mut self) {
+        self.time_engine.paused = false;
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.time_engine.paused
+    }
+
+    /// How far the current frame is between the last fixed step and the next one, from 0.0 to 1.0.
+    /// `draw_world` uses it to place entities that have a [`PreviousTransform`].
+    #[inline]
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.time_engine.interpolation_alpha()
+    }
+
     /// Time since Epoch
     #[inline]
     pub fn now(&self) -> f64 {
@@ -92,9 +155,17 @@ impl<'c> Emerald<'c> {
 
     #[inline]
     pub fn screen_size(&self) -> (f32, f32) {
-        let s = self.quad_ctx.screen_size();
-        let dpi = self.quad_ctx.dpi_scale();
-        (s.0 * dpi, s.1 * dpi)
+        match self.quad_ctx.as_deref() {
+            Some(ctx) => {
+                let s = ctx.screen_size();
+                let dpi = ctx.dpi_scale();
+                (s.0 * dpi, s.1 * dpi)
+            }
+            None => {
+                let (w, h) = self.rendering_engine.screen_size();
+                (w as f32, h as f32)
+            }
+        }
     }
 
     #[inline]
@@ -108,13 +179,15 @@ impl<'c> Emerald<'c> {
             self.audio_engine.clear().ok();
         }
 
-        self.quad_ctx.quit()
+        if let Some(ctx) = self.quad_ctx.as_deref_mut() {
+            ctx.quit()
+        }
     }
     // *****************************************
 
     pub fn graphics(&mut self) -> GraphicsHandler<'_> {
         GraphicsHandler::new(
-            &mut self.quad_ctx,
+            self.quad_ctx.as_deref_mut(),
             &mut self.asset_store,
             &mut self.rendering_engine,
         )
@@ -130,7 +203,7 @@ impl<'c> Emerald<'c> {
     #[inline]
     pub fn loader(&mut self) -> AssetLoader<'_> {
         AssetLoader::new(
-            &mut self.quad_ctx,
+            self.quad_ctx.as_deref_mut(),
             &mut self.asset_store,
             &mut self.rendering_engine,
             &mut self.audio_engine,
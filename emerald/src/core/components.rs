@@ -1,3 +1,4 @@
 pub mod autotilemap;
+pub mod hierarchy;
 pub mod tilemap;
 pub mod transform;
@@ -158,8 +158,8 @@ impl AutoTilemap {
         }
     }
 
-    /// Bakes the inner tileset in accordance to the Autotilemap
-    /// TODO: feature(physics): Additionally bakes the colliders
+    /// Bakes the inner tileset in accordance to the Autotilemap.
+    /// Colliders built with `world.physics().build_tilemap_colliders()` follow the baked tiles.
     pub fn bake(&mut self) -> Result<(), EmeraldError> {
         for x in 0..self.width() {
             for y in 0..self.height() {
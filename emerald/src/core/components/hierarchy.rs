@@ -0,0 +1,61 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::Transform;
+
+/// Attaches an entity to another. The `Transform` of an entity with a parent is relative to the parent.
+/// Managed through `World::set_parent` and `World::remove_parent`, which keep the parent's [`Children`] in sync.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parent {
+    pub(crate) entity: Entity,
+}
+impl Parent {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+}
+
+/// The entities attached to an entity, in the order they were attached.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Children {
+    pub(crate) entities: Vec<Entity>,
+}
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+}
+
+/// The world-space transform that the `Transform` of the entity is relative to,
+/// made of the transforms of all of its ancestors. `None` for entities without a parent.
+/// An ancestor without a `Transform` is treated as the identity.
+pub(crate) fn parent_global_transform(world: &hecs::World, entity: Entity) -> Option<Transform> {
+    let mut ancestor = world
+        .get::<Parent>(entity)
+        .ok()
+        .map(|parent| parent.entity)?;
+    let mut global_transform = Transform::default();
+
+    loop {
+        if let Ok(transform) = world.get::<Transform>(ancestor) {
+            global_transform = transform.mul_transform(&global_transform);
+        }
+
+        match world.get::<Parent>(ancestor) {
+            Ok(parent) => ancestor = parent.entity,
+            Err(_) => return Some(global_transform),
+        }
+    }
+}
@@ -1,14 +1,21 @@
 use crate::*;
+use serde::{Deserialize, Serialize};
 
 pub type TileId = usize;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Tilemap {
     pub(crate) width: usize,
     pub(crate) height: usize,
     pub(crate) tilesheet: TextureKey,
     pub(crate) tile_size: Vector2<usize>,
+    #[serde(
+        serialize_with = "serialize_tiles",
+        deserialize_with = "deserialize_tiles"
+    )]
     pub(crate) tiles: Vec<Option<TileId>>,
+    /// Incremented by every change to the tiles, so that what is built from them knows when to update.
+    pub(crate) revision: u64,
     pub z_index: f32,
     pub visible: bool,
 }
@@ -32,6 +39,7 @@ impl Tilemap {
             height,
             width,
             tiles,
+            revision: 0,
             z_index: 0.0,
             visible: true,
         }
@@ -65,6 +73,7 @@ impl Tilemap {
 
         if let Some(tile_id) = self.tiles.get_mut(tile_index) {
             *tile_id = new_tile;
+            self.revision += 1;
 
             return Ok(());
         }
@@ -117,3 +126,46 @@ pub(crate) fn get_tilemap_index(
 
     Ok((y * width) + x)
 }
+
+/// The tiles that are set, along with the amount of tiles,
+/// since formats like toml can't hold empty tiles in a list.
+#[derive(Serialize, Deserialize)]
+struct SparseTiles {
+    len: usize,
+    tiles: Vec<(usize, TileId)>,
+}
+
+fn serialize_tiles<S: serde::Serializer>(
+    tiles: &[Option<TileId>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    SparseTiles {
+        len: tiles.len(),
+        tiles: tiles
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tile)| tile.map(|tile_id| (index, tile_id)))
+            .collect(),
+    }
+    .serialize(serializer)
+}
+
+fn deserialize_tiles<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<TileId>>, D::Error> {
+    let sparse = SparseTiles::deserialize(deserializer)?;
+    let mut tiles = vec![None; sparse.len];
+    for (index, tile_id) in sparse.tiles {
+        match tiles.get_mut(index) {
+            Some(tile) => *tile = Some(tile_id),
+            None => {
+                return Err(serde::de::Error::custom(format!(
+                    "Tile {} is out of the {} tiles of the tilemap",
+                    index, sparse.len
+                )))
+            }
+        }
+    }
+
+    Ok(tiles)
+}
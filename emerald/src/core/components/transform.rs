@@ -1,9 +1,10 @@
 use glam::{vec2, Vec2};
 use nalgebra::{Isometry2, Translation2, Vector2};
 use nanoserde::DeJson;
+use serde::{Deserialize, Serialize};
 
 /// The core piece of an entity, determines it's transformative state and position in the world.
-#[derive(Clone, Copy, Debug, DeJson)]
+#[derive(Clone, Copy, Debug, DeJson, Serialize, Deserialize)]
 pub struct Transform {
     pub translation: Translation,
     pub rotation: f32,
@@ -16,6 +17,68 @@ impl Transform {
 
         transform
     }
+
+    /// Applies this transform to a transform that is relative to it.
+    /// Used to turn the local transform of a child into its global transform, given the global transform of its parent.
+    pub fn mul_transform(&self, local: &Transform) -> Transform {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = local.translation.x * self.scale.x;
+        let y = local.translation.y * self.scale.y;
+        let translation = Translation::new(
+            self.translation.x + x * cos - y * sin,
+            self.translation.y + x * sin + y * cos,
+        );
+
+        Transform {
+            translation,
+            rotation: self.rotation + local.rotation,
+            scale: Scale::new(self.scale.x * local.scale.x, self.scale.y * local.scale.y),
+        }
+    }
+
+    /// Turns a global transform back into one relative to this transform, undoing [`Transform::mul_transform`].
+    /// Axes scaled down to 0 can't be undone and come back as 0.
+    pub fn inverse_mul_transform(&self, global: &Transform) -> Transform {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = global.translation.x - self.translation.x;
+        let y = global.translation.y - self.translation.y;
+        let divide = |value: f32, by: f32| if by == 0.0 { 0.0 } else { value / by };
+
+        Transform {
+            translation: Translation::new(
+                divide(x * cos + y * sin, self.scale.x),
+                divide(y * cos - x * sin, self.scale.y),
+            ),
+            rotation: global.rotation - self.rotation,
+            scale: Scale::new(
+                divide(global.scale.x, self.scale.x),
+                divide(global.scale.y, self.scale.y),
+            ),
+        }
+    }
+
+    /// Blends from this transform to `other`, `t` going from 0.0 to 1.0.
+    /// Rotations are blended the short way around.
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        let mut rotation_delta = (other.rotation - self.rotation) % std::f32::consts::TAU;
+        if rotation_delta > std::f32::consts::PI {
+            rotation_delta -= std::f32::consts::TAU;
+        } else if rotation_delta < -std::f32::consts::PI {
+            rotation_delta += std::f32::consts::TAU;
+        }
+
+        Transform {
+            translation: Translation::new(
+                self.translation.x + (other.translation.x - self.translation.x) * t,
+                self.translation.y + (other.translation.y - self.translation.y) * t,
+            ),
+            rotation: self.rotation + rotation_delta * t,
+            scale: Scale::new(
+                self.scale.x + (other.scale.x - self.scale.x) * t,
+                self.scale.y + (other.scale.y - self.scale.y) * t,
+            ),
+        }
+    }
 }
 impl Default for Transform {
     fn default() -> Self {
@@ -57,7 +120,15 @@ impl std::ops::Add for Transform {
     }
 }
 
-#[derive(Clone, Copy, Debug, DeJson)]
+/// The `Transform` of an entity as of the previous fixed step, see [`crate::Game::fixed_update`].
+/// Entities holding one are drawn between their previous and current transform
+/// by the interpolation alpha of the frame, smoothing out movement done in fixed steps.
+/// It is refreshed at the start of every fixed step for the worlds returned by
+/// [`crate::Game::interpolated_worlds`], see [`crate::World::store_previous_transforms`].
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PreviousTransform(pub Transform);
+
+#[derive(Clone, Copy, Debug, DeJson, Serialize, Deserialize)]
 pub struct Scale {
     pub x: f32,
     pub y: f32,
@@ -93,7 +164,7 @@ impl std::ops::Add for Scale {
     }
 }
 
-#[derive(Clone, Copy, Debug, DeJson)]
+#[derive(Clone, Copy, Debug, DeJson, Serialize, Deserialize)]
 pub struct Translation {
     pub x: f32,
     pub y: f32,
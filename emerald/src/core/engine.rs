@@ -15,20 +15,39 @@ pub struct GameEngine {
     input_engine: InputEngine,
     logging_engine: LoggingEngine,
     rendering_engine: RenderingEngine,
+    time_engine: TimeEngine,
     last_instant: f64,
     fps_tracker: VecDeque<f64>,
     asset_store: AssetStore,
     profile_cache: ProfileCache,
 }
 impl<'c> GameEngine {
-    pub fn new(mut game: Box<dyn Game>, settings: GameSettings, mut ctx: &mut Context) -> Self {
-        let mut asset_store = AssetStore::new(ctx, settings.title.clone()).unwrap();
+    pub fn new(game: Box<dyn Game>, settings: GameSettings, ctx: &mut Context) -> Self {
+        Self::with_context(game, settings, Some(ctx))
+    }
+
+    /// Creates an engine that runs without a window, see [`crate::HeadlessRunner`].
+    #[cfg(feature = "headless")]
+    pub(crate) fn new_headless(game: Box<dyn Game>, settings: GameSettings) -> Self {
+        Self::with_context(game, settings, None)
+    }
+
+    fn with_context(
+        mut game: Box<dyn Game>,
+        settings: GameSettings,
+        mut ctx: Option<&mut Context>,
+    ) -> Self {
+        let mut asset_store = AssetStore::new(ctx.as_deref_mut(), settings.title.clone()).unwrap();
         let mut logging_engine = LoggingEngine::new();
         let mut audio_engine = AudioEngine::new();
         let mut input_engine = InputEngine::new();
-        let mut rendering_engine =
-            RenderingEngine::new(&mut ctx, settings.render_settings.clone(), &mut asset_store);
+        let mut rendering_engine = RenderingEngine::new(
+            ctx.as_deref_mut(),
+            settings.render_settings.clone(),
+            &mut asset_store,
+        );
 
+        let mut time_engine = TimeEngine::new(&settings.time_settings);
         let mut profile_cache = ProfileCache::new(Default::default());
 
         let delta = 0.0;
@@ -40,11 +59,12 @@ impl<'c> GameEngine {
         let emd = Emerald::new(
             delta,
             0.0,
-            &mut ctx,
+            ctx,
             &mut audio_engine,
             &mut input_engine,
             &mut logging_engine,
             &mut rendering_engine,
+            &mut time_engine,
             &mut asset_store,
             &mut profile_cache,
         );
@@ -59,6 +79,7 @@ impl<'c> GameEngine {
             input_engine,
             logging_engine,
             rendering_engine,
+            time_engine,
             last_instant,
             asset_store,
             profile_cache,
@@ -77,24 +98,54 @@ impl<'c> GameEngine {
         self.fps_tracker.pop_front();
         self.fps_tracker.push_back(delta);
     }
-}
-impl<'a, 'b> EventHandler for GameEngine {
+
+    #[cfg(feature = "headless")]
     #[inline]
-    fn update(&mut self, mut ctx: &mut Context) {
-        let start_of_frame = miniquad::date::now();
-        let delta = start_of_frame - self.last_instant;
-        self.last_instant = start_of_frame;
+    pub(crate) fn input_engine(&mut self) -> &mut InputEngine {
+        &mut self.input_engine
+    }
+
+    #[cfg(feature = "headless")]
+    #[inline]
+    pub(crate) fn screenshot(&self) -> Option<Screenshot> {
+        self.rendering_engine.screenshot(&self.asset_store)
+    }
 
+    /// Runs the fixed steps that fit in a frame that lasted `delta` seconds, then `Game::update`.
+    pub(crate) fn update_frame(&mut self, mut ctx: Option<&mut Context>, delta: f64) {
         self.update_fps_tracker(delta);
+        let (delta, fixed_steps) = self.time_engine.advance(delta);
+
+        for _ in 0..fixed_steps {
+            for world in self.game.interpolated_worlds() {
+                world.store_previous_transforms();
+            }
+
+            let emd = Emerald::new(
+                self.time_engine.fixed_timestep,
+                self.get_fps(),
+                ctx.as_deref_mut(),
+                &mut self.audio_engine,
+                &mut self.input_engine,
+                &mut self.logging_engine,
+                &mut self.rendering_engine,
+                &mut self.time_engine,
+                &mut self.asset_store,
+                &mut self.profile_cache,
+            );
+
+            self.game.fixed_update(emd);
+        }
 
         let emd = Emerald::new(
-            delta as f32,
+            delta,
             self.get_fps(),
-            &mut ctx,
+            ctx,
             &mut self.audio_engine,
             &mut self.input_engine,
             &mut self.logging_engine,
             &mut self.rendering_engine,
+            &mut self.time_engine,
             &mut self.asset_store,
             &mut self.profile_cache,
         );
@@ -105,6 +156,44 @@ impl<'a, 'b> EventHandler for GameEngine {
         self.audio_engine.post_update().unwrap();
     }
 
+    /// Runs `Game::draw`, then commits the frame when there is a window to present it to.
+    pub(crate) fn draw_frame(&mut self, mut ctx: Option<&mut Context>, delta: f64) {
+        self.rendering_engine
+            .pre_draw(ctx.as_deref_mut(), &mut self.asset_store)
+            .unwrap();
+        self.rendering_engine
+            .set_interpolation_alpha(self.time_engine.interpolation_alpha());
+        let emd = Emerald::new(
+            delta as f32,
+            self.get_fps(),
+            ctx.as_deref_mut(),
+            &mut self.audio_engine,
+            &mut self.input_engine,
+            &mut self.logging_engine,
+            &mut self.rendering_engine,
+            &mut self.time_engine,
+            &mut self.asset_store,
+            &mut self.profile_cache,
+        );
+
+        self.game.draw(emd);
+        if let Some(ctx) = ctx.as_deref_mut() {
+            ctx.commit_frame();
+        }
+
+        self.rendering_engine.post_draw(ctx, &mut self.asset_store);
+    }
+}
+impl<'a, 'b> EventHandler for GameEngine {
+    #[inline]
+    fn update(&mut self, ctx: &mut Context) {
+        let start_of_frame = miniquad::date::now();
+        let delta = start_of_frame - self.last_instant;
+        self.last_instant = start_of_frame;
+
+        self.update_frame(Some(ctx), delta);
+    }
+
     #[inline]
     fn key_down_event(
         &mut self,
@@ -146,28 +235,10 @@ impl<'a, 'b> EventHandler for GameEngine {
     }
 
     #[inline]
-    fn draw(&mut self, mut ctx: &mut Context) {
+    fn draw(&mut self, ctx: &mut Context) {
         let start_of_frame = miniquad::date::now();
         let delta = start_of_frame - self.last_instant;
 
-        self.rendering_engine
-            .pre_draw(ctx, &mut self.asset_store)
-            .unwrap();
-        let emd = Emerald::new(
-            delta as f32,
-            self.get_fps(),
-            &mut ctx,
-            &mut self.audio_engine,
-            &mut self.input_engine,
-            &mut self.logging_engine,
-            &mut self.rendering_engine,
-            &mut self.asset_store,
-            &mut self.profile_cache,
-        );
-
-        self.game.draw(emd);
-        ctx.commit_frame();
-
-        self.rendering_engine.post_draw(ctx, &mut self.asset_store);
+        self.draw_frame(Some(ctx), delta);
     }
 }
@@ -62,6 +62,22 @@ impl From<toml::de::Error> for EmeraldError {
     }
 }
 
+impl From<toml::ser::Error> for EmeraldError {
+    fn from(e: toml::ser::Error) -> EmeraldError {
+        EmeraldError {
+            message: format!("toml::ser::Error {:?}", &e.to_string()),
+        }
+    }
+}
+
+impl From<miniquad::ShaderError> for EmeraldError {
+    fn from(e: miniquad::ShaderError) -> EmeraldError {
+        EmeraldError {
+            message: format!("miniquad::ShaderError {:?}", &e.to_string()),
+        }
+    }
+}
+
 impl From<image::ImageError> for EmeraldError {
     fn from(e: image::ImageError) -> EmeraldError {
         EmeraldError {
@@ -2,6 +2,14 @@ use crate::*;
 
 pub trait Game {
     fn initialize(&mut self, mut _emd: Emerald<'_>) {}
+    /// Runs every `GameSettings::time_settings.fixed_timestep` of scaled time, zero or more times per frame
+    /// before `update`. Physics should be stepped here with `emd.delta()`.
+    fn fixed_update(&mut self, _emd: Emerald<'_>) {}
+    /// The worlds drawn with interpolation. Their [`PreviousTransform`]s are stored
+    /// with [`World::store_previous_transforms`] before every `fixed_update`.
+    fn interpolated_worlds(&mut self) -> Vec<&mut World> {
+        Vec::new()
+    }
     fn update(&mut self, _emd: Emerald<'_>) {}
     fn draw(&mut self, mut emd: Emerald<'_>) {
         emd.graphics().begin().unwrap();
@@ -4,12 +4,37 @@ use crate::*;
 pub struct GameSettings {
     pub title: String,
     pub render_settings: RenderSettings,
+    pub time_settings: TimeSettings,
 }
 impl Default for GameSettings {
     fn default() -> GameSettings {
         GameSettings {
             title: String::from("Emerald"),
             render_settings: RenderSettings::default(),
+            time_settings: TimeSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TimeSettings {
+    /// Duration in seconds of every `Game::fixed_update`.
+    pub fixed_timestep: f32,
+
+    /// Most fixed steps run in a single frame. After a long frame at most one more step
+    /// of the remaining time is carried over to the next frame and the rest is dropped,
+    /// slowing the game down instead of falling further behind.
+    pub max_fixed_steps_per_frame: u32,
+
+    /// Multiplies the delta of every frame, 0.5 runs the game at half speed.
+    pub time_scale: f32,
+}
+impl Default for TimeSettings {
+    fn default() -> TimeSettings {
+        TimeSettings {
+            fixed_timestep: 1.0 / 60.0,
+            max_fixed_steps_per_frame: 8,
+            time_scale: 1.0,
         }
     }
 }
@@ -0,0 +1,458 @@
+use crate::core::*;
+
+use miniquad::{KeyCode, MouseButton, TouchPhase};
+
+const DEFAULT_HEADLESS_DELTA: f64 = 1.0 / 60.0;
+
+/// Drives a game without a window, for testing game logic on machines without a display.
+///
+/// Every frame runs `Game::update` then `Game::draw` with a fixed delta.
+/// Draw commands are rasterized on the cpu, and input is injected
+/// through the runner instead of coming from window events.
+///
+/// ```ignore
+/// let mut runner = HeadlessRunner::new(Box::new(MyGame::default()), GameSettings::default());
+/// runner.press_key(KeyCode::Space);
+/// runner.run(60);
+/// assert!(runner.screenshot()?.matches_png("tests/golden/my_game.png", 0)?);
+/// ```
+pub struct HeadlessRunner {
+    engine: GameEngine,
+    delta: f64,
+    frame: usize,
+}
+impl HeadlessRunner {
+    /// Creates the engine and runs `Game::initialize`.
+    /// The screen is the size of `settings.render_settings.resolution`.
+    pub fn new(game: Box<dyn Game>, settings: GameSettings) -> Self {
+        HeadlessRunner {
+            engine: GameEngine::new_headless(game, settings),
+            delta: DEFAULT_HEADLESS_DELTA,
+            frame: 0,
+        }
+    }
+
+    /// Duration of every frame in seconds, defaults to 1/60th of a second.
+    #[inline]
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    #[inline]
+    pub fn set_delta(&mut self, delta: f64) {
+        self.delta = delta;
+    }
+
+    /// Amount of frames run so far.
+    #[inline]
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Runs a single update and draw.
+    pub fn step(&mut self) {
+        self.engine.update_frame(None, self.delta);
+        self.engine.draw_frame(None, self.delta);
+        self.frame += 1;
+    }
+
+    pub fn run(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// The last frame drawn to the screen, before post-processing.
+    pub fn screenshot(&self) -> Result<Screenshot, EmeraldError> {
+        match self.engine.screenshot() {
+            Some(screenshot) => Ok(screenshot),
+            None => Err(EmeraldError::new("Unable to retrieve the screen texture")),
+        }
+    }
+
+    // ************* Input ************* //
+    // Injected input is seen by the game during the next update.
+
+    #[inline]
+    pub fn press_key(&mut self, keycode: KeyCode) {
+        self.engine.input_engine().set_key_down(keycode, false);
+    }
+
+    #[inline]
+    pub fn release_key(&mut self, keycode: KeyCode) {
+        self.engine.input_engine().set_key_up(keycode);
+    }
+
+    /// Moves the mouse to a point in screen space, with the origin at the bottom left.
+    #[inline]
+    pub fn set_mouse_translation(&mut self, x: f32, y: f32) {
+        self.engine.input_engine().set_mouse_translation(x, y);
+    }
+
+    #[inline]
+    pub fn press_mouse_button(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.engine.input_engine().set_mouse_down(button, x, y);
+    }
+
+    #[inline]
+    pub fn release_mouse_button(&mut self, button: MouseButton, x: f32, y: f32) {
+        self.engine.input_engine().set_mouse_up(button, x, y);
+    }
+
+    #[inline]
+    pub fn touch(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        self.engine.input_engine().touch_event(phase, id, x, y);
+    }
+    // ********************************* //
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use miniquad::{KeyCode, MouseButton};
+
+    use crate::*;
+
+    #[derive(Default)]
+    struct Record {
+        initialized: bool,
+        updates: usize,
+        draws: usize,
+        elapsed: f32,
+        frames_space_pressed: Vec<usize>,
+        screen_size: (f32, f32),
+    }
+
+    struct RecordingGame {
+        record: Rc<RefCell<Record>>,
+        world: World,
+    }
+    impl Game for RecordingGame {
+        fn initialize(&mut self, emd: Emerald<'_>) {
+            self.record.borrow_mut().initialized = true;
+            self.world
+                .spawn((Transform::default(), ColorRect::new(WHITE, 16, 16)));
+            self.world.spawn((
+                Transform::default(),
+                Sprite::from_texture(TextureKey::default()),
+            ));
+            self.record.borrow_mut().screen_size = emd.screen_size();
+        }
+
+        fn update(&mut self, mut emd: Emerald<'_>) {
+            let mut record = self.record.borrow_mut();
+            if emd.input().is_key_just_pressed(KeyCode::Space) {
+                let frame = record.updates;
+                record.frames_space_pressed.push(frame);
+            }
+            record.updates += 1;
+            record.elapsed += emd.delta();
+        }
+
+        fn draw(&mut self, mut emd: Emerald<'_>) {
+            self.record.borrow_mut().draws += 1;
+            emd.graphics().begin().unwrap();
+            emd.graphics().draw_world(&mut self.world).unwrap();
+            emd.graphics()
+                .draw_circle(Vector2::new(8.0, 8.0), 4.0, WHITE, 0.0)
+                .unwrap();
+            emd.graphics().render().unwrap();
+        }
+    }
+
+    #[test]
+    fn runs_frames_with_injected_input() {
+        let record = Rc::new(RefCell::new(Record::default()));
+        let game = RecordingGame {
+            record: record.clone(),
+            world: World::new(),
+        };
+        let mut settings = GameSettings::default();
+        settings.render_settings.resolution = (320, 180);
+
+        let mut runner = HeadlessRunner::new(Box::new(game), settings);
+        runner.set_delta(0.5);
+        runner.run(2);
+        runner.press_key(KeyCode::Space);
+        runner.run(2);
+        runner.release_key(KeyCode::Space);
+        runner.step();
+
+        let record = record.borrow();
+        assert!(record.initialized);
+        assert_eq!(runner.frame(), 5);
+        assert_eq!(record.updates, 5);
+        assert_eq!(record.draws, 5);
+        assert_eq!(record.elapsed, 2.5);
+        assert_eq!(record.frames_space_pressed, vec![2]);
+        assert_eq!(record.screen_size, (320.0, 180.0));
+    }
+
+    #[derive(Default)]
+    struct FixedStepRecord {
+        fixed_updates: usize,
+        fixed_elapsed: f32,
+        elapsed: f32,
+    }
+
+    struct FixedStepGame {
+        record: Rc<RefCell<FixedStepRecord>>,
+    }
+    impl Game for FixedStepGame {
+        fn fixed_update(&mut self, emd: Emerald<'_>) {
+            let mut record = self.record.borrow_mut();
+            record.fixed_updates += 1;
+            record.fixed_elapsed += emd.delta();
+        }
+
+        fn update(&mut self, mut emd: Emerald<'_>) {
+            let mut record = self.record.borrow_mut();
+            record.elapsed += emd.delta();
+            if record.elapsed >= 1.0 {
+                emd.pause();
+            }
+        }
+    }
+
+    #[test]
+    fn runs_fixed_steps_at_scaled_time() {
+        let record = Rc::new(RefCell::new(FixedStepRecord::default()));
+        let game = FixedStepGame {
+            record: record.clone(),
+        };
+        let mut settings = GameSettings::default();
+        settings.time_settings.fixed_timestep = 0.25;
+        settings.time_settings.time_scale = 0.5;
+
+        let mut runner = HeadlessRunner::new(Box::new(game), settings);
+        runner.set_delta(1.0);
+        runner.run(4);
+
+        // Paused once a second of scaled time went by, after two frames.
+        let record = record.borrow();
+        assert_eq!(record.fixed_updates, 4);
+        assert_eq!(record.fixed_elapsed, 1.0);
+        assert_eq!(record.elapsed, 1.0);
+    }
+
+    struct MovingGame {
+        world: World,
+        entity: Entity,
+        previous_xs: Rc<RefCell<Vec<f32>>>,
+    }
+    impl Game for MovingGame {
+        fn fixed_update(&mut self, _emd: Emerald<'_>) {
+            let previous_transform = self.world.get::<PreviousTransform>(self.entity).unwrap();
+            self.previous_xs
+                .borrow_mut()
+                .push(previous_transform.0.translation.x);
+
+            self.world
+                .get_mut::<Transform>(self.entity)
+                .unwrap()
+                .translation
+                .x += 1.0;
+        }
+
+        fn interpolated_worlds(&mut self) -> Vec<&mut World> {
+            vec![&mut self.world]
+        }
+    }
+
+    #[test]
+    fn stores_previous_transforms_before_fixed_steps() {
+        let previous_xs = Rc::new(RefCell::new(Vec::new()));
+        let mut world = World::new();
+        let entity = world.spawn((
+            Transform::from_translation((10.0, 0.0)),
+            PreviousTransform::default(),
+        ));
+        let game = MovingGame {
+            world,
+            entity,
+            previous_xs: previous_xs.clone(),
+        };
+        let mut settings = GameSettings::default();
+        settings.time_settings.fixed_timestep = 0.25;
+
+        let mut runner = HeadlessRunner::new(Box::new(game), settings);
+        runner.set_delta(0.5);
+        runner.run(2);
+
+        assert_eq!(*previous_xs.borrow(), vec![10.0, 11.0, 12.0, 13.0]);
+    }
+
+    const RED: Color = Color {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+    const BLUE: Color = Color {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+
+    struct SceneGame {
+        world: World,
+    }
+    impl Game for SceneGame {
+        fn initialize(&mut self, mut emd: Emerald<'_>) {
+            emd.set_asset_folder_root(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets/").to_string(),
+            );
+            let bunny = emd.loader().sprite("bunny.png").unwrap();
+            let font = emd.loader().font("Roboto-Light.ttf", 16).unwrap();
+            self.world
+                .spawn((Transform::from_translation((40.0, -24.0)), bunny));
+            self.world.spawn((
+                Transform::from_translation((0.0, 32.0)),
+                Label::new("Emerald", font, 16),
+            ));
+
+            // Spawned first but drawn over the red rect, with its bottom left corner
+            // in the middle of the screen.
+            let mut blue_rect = ColorRect::new(BLUE, 8, 8);
+            blue_rect.centered = false;
+            blue_rect.z_index = 1.0;
+            self.world.spawn((Transform::default(), blue_rect));
+
+            self.world
+                .spawn((Transform::default(), ColorRect::new(RED, 8, 8)));
+            self.world.spawn((
+                Transform::from_translation((-16.0, 0.0)),
+                Sprite::from_texture(TextureKey::default()),
+            ));
+            self.world.spawn((
+                Transform::from_translation((1000.0, 0.0)),
+                ColorRect::new(WHITE, 8, 8),
+            ));
+        }
+
+        fn draw(&mut self, mut emd: Emerald<'_>) {
+            emd.graphics().begin().unwrap();
+            emd.graphics().draw_world(&mut self.world).unwrap();
+            emd.graphics().render().unwrap();
+        }
+    }
+
+    #[test]
+    fn renders_scene_in_software() {
+        let mut settings = GameSettings::default();
+        settings.render_settings.resolution = (128, 96);
+        let game = SceneGame {
+            world: World::new(),
+        };
+
+        let mut runner = HeadlessRunner::new(Box::new(game), settings);
+        runner.step();
+        let screenshot = runner.screenshot().unwrap();
+
+        assert_eq!((screenshot.width(), screenshot.height()), (128, 96));
+        assert_eq!(screenshot.get_pixel(0, 0), Some(CORNFLOWER_BLUE));
+        assert_eq!(screenshot.get_pixel(60, 44), Some(RED));
+        assert_eq!(screenshot.get_pixel(59, 44), Some(CORNFLOWER_BLUE));
+        assert_eq!(screenshot.get_pixel(63, 51), Some(RED));
+        assert_eq!(screenshot.get_pixel(64, 48), Some(BLUE));
+        assert_eq!(screenshot.get_pixel(71, 55), Some(BLUE));
+        assert_eq!(screenshot.get_pixel(72, 56), Some(CORNFLOWER_BLUE));
+        assert_eq!(screenshot.get_pixel(46, 46), Some(WHITE));
+        assert_eq!(screenshot.get_pixel(49, 49), Some(WHITE));
+        assert_eq!(screenshot.get_pixel(50, 50), Some(CORNFLOWER_BLUE));
+
+        // Set EMERALD_UPDATE_GOLDEN to regenerate the reference after an intended change.
+        let golden = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/golden/headless_scene.png"
+        );
+        if std::env::var_os("EMERALD_UPDATE_GOLDEN").is_some() {
+            screenshot.save_png(golden).unwrap();
+        }
+        assert!(screenshot.matches_png(golden, 0).unwrap());
+    }
+
+    struct LongLineGame;
+    impl Game for LongLineGame {
+        fn draw(&mut self, mut emd: Emerald<'_>) {
+            // Two vertices per point, more than a single batch can hold, ending at x = 60 on the screen.
+            let points = (0..5000)
+                .map(|i| Vector2::new(-300.0 + i as f32 * 0.072, 20.0))
+                .collect::<Vec<Vector2<f32>>>();
+
+            emd.graphics().begin().unwrap();
+            emd.graphics()
+                .draw_polyline(&points, 4.0, false, RED, 0.0)
+                .unwrap();
+            emd.graphics().render().unwrap();
+        }
+    }
+
+    #[test]
+    fn draws_geometry_larger_than_a_batch() {
+        let mut settings = GameSettings::default();
+        settings.render_settings.resolution = (128, 96);
+
+        let mut runner = HeadlessRunner::new(Box::new(LongLineGame), settings);
+        runner.step();
+        let screenshot = runner.screenshot().unwrap();
+
+        assert_eq!(screenshot.get_pixel(4, 20), Some(RED));
+        assert_eq!(screenshot.get_pixel(58, 20), Some(RED));
+        assert_eq!(screenshot.get_pixel(66, 20), Some(CORNFLOWER_BLUE));
+        assert_eq!(screenshot.get_pixel(30, 30), Some(CORNFLOWER_BLUE));
+    }
+
+    struct ButtonGame {
+        world: World,
+        button: Entity,
+        pressed: Rc<RefCell<Vec<bool>>>,
+    }
+    impl Game for ButtonGame {
+        fn update(&mut self, mut emd: Emerald<'_>) {
+            ui_button_system(&mut emd, &mut self.world);
+            let ui_button = self.world.get::<UIButton>(self.button).unwrap();
+            self.pressed.borrow_mut().push(ui_button.is_pressed());
+        }
+    }
+
+    #[test]
+    fn buttons_are_picked_at_their_global_transform() {
+        // The 4 by 4 default texture, scaled to 16 by 8 and turned upright by the parent,
+        // ends up 8 wide and 16 high centered on (50, 40).
+        let mut world = World::new();
+        let parent = world.spawn((Transform {
+            rotation: std::f32::consts::FRAC_PI_2,
+            scale: Scale::new(4.0, 2.0),
+            ..Transform::from_translation((50.0, 0.0))
+        },));
+        let button = world.spawn((
+            Transform::from_translation((10.0, 0.0)),
+            UIButton::new(TextureKey::default(), TextureKey::default()),
+        ));
+        world.set_parent(button, parent).unwrap();
+
+        let pressed = Rc::new(RefCell::new(Vec::new()));
+        let game = ButtonGame {
+            world,
+            button,
+            pressed: pressed.clone(),
+        };
+        let mut settings = GameSettings::default();
+        settings.render_settings.resolution = (320, 180);
+
+        // The screen is centered on the origin of the world.
+        let mut runner = HeadlessRunner::new(Box::new(game), settings);
+        runner.press_mouse_button(MouseButton::Left, 210.0, 137.0);
+        runner.step();
+        runner.set_mouse_translation(216.0, 130.0);
+        runner.step();
+        runner.set_mouse_translation(160.0, 90.0);
+        runner.step();
+
+        assert_eq!(*pressed.borrow(), vec![true, false, false]);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::core::*;
+
+/// Turns the delta of every frame into fixed steps, keeping track of the time left over.
+pub(crate) struct TimeEngine {
+    pub(crate) fixed_timestep: f32,
+    pub(crate) max_fixed_steps_per_frame: u32,
+    pub(crate) time_scale: f32,
+    pub(crate) paused: bool,
+    accumulator: f64,
+}
+impl TimeEngine {
+    pub(crate) fn new(settings: &TimeSettings) -> Self {
+        TimeEngine {
+            fixed_timestep: settings.fixed_timestep,
+            max_fixed_steps_per_frame: settings.max_fixed_steps_per_frame,
+            time_scale: settings.time_scale,
+            paused: false,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Scales the delta of a frame and accumulates it.
+    /// Returns the scaled delta and the amount of fixed steps to run this frame.
+    /// Past `max_fixed_steps_per_frame`, at most one step of the time left over is kept
+    /// for the next frame and the rest is dropped, so a long frame can't snowball into the next ones.
+    pub(crate) fn advance(&mut self, delta: f64) -> (f32, u32) {
+        if self.paused {
+            return (0.0, 0);
+        }
+
+        let delta = delta * self.time_scale as f64;
+        let fixed_timestep = self.fixed_timestep as f64;
+        if fixed_timestep <= 0.0 {
+            return (delta as f32, 0);
+        }
+
+        self.accumulator += delta;
+        let steps = ((self.accumulator / fixed_timestep).floor() as u32)
+            .min(self.max_fixed_steps_per_frame);
+        self.accumulator -= steps as f64 * fixed_timestep;
+        self.accumulator = self.accumulator.min(fixed_timestep);
+
+        (delta as f32, steps)
+    }
+
+    /// How far the accumulated time is between the last fixed step and the next one, from 0.0 to 1.0.
+    #[inline]
+    pub(crate) fn interpolation_alpha(&self) -> f32 {
+        if self.fixed_timestep <= 0.0 {
+            return 1.0;
+        }
+
+        (self.accumulator / self.fixed_timestep as f64).min(1.0) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TimeSettings;
+
+    use super::TimeEngine;
+
+    fn time_engine() -> TimeEngine {
+        TimeEngine::new(&TimeSettings {
+            fixed_timestep: 0.25,
+            max_fixed_steps_per_frame: 3,
+            time_scale: 1.0,
+        })
+    }
+
+    #[test]
+    fn accumulates_partial_steps() {
+        let mut time_engine = time_engine();
+
+        assert_eq!(time_engine.advance(0.125), (0.125, 0));
+        assert_eq!(time_engine.interpolation_alpha(), 0.5);
+        assert_eq!(time_engine.advance(0.5), (0.5, 2));
+        assert_eq!(time_engine.interpolation_alpha(), 0.5);
+    }
+
+    #[test]
+    fn keeps_at_most_one_step_beyond_the_catch_up_limit() {
+        let mut time_engine = time_engine();
+
+        assert_eq!(time_engine.advance(2.125), (2.125, 3));
+        assert_eq!(time_engine.interpolation_alpha(), 1.0);
+        assert_eq!(time_engine.advance(0.125), (0.125, 1));
+        assert_eq!(time_engine.interpolation_alpha(), 0.5);
+        assert_eq!(time_engine.advance(0.125), (0.125, 1));
+        assert_eq!(time_engine.interpolation_alpha(), 0.0);
+    }
+
+    #[test]
+    fn scales_and_pauses_time() {
+        let mut time_engine = time_engine();
+        time_engine.time_scale = 0.5;
+
+        assert_eq!(time_engine.advance(1.0), (0.5, 2));
+
+        time_engine.paused = true;
+        assert_eq!(time_engine.advance(1.0), (0.0, 0));
+        assert_eq!(time_engine.interpolation_alpha(), 0.0);
+    }
+}
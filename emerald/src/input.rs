@@ -15,21 +15,43 @@ pub use mouse_state::*;
 pub use systems::*;
 pub use touch_state::*;
 
-use crate::{transform::Translation, World};
+use crate::{
+    transform::{Transform, Translation},
+    Camera, World,
+};
 
 /// Returns a world translation equivalent to the given point on a given screen.
+/// Takes the position, rotation and zoom of the active camera into account.
 pub fn screen_translation_to_world_translation(
     screen_size: (u32, u32),
     screen_translation: &Translation,
     world: &mut World,
 ) -> Translation {
-    let camera_pos = world
-        .get_active_camera()
-        .and_then(|id| world.get_mut::<Translation>(id.clone()).ok())
-        .map(|pos| *pos)
-        .unwrap_or_default();
+    let (camera, camera_transform) = get_active_camera_and_transform(world);
+    let screen_size = (screen_size.0 as f32, screen_size.1 as f32);
+
+    camera.screen_to_world(&camera_transform, screen_size, screen_translation)
+}
+
+/// Returns the point on a given screen where the given world translation is drawn.
+pub fn world_translation_to_screen_translation(
+    screen_size: (u32, u32),
+    world_translation: &Translation,
+    world: &mut World,
+) -> Translation {
+    let (camera, camera_transform) = get_active_camera_and_transform(world);
+    let screen_size = (screen_size.0 as f32, screen_size.1 as f32);
 
-    // TODO(bombfuse): take the camera zoom level into account when translating
-    let screen_size = Translation::new(screen_size.0 as f32, screen_size.1 as f32);
-    Translation::from(screen_size * -0.5) + *screen_translation + camera_pos
+    camera.world_to_screen(&camera_transform, screen_size, world_translation)
+}
+
+fn get_active_camera_and_transform(world: &World) -> (Camera, Transform) {
+    world
+        .get_active_camera()
+        .and_then(|id| {
+            let camera = *world.get::<Camera>(id).ok()?;
+            let transform = world.global_transform(id).unwrap_or_default();
+            Some((camera, transform))
+        })
+        .unwrap_or_default()
 }
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use hecs::Entity;
+
 use crate::{
     screen_translation_to_world_translation,
     transform::{Transform, Translation},
@@ -29,7 +31,24 @@ pub fn ui_button_system(emd: &mut Emerald<'_>, world: &mut World) {
         })
         .collect();
 
-    for (_, (ui_button, transform)) in world.query::<(&mut UIButton, &Transform)>().iter() {
+    let buttons = world
+        .query::<&UIButton>()
+        .with::<Transform>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<Entity>>();
+
+    for entity in buttons {
+        // Buttons are drawn at their global transform, so they are picked there as well.
+        let transform = match world.global_transform(entity) {
+            Ok(transform) => transform,
+            Err(_) => continue,
+        };
+        let mut ui_button = match world.get_mut::<UIButton>(entity) {
+            Ok(ui_button) => ui_button,
+            Err(_) => continue,
+        };
+
         let button_check =
             is_translation_inside_button(emd, &ui_button, &transform, &mouse_position)
                 || check_touches_overlap_button(
@@ -77,33 +96,33 @@ fn check_touches_overlap_button(
     })
 }
 
-// TODO: take into account the scale and rotation of the button.
+/// Whether the translation is on the texture of the button, as drawn centered on its transform,
+/// rotated and scaled by it.
 fn is_translation_inside_button(
     emd: &mut Emerald<'_>,
     ui_button: &UIButton,
     ui_button_transform: &Transform,
     translation: &Translation,
 ) -> bool {
-    let mut is_inside = false;
-
     let texture_key = if ui_button.is_pressed() {
         &ui_button.pressed_texture
     } else {
         &ui_button.unpressed_texture
     };
 
-    if let Some(texture) = emd.asset_store.get_texture(texture_key) {
-        if (translation.x >= ui_button_transform.translation.x - texture.width as f32 / 2.0)
-            && (translation.x <= ui_button_transform.translation.x + texture.width as f32 / 2.0)
-        {
-            if (translation.y >= ui_button_transform.translation.y - texture.height as f32 / 2.0)
-                && (translation.y
-                    <= ui_button_transform.translation.y + texture.height as f32 / 2.0)
-            {
-                is_inside = true;
-            }
+    match emd.asset_store.get_texture(texture_key) {
+        Some(texture) => {
+            // The translation, brought into the unrotated space of the button.
+            let (sin, cos) = ui_button_transform.rotation.sin_cos();
+            let offset = *translation - ui_button_transform.translation;
+            let x = offset.x * cos + offset.y * sin;
+            let y = offset.y * cos - offset.x * sin;
+
+            let half_width = texture.width as f32 * ui_button_transform.scale.x.abs() / 2.0;
+            let half_height = texture.height as f32 * ui_button_transform.scale.y.abs() / 2.0;
+
+            x.abs() <= half_width && y.abs() <= half_height
         }
+        None => false,
     }
-
-    is_inside
 }
@@ -1,17 +1,37 @@
+mod batch;
 pub mod components;
 mod engine;
 mod font;
 mod handler;
+mod material;
+#[cfg(feature = "physics")]
+mod physics_debug;
+mod post_process;
+mod primitives;
+mod rasterizer;
 mod render_settings;
+#[cfg(feature = "headless")]
+mod screenshot;
 mod shaders;
 mod texture;
 
+pub(crate) use batch::*;
 pub use components::*;
 pub(crate) use engine::*;
 pub use font::*;
 pub use handler::*;
+pub use material::*;
 pub use miniquad::conf::Icon;
+pub use miniquad::UniformType;
+#[cfg(feature = "physics")]
+pub use physics_debug::*;
+pub use post_process::*;
+pub(crate) use primitives::*;
+pub(crate) use rasterizer::*;
 pub use render_settings::*;
+#[cfg(feature = "headless")]
+pub use screenshot::*;
 pub(crate) use shaders::*;
+pub use shaders::{FRAGMENT as DEFAULT_FRAGMENT_SHADER, VERTEX as DEFAULT_VERTEX_SHADER};
 pub use texture::TextureKey;
 pub(crate) use texture::*;
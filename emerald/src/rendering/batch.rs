@@ -0,0 +1,224 @@
+use crate::rendering::*;
+use crate::*;
+
+use glam::{vec2, vec3, Mat4, Vec4};
+use miniquad::{Bindings, Buffer, BufferType, Context, IndexType};
+
+/// Maximum amount of vertices in a single draw call, limited by the u16 indices.
+const MAX_VERTICES: usize = 8192;
+const MAX_INDICES: usize = MAX_VERTICES / 4 * 6;
+
+pub(crate) const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+/// Collects consecutive geometry that shares a texture and material into a single draw call.
+/// The rendering engine flushes the batch when either changes, when it is full, and at the end
+/// of every pass, so the order of draw commands is preserved.
+pub(crate) struct SpriteBatch {
+    /// Vertex and index buffers, created on the first flush so a batch can exist without a context.
+    buffers: Option<(Buffer, Buffer)>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    texture_key: Option<TextureKey>,
+    material_key: Option<MaterialKey>,
+}
+impl SpriteBatch {
+    pub(crate) fn new() -> Self {
+        SpriteBatch {
+            buffers: None,
+            vertices: Vec::with_capacity(MAX_VERTICES),
+            indices: Vec::with_capacity(MAX_INDICES),
+            texture_key: None,
+            material_key: None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    #[inline]
+    pub(crate) fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+
+    #[inline]
+    pub(crate) fn material_key(&self) -> Option<MaterialKey> {
+        self.material_key
+    }
+
+    /// Whether the geometry fits in an empty batch, larger geometry has to be split across batches.
+    #[inline]
+    pub(crate) fn fits(vertex_count: usize, index_count: usize) -> bool {
+        vertex_count <= MAX_VERTICES && index_count <= MAX_INDICES
+    }
+
+    /// Whether the batch has to be flushed before the given geometry can be pushed.
+    pub(crate) fn needs_flush(
+        &self,
+        texture_key: &TextureKey,
+        material_key: Option<MaterialKey>,
+        vertex_count: usize,
+        index_count: usize,
+    ) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        self.texture_key.as_ref() != Some(texture_key)
+            || self.material_key != material_key
+            || self.vertices.len() + vertex_count > MAX_VERTICES
+            || self.indices.len() + index_count > MAX_INDICES
+    }
+
+    /// Vertices of a textured quad, the unit square transformed by the model matrix.
+    /// `source` is the region of the texture to sample, in texture coordinates.
+    pub(crate) fn quad(model: &Mat4, source: &Rectangle, color: Color) -> [Vertex; 4] {
+        let (r, g, b, a) = color.to_percentage();
+        let color = Vec4::new(r, g, b, a);
+
+        let corners = [
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+        ];
+        let mut vertices = [Vertex {
+            position: vec2(0.0, 0.0),
+            uv: vec2(0.0, 0.0),
+            color,
+        }; 4];
+        for (vertex, corner) in vertices.iter_mut().zip(corners.iter()) {
+            vertex.position = model
+                .transform_point3(vec3(corner.x, corner.y, 0.0))
+                .truncate();
+            vertex.uv = vec2(
+                corner.x * source.width + source.x,
+                corner.y * source.height + source.y,
+            );
+        }
+
+        vertices
+    }
+
+    /// Pushes triangles, the indices are relative to the given vertices.
+    /// Callers are expected to check [`SpriteBatch::needs_flush`] beforehand,
+    /// and to split geometry that doesn't [fit](SpriteBatch::fits) in a single batch.
+    pub(crate) fn push(
+        &mut self,
+        texture_key: &TextureKey,
+        material_key: Option<MaterialKey>,
+        vertices: &[Vertex],
+        indices: &[u16],
+    ) {
+        debug_assert!(
+            self.vertices.len() + vertices.len() <= MAX_VERTICES
+                && self.indices.len() + indices.len() <= MAX_INDICES,
+            "Pushed geometry overflows the batch"
+        );
+
+        if self.is_empty() {
+            self.texture_key = Some(texture_key.clone());
+            self.material_key = material_key;
+        }
+
+        let index_offset = self.vertices.len() as u16;
+        self.vertices.extend_from_slice(vertices);
+        self.indices
+            .extend(indices.iter().map(|index| index + index_offset));
+    }
+
+    /// Draws everything collected so far with the currently applied pipeline.
+    /// `uniforms` must match the uniform layout of that pipeline's shader,
+    /// and `textures` the samplers it declares after `tex`.
+    pub(crate) fn flush(
+        &mut self,
+        ctx: &mut Context,
+        asset_store: &mut AssetStore,
+        uniforms: &[f32],
+        textures: &[TextureKey],
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        if let Some(texture) = self
+            .texture_key
+            .as_ref()
+            .and_then(|key| asset_store.get_texture(key))
+        {
+            let (vertex_buffer, index_buffer) = *self.buffers.get_or_insert_with(|| {
+                (
+                    Buffer::stream(
+                        ctx,
+                        BufferType::VertexBuffer,
+                        MAX_VERTICES * std::mem::size_of::<Vertex>(),
+                    ),
+                    Buffer::index_stream(
+                        ctx,
+                        IndexType::Short,
+                        MAX_INDICES * std::mem::size_of::<u16>(),
+                    ),
+                )
+            });
+            vertex_buffer.update(ctx, &self.vertices);
+            index_buffer.update(ctx, &self.indices);
+
+            texture.inner.set_filter(ctx, texture.filter);
+            let mut images = vec![texture.inner];
+            for key in textures {
+                let extra = asset_store
+                    .get_texture(key)
+                    .or_else(|| asset_store.get_texture(&TextureKey::default()));
+                if let Some(extra) = extra {
+                    extra.inner.set_filter(ctx, extra.filter);
+                    images.push(extra.inner);
+                }
+            }
+
+            ctx.apply_bindings(&Bindings {
+                vertex_buffers: vec![vertex_buffer],
+                index_buffer,
+                images,
+            });
+            ctx.apply_uniforms_from_bytes(
+                uniforms.as_ptr() as *const u8,
+                std::mem::size_of_val(uniforms),
+            );
+            ctx.draw(0, self.indices.len() as i32, 1);
+        }
+
+        self.clear();
+    }
+
+    /// Draws everything collected so far into the pixels of a headless render target,
+    /// the software counterpart of [`SpriteBatch::flush`].
+    pub(crate) fn rasterize(&mut self, asset_store: &mut AssetStore, target_key: &TextureKey) {
+        if self.is_empty() {
+            return;
+        }
+
+        // The target's pixels are taken out while drawing, as the texture lives in the same store.
+        let target = asset_store.get_texture_mut(target_key).and_then(|target| {
+            let size = (target.width as usize, target.height as usize);
+            target.pixels.take().map(|pixels| (pixels, size))
+        });
+
+        if let Some((mut pixels, size)) = target {
+            if let Some(texture) = self
+                .texture_key
+                .as_ref()
+                .and_then(|key| asset_store.get_texture(key))
+            {
+                rasterize_triangles(&mut pixels, size, texture, &self.vertices, &self.indices);
+            }
+
+            if let Some(target) = asset_store.get_texture_mut(target_key) {
+                target.pixels = Some(pixels);
+            }
+        }
+
+        self.clear();
+    }
+}
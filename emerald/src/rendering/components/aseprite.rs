@@ -23,6 +23,8 @@ pub struct Aseprite {
     pub visible: bool,
     pub color: Color,
     pub centered: bool,
+    /// Drawn with the shader of this material instead of the default one.
+    pub material: Option<MaterialKey>,
     pub z_index: f32,
 }
 impl Aseprite {
@@ -31,7 +33,7 @@ impl Aseprite {
     }
 
     pub(crate) fn new(
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
         path: &str,
         data: Vec<u8>,
@@ -43,11 +45,12 @@ impl Aseprite {
 
     pub(crate) fn from_exported(
         sprite: Sprite,
+        animation_path: &str,
         animation_json: Vec<u8>,
     ) -> Result<Self, EmeraldError> {
         let animation_json = std::str::from_utf8(&animation_json)?;
         let json_data: json_types::AsepriteData = DeJson::deserialize_json(animation_json)?;
-        let data = AsepriteData::from_sprite_and_json(sprite, json_data)?;
+        let data = AsepriteData::from_sprite_and_json(sprite, animation_path, json_data)?;
         Ok(Self::from_data(data))
     }
 
@@ -66,6 +69,7 @@ impl Aseprite {
             offset: Vector2::new(0.0, 0.0),
             color: WHITE,
             centered: true,
+            material: None,
             z_index: 0.0,
             visible: true,
         }
@@ -378,7 +382,7 @@ struct Frame {
 
 impl Frame {
     fn from_asefile(
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
         path: &str,
         frame_index: u32,
@@ -426,11 +430,16 @@ impl Frame {
 pub(crate) struct AsepriteData {
     frames: Vec<Frame>,
     tags: Vec<Tag>,
+
+    /// Path of the `.aseprite` file, or of the sprite sheet when loaded from an export.
+    pub(crate) path: String,
+    /// Path of the exported animations json, if any.
+    pub(crate) animation_path: Option<String>,
 }
 
 impl AsepriteData {
     fn from_asefile(
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
         path: &str,
         aseprite: asefile::AsepriteFile,
@@ -438,7 +447,7 @@ impl AsepriteData {
         let frames: Vec<Frame> = (0..aseprite.num_frames())
             .map(|frame_index| {
                 let frame = aseprite.frame(frame_index);
-                Frame::from_asefile(ctx, asset_store, path, frame_index, frame)
+                Frame::from_asefile(ctx.as_deref_mut(), asset_store, path, frame_index, frame)
             })
             .collect::<Result<_, EmeraldError>>()?;
 
@@ -446,13 +455,20 @@ impl AsepriteData {
             .map(|i| Tag::from_asefile(aseprite.tag(i), &frames))
             .collect();
 
-        Ok(Self { frames, tags })
+        Ok(Self {
+            frames,
+            tags,
+            path: path.to_string(),
+            animation_path: None,
+        })
     }
 
     fn from_sprite_and_json(
         sprite: Sprite,
+        animation_path: &str,
         json_data: json_types::AsepriteData,
     ) -> Result<Self, EmeraldError> {
+        let path = sprite.texture_key.get_name();
         let sheet_size = &json_data.meta.size;
         let frames: Vec<Frame> = json_data
             .frames
@@ -467,6 +483,11 @@ impl AsepriteData {
             .map(|tag| Tag::from_json(tag, &frames))
             .collect::<Result<_, _>>()?;
 
-        Ok(Self { frames, tags })
+        Ok(Self {
+            frames,
+            tags,
+            path,
+            animation_path: Some(animation_path.to_string()),
+        })
     }
 }
@@ -4,9 +4,63 @@ use crate::*;
 pub struct Camera {
     pub offset: Vector2<f32>,
     pub centered: bool,
+    /// Magnification of the view, values above 1.0 zoom in.
     pub zoom: f32,
     pub(crate) is_active: bool,
 }
+impl Camera {
+    /// The transform that maps a world space transform onto the screen.
+    /// `camera_transform` is the global transform of the entity holding the camera, its rotation rotates the view.
+    pub fn view_transform(
+        &self,
+        camera_transform: &Transform,
+        screen_size: (f32, f32),
+    ) -> Transform {
+        let mut translation = Translation::from(self.offset);
+        if self.centered {
+            translation += Translation::new(screen_size.0 / 2.0, screen_size.1 / 2.0);
+        }
+
+        let view = Transform {
+            translation,
+            rotation: -camera_transform.rotation,
+            scale: Scale::new(self.zoom, self.zoom),
+        };
+
+        view.mul_transform(&Transform::from_translation(
+            camera_transform.translation * -1.0,
+        ))
+    }
+
+    pub fn world_to_screen(
+        &self,
+        camera_transform: &Transform,
+        screen_size: (f32, f32),
+        world_translation: &Translation,
+    ) -> Translation {
+        self.view_transform(camera_transform, screen_size)
+            .mul_transform(&Transform::from_translation(*world_translation))
+            .translation
+    }
+
+    pub fn screen_to_world(
+        &self,
+        camera_transform: &Transform,
+        screen_size: (f32, f32),
+        screen_translation: &Translation,
+    ) -> Translation {
+        let mut view_translation = *screen_translation - Translation::from(self.offset);
+        if self.centered {
+            view_translation -= Translation::new(screen_size.0 / 2.0, screen_size.1 / 2.0);
+        }
+
+        let (sin, cos) = camera_transform.rotation.sin_cos();
+        let x = view_translation.x / self.zoom;
+        let y = view_translation.y / self.zoom;
+
+        camera_transform.translation + Translation::new(x * cos - y * sin, x * sin + y * cos)
+    }
+}
 impl Default for Camera {
     fn default() -> Camera {
         Camera {
@@ -17,3 +71,40 @@ impl Default for Camera {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Camera, Transform, Translation};
+
+    #[test]
+    fn zoom_scales_distance_from_camera() {
+        let mut camera = Camera::default();
+        camera.zoom = 2.0;
+        let camera_transform = Transform::from_translation((10.0, 10.0));
+
+        let screen = camera.world_to_screen(
+            &camera_transform,
+            (800.0, 600.0),
+            &Translation::new(20.0, 10.0),
+        );
+
+        assert!((screen.x - 420.0).abs() < 0.001);
+        assert!((screen.y - 300.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn screen_to_world_inverts_world_to_screen() {
+        let mut camera = Camera::default();
+        camera.zoom = 3.0;
+        camera.offset.x = 15.0;
+        let mut camera_transform = Transform::from_translation((-40.0, 25.0));
+        camera_transform.rotation = 0.7;
+        let world_translation = Translation::new(12.0, -7.0);
+
+        let screen = camera.world_to_screen(&camera_transform, (640.0, 480.0), &world_translation);
+        let back = camera.screen_to_world(&camera_transform, (640.0, 480.0), &screen);
+
+        assert!((back.x - world_translation.x).abs() < 0.001);
+        assert!((back.y - world_translation.y).abs() < 0.001);
+    }
+}
@@ -9,6 +9,8 @@ pub struct ColorRect {
     pub height: u32,
     pub centered: bool,
     pub rotation: f32,
+    /// Drawn with the shader of this material instead of the default one.
+    pub material: Option<MaterialKey>,
     pub z_index: f32,
 }
 impl ColorRect {
@@ -31,6 +33,7 @@ impl Default for ColorRect {
             height: 32,
             centered: true,
             rotation: 0.0,
+            material: None,
             z_index: 0.0,
         }
     }
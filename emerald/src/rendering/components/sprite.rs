@@ -10,6 +10,8 @@ pub struct Sprite {
     pub color: Color,
     pub centered: bool,
     pub(crate) texture_key: TextureKey,
+    /// Drawn with the shader of this material instead of the default one.
+    pub material: Option<MaterialKey>,
     pub z_index: f32,
 }
 impl Sprite {
@@ -30,6 +32,7 @@ impl Default for Sprite {
             color: WHITE,
             centered: true,
             texture_key: TextureKey::default(),
+            material: None,
             z_index: 0.0,
             visible: true,
         }
@@ -7,7 +7,7 @@ use crate::*;
 use crate::{rendering::components::*, transform::Translation};
 
 use fontdue::layout::{CoordinateSystem, Layout, LayoutSettings, TextStyle};
-use glam::{vec2, Mat4, Vec2, Vec4};
+use glam::{vec2, vec3, Mat4, Quat, Vec2, Vec4};
 use miniquad::*;
 use std::collections::{HashMap, VecDeque};
 
@@ -20,6 +20,12 @@ const EMERALD_DEFAULT_RENDER_TARGET: &str = "emerald_default_render_target";
 pub(crate) struct RenderingEngine {
     pub(crate) settings: RenderSettings,
     pipelines: HashMap<String, Pipeline>,
+    shader_uniforms: HashMap<ShaderKey, Vec<(String, UniformType)>>,
+    /// Samplers of every shader following `tex`.
+    shader_textures: HashMap<ShaderKey, Vec<String>>,
+    materials: HashMap<MaterialKey, Material>,
+    material_counter: usize,
+    post_process: Vec<MaterialKey>,
     layout: Layout,
     render_texture_counter: usize,
     last_screen_size: (usize, usize),
@@ -27,67 +33,65 @@ pub(crate) struct RenderingEngine {
     render_passes: HashMap<TextureKey, RenderPass>,
     current_render_texture_key: TextureKey,
     current_resolution: (usize, usize),
+    batch: SpriteBatch,
+    interpolation_alpha: f32,
 
     draw_queue: VecDeque<DrawCommand>,
 }
 impl RenderingEngine {
+    /// Without a context, as in headless mode, the screen is the size of `settings.resolution`
+    /// and draw commands are consumed without reaching the GPU.
     pub(crate) fn new(
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         settings: RenderSettings,
         asset_store: &mut AssetStore,
     ) -> Self {
         let mut pipelines = HashMap::new();
+        let mut render_passes = HashMap::new();
 
-        let shader = Shader::new(ctx, VERTEX, FRAGMENT, shaders::meta()).unwrap();
-        let params = PipelineParams {
-            depth_write: true,
-            color_blend: Some(BlendState::new(
-                Equation::Add,
-                BlendFactor::Value(BlendValue::SourceAlpha),
-                BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
-            )),
-            alpha_blend: Some(BlendState::new(
-                Equation::Add,
-                BlendFactor::Zero,
-                BlendFactor::One,
-            )),
-            ..Default::default()
+        let current_resolution = match ctx.as_deref_mut() {
+            Some(ctx) => current_window_resolution(ctx),
+            None => (
+                settings.resolution.0 as usize,
+                settings.resolution.1 as usize,
+            ),
         };
 
-        let texture_pipeline = Pipeline::with_params(
-            ctx,
-            &[BufferLayout::default()],
-            &[VertexAttribute::new("position", VertexFormat::Float2)],
-            shader,
-            params,
-        );
-
-        pipelines.insert(EMERALD_TEXTURE_PIPELINE_NAME.to_string(), texture_pipeline);
-
         let mut render_texture_counter = 0;
         let key = TextureKey::new(String::from(EMERALD_DEFAULT_RENDER_TARGET));
-        let current_resolution = current_window_resolution(ctx);
         let screen_texture_key = create_render_texture(
             current_resolution.0,
             current_resolution.1,
             key,
-            ctx,
+            ctx.as_deref_mut(),
             asset_store,
         )
         .unwrap();
         render_texture_counter += 1;
 
-        let texture = asset_store.get_texture(&screen_texture_key).unwrap();
+        if let Some(ctx) = ctx {
+            let shader = Shader::new(ctx, VERTEX, FRAGMENT, shaders::meta()).unwrap();
+            let texture_pipeline = create_pipeline(ctx, shader);
+            pipelines.insert(EMERALD_TEXTURE_PIPELINE_NAME.to_string(), texture_pipeline);
+
+            let texture = asset_store.get_texture(&screen_texture_key).unwrap();
+            render_passes.insert(
+                screen_texture_key.clone(),
+                RenderPass::new(ctx, texture.inner, None),
+            );
+        }
+
         let current_render_texture_key = screen_texture_key.clone();
-        let mut render_passes = HashMap::new();
-        render_passes.insert(
-            screen_texture_key.clone(),
-            RenderPass::new(ctx, texture.inner, None),
-        );
+        let batch = SpriteBatch::new();
 
         RenderingEngine {
             settings,
             pipelines,
+            shader_uniforms: HashMap::new(),
+            shader_textures: HashMap::new(),
+            materials: HashMap::new(),
+            material_counter: 0,
+            post_process: Vec::new(),
             layout: Layout::new(CoordinateSystem::PositiveYDown),
             render_texture_counter,
             render_passes,
@@ -95,6 +99,8 @@ impl RenderingEngine {
             screen_texture_key,
             current_render_texture_key,
             current_resolution,
+            batch,
+            interpolation_alpha: 1.0,
             draw_queue: VecDeque::new(),
         }
     }
@@ -104,7 +110,7 @@ impl RenderingEngine {
         &mut self,
         w: usize,
         h: usize,
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<TextureKey, EmeraldError> {
         self.render_texture_counter += 1;
@@ -116,12 +122,161 @@ impl RenderingEngine {
         create_render_texture(w, h, key, ctx, asset_store)
     }
 
+    /// Without a context the shader is not compiled, materials using it can still be created.
+    pub(crate) fn create_shader(
+        &mut self,
+        ctx: Option<&mut Context>,
+        name: &str,
+        vertex: &str,
+        fragment: &str,
+        uniforms: &[(&str, UniformType)],
+        textures: &[&str],
+    ) -> Result<ShaderKey, EmeraldError> {
+        if self.shader_uniforms.contains_key(&ShaderKey::new(name)) {
+            return Err(EmeraldError::new(format!(
+                "A shader named {:?} already exists.",
+                name
+            )));
+        }
+
+        let mut shader_uniforms = Vec::with_capacity(uniforms.len());
+        for (uniform_name, uniform_type) in uniforms {
+            if uniform_float_count(*uniform_type).is_none() {
+                return Err(EmeraldError::new(format!(
+                    "Uniform {:?} is a {:?}, which materials do not support.",
+                    uniform_name, uniform_type
+                )));
+            }
+
+            shader_uniforms.push((uniform_name.to_string(), *uniform_type));
+        }
+
+        let mut shader_textures: Vec<String> = Vec::with_capacity(textures.len());
+        for texture_name in textures {
+            if *texture_name == "tex" || shader_textures.iter().any(|n| n == texture_name) {
+                return Err(EmeraldError::new(format!(
+                    "Shader {:?} declares the texture {:?} more than once.",
+                    name, texture_name
+                )));
+            }
+
+            shader_textures.push(texture_name.to_string());
+        }
+
+        if let Some(ctx) = ctx {
+            let meta = meta_with_uniforms(&shader_uniforms, &shader_textures);
+            let shader = Shader::new(ctx, vertex, fragment, meta)?;
+            self.pipelines
+                .insert(name.to_string(), create_pipeline(ctx, shader));
+        }
+
+        let key = ShaderKey::new(name);
+        self.shader_uniforms.insert(key.clone(), shader_uniforms);
+        self.shader_textures.insert(key.clone(), shader_textures);
+
+        Ok(key)
+    }
+
+    pub(crate) fn create_material(
+        &mut self,
+        shader_key: &ShaderKey,
+    ) -> Result<MaterialKey, EmeraldError> {
+        let uniforms = match self.shader_uniforms.get(shader_key) {
+            Some(uniforms) => uniforms,
+            None => {
+                return Err(EmeraldError::new(format!(
+                    "Unable to find shader {:?}",
+                    shader_key.get_name()
+                )))
+            }
+        };
+
+        self.material_counter += 1;
+        let key = MaterialKey(self.material_counter);
+        let textures = self
+            .shader_textures
+            .get(shader_key)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        self.materials
+            .insert(key, Material::new(shader_key.clone(), uniforms, textures));
+
+        Ok(key)
+    }
+
+    #[inline]
+    pub(crate) fn get_material(&self, key: MaterialKey) -> Option<&Material> {
+        self.materials.get(&key)
+    }
+
+    #[inline]
+    pub(crate) fn get_material_mut(&mut self, key: MaterialKey) -> Option<&mut Material> {
+        self.materials.get_mut(&key)
+    }
+
+    #[inline]
+    pub(crate) fn remove_material(&mut self, key: MaterialKey) -> Option<Material> {
+        self.materials.remove(&key)
+    }
+
+    pub(crate) fn create_post_process_effect(
+        &mut self,
+        ctx: Option<&mut Context>,
+        effect: PostProcessEffect,
+    ) -> Result<MaterialKey, EmeraldError> {
+        let shader_key = ShaderKey::new(effect.shader_name());
+        if !self.shader_uniforms.contains_key(&shader_key) {
+            self.create_shader(
+                ctx,
+                effect.shader_name(),
+                VERTEX,
+                effect.fragment_shader(),
+                &effect.uniforms(),
+                &effect.textures(),
+            )?;
+        }
+
+        let material_key = self.create_material(&shader_key)?;
+        if let Some(material) = self.materials.get_mut(&material_key) {
+            for (name, value) in effect.default_uniforms() {
+                material.set_uniform(name, value)?;
+            }
+        }
+
+        Ok(material_key)
+    }
+
+    /// Appends a stage to the post-process stack, stages are applied in the order they were added.
+    #[inline]
+    pub(crate) fn add_post_process(&mut self, material_key: MaterialKey) {
+        self.post_process.push(material_key);
+    }
+
+    #[inline]
+    pub(crate) fn remove_post_process(&mut self, material_key: MaterialKey) -> bool {
+        let len = self.post_process.len();
+        self.post_process.retain(|key| *key != material_key);
+
+        len != self.post_process.len()
+    }
+
+    #[inline]
+    pub(crate) fn clear_post_process(&mut self) {
+        self.post_process.clear();
+    }
+
     #[inline]
     pub(crate) fn pre_draw(
         &mut self,
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<(), EmeraldError> {
+        // Without a window the screen never changes size.
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => return Ok(()),
+        };
+
         let (w, h) = current_window_resolution(ctx);
         let (prev_w, prev_h) = self.last_screen_size;
 
@@ -148,14 +303,37 @@ impl RenderingEngine {
         }
 
         let screen_texture_key =
-            create_render_texture(w as usize, h as usize, key, ctx, asset_store)?;
+            create_render_texture(w as usize, h as usize, key, Some(ctx), asset_store)?;
 
         Ok(screen_texture_key)
     }
 
+    /// The last frame drawn to the screen, only available when rendering headless.
+    #[cfg(feature = "headless")]
+    pub(crate) fn screenshot(&self, asset_store: &AssetStore) -> Option<Screenshot> {
+        asset_store
+            .get_texture(&self.screen_texture_key)
+            .and_then(Screenshot::from_texture)
+    }
+
+    /// How far the drawn frame is between the previous and the current fixed step,
+    /// used to interpolate entities that have a `PreviousTransform`.
+    #[inline]
+    pub(crate) fn set_interpolation_alpha(&mut self, interpolation_alpha: f32) {
+        self.interpolation_alpha = interpolation_alpha;
+    }
+
+    /// Size of the screen as of the last frame.
     #[inline]
-    pub(crate) fn post_draw(&mut self, ctx: &mut Context, _asset_store: &mut AssetStore) {
-        self.last_screen_size = current_window_resolution(ctx);
+    pub(crate) fn screen_size(&self) -> (usize, usize) {
+        self.last_screen_size
+    }
+
+    #[inline]
+    pub(crate) fn post_draw(&mut self, ctx: Option<&mut Context>, _asset_store: &mut AssetStore) {
+        if let Some(ctx) = ctx {
+            self.last_screen_size = current_window_resolution(ctx);
+        }
     }
 
     #[inline]
@@ -168,7 +346,8 @@ impl RenderingEngine {
             self.current_resolution.0 as f32,
             self.current_resolution.1 as f32,
         );
-        let (camera, camera_transform) = get_camera_and_camera_transform(world);
+        let (camera, camera_transform) =
+            get_camera_and_camera_transform(world, self.interpolation_alpha);
         let mut draw_queue = Vec::new();
 
         let cmd_adder = DrawCommandAdder::new(self, world);
@@ -185,22 +364,9 @@ impl RenderingEngine {
 
         draw_queue.sort_by(|a, b| a.z_index.partial_cmp(&b.z_index).unwrap());
 
+        let view_transform = camera.view_transform(&camera_transform, screen_size);
         for mut draw_command in draw_queue {
-            let translation = {
-                let mut translation =
-                    draw_command.transform.translation - camera_transform.translation;
-
-                if camera.centered {
-                    translation =
-                        translation + Translation::new(screen_size.0 / 2.0, screen_size.1 / 2.0);
-                }
-
-                translation += Translation::from(camera.offset);
-
-                translation
-            };
-
-            draw_command.transform.translation = translation;
+            draw_command.transform = view_transform.mul_transform(&draw_command.transform);
             self.push_draw_command(draw_command)?;
         }
 
@@ -213,55 +379,133 @@ impl RenderingEngine {
         &mut self,
         world: &mut World,
         collider_color: Color,
+    ) -> Result<(), EmeraldError> {
+        let settings = ColliderDebugSettings {
+            collider_color,
+            ..Default::default()
+        };
+
+        self.draw_colliders_with_settings(world, &settings)
+    }
+
+    #[cfg(feature = "physics")]
+    pub fn draw_colliders_with_settings(
+        &mut self,
+        world: &World,
+        settings: &ColliderDebugSettings,
     ) -> Result<(), EmeraldError> {
         let screen_size = (
             self.current_resolution.0 as f32,
             self.current_resolution.1 as f32,
         );
-        let mut color_rect = ColorRect {
-            color: collider_color,
-            ..Default::default()
-        };
-        color_rect.color = collider_color;
-        let (camera, camera_transform) = get_camera_and_camera_transform(world);
-
-        for (_id, body_handle) in world.inner.query::<&RigidBodyHandle>().iter() {
-            if let Some(body) = world.physics_engine.bodies.get(*body_handle) {
-                for collider_handle in body.colliders() {
-                    if let Some(collider) = world.physics_engine.colliders.get(*collider_handle) {
-                        let aabb = collider.compute_aabb();
-                        let body_translation = Translation::from(Vec2::from(aabb.center().coords));
-                        color_rect.width = aabb.half_extents().x as u32 * 2;
-                        color_rect.height = aabb.half_extents().y as u32 * 2;
-
-                        let translation = {
-                            let mut translation = body_translation - camera_transform.translation;
-
-                            if camera.centered {
-                                translation = translation
-                                    + Translation::new(screen_size.0 / 2.0, screen_size.1 / 2.0);
-                            }
-
-                            translation
-                        };
-
-                        self.push_draw_command(DrawCommand {
-                            drawable: Drawable::ColorRect { color_rect },
-                            transform: Transform::from_translation(translation),
-                            z_index: 0.0,
-                        })?;
+        let (camera, camera_transform) =
+            get_camera_and_camera_transform(world, self.interpolation_alpha);
+        let view_transform = camera.view_transform(&camera_transform, screen_size);
+
+        // Shapes are built in world space, sizes given in pixels are scaled back by the zoom.
+        let thickness = settings.thickness / camera.zoom;
+        let normal_length = settings.normal_length / camera.zoom;
+        let physics_engine = &world.physics_engine;
+
+        let mut outlines = Vec::new();
+        for (_handle, collider) in physics_engine.colliders.iter() {
+            outlines.clear();
+            shape_outlines(collider.shape(), collider.position(), &mut outlines);
+
+            let color = if collider.is_sensor() {
+                settings.sensor_color
+            } else {
+                settings.collider_color
+            };
+
+            for outline in &outlines {
+                self.push_debug_primitive(
+                    Primitive::polyline(&outline.points, thickness, outline.closed),
+                    color,
+                    &view_transform,
+                    settings.z_index,
+                )?;
+            }
+        }
+
+        if settings.draw_contacts {
+            let contact_pairs = physics_engine
+                .narrow_phase
+                .contact_pairs()
+                .filter(|contact_pair| contact_pair.has_any_active_contact);
+
+            for contact_pair in contact_pairs {
+                for manifold in &contact_pair.manifolds {
+                    let normal = vec2(manifold.data.normal.x, manifold.data.normal.y);
+
+                    for contact in &manifold.data.solver_contacts {
+                        let point = vec2(contact.point.x, contact.point.y);
+                        self.push_debug_primitive(
+                            Primitive::circle(point, thickness * 2.0),
+                            settings.contact_color,
+                            &view_transform,
+                            settings.z_index,
+                        )?;
+                        self.push_debug_primitive(
+                            Primitive::line(point, point + normal * normal_length, thickness),
+                            settings.contact_color,
+                            &view_transform,
+                            settings.z_index,
+                        )?;
                     }
                 }
             }
         }
 
+        if settings.draw_joints {
+            for (_handle, joint) in physics_engine.impulse_joints.iter() {
+                let bodies = (
+                    physics_engine.bodies.get(joint.body1),
+                    physics_engine.bodies.get(joint.body2),
+                );
+
+                if let (Some(body1), Some(body2)) = bodies {
+                    let anchor1 = body1.position() * joint.data.local_frame1;
+                    let anchor2 = body2.position() * joint.data.local_frame2;
+                    let points = [
+                        Vec2::from(*body1.translation()),
+                        Vec2::from(anchor1.translation.vector),
+                        Vec2::from(anchor2.translation.vector),
+                        Vec2::from(*body2.translation()),
+                    ];
+
+                    self.push_debug_primitive(
+                        Primitive::polyline(&points, thickness, false),
+                        settings.joint_color,
+                        &view_transform,
+                        settings.z_index,
+                    )?;
+                }
+            }
+        }
+
         Ok(())
     }
 
+    #[cfg(feature = "physics")]
+    fn push_debug_primitive(
+        &mut self,
+        primitive: Primitive,
+        color: Color,
+        view_transform: &Transform,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        self.push_draw_command(DrawCommand {
+            drawable: Drawable::Primitive { primitive, color },
+            transform: *view_transform,
+            z_index,
+        })
+    }
+
     #[inline]
     pub(crate) fn begin(
         &mut self,
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<(), EmeraldError> {
         self.current_render_texture_key = self.screen_texture_key.clone();
@@ -282,7 +526,7 @@ impl RenderingEngine {
     #[inline]
     pub(crate) fn begin_texture(
         &mut self,
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         texture_key: TextureKey,
         asset_store: &mut AssetStore,
     ) -> Result<(), EmeraldError> {
@@ -307,10 +551,25 @@ impl RenderingEngine {
     #[inline]
     fn begin_texture_pass(
         &mut self,
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
         texture_key: TextureKey,
     ) -> Result<(), EmeraldError> {
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => {
+                let background_color = self.settings.background_color;
+                let pixels = asset_store
+                    .get_texture_mut(&texture_key)
+                    .and_then(|texture| texture.pixels.as_mut());
+                if let Some(pixels) = pixels {
+                    clear_pixels(pixels, background_color);
+                }
+
+                return Ok(());
+            }
+        };
+
         if let Some(texture) = asset_store.get_texture(&texture_key) {
             if !self.render_passes.contains_key(&texture_key) {
                 self.render_passes.insert(
@@ -347,36 +606,118 @@ impl RenderingEngine {
     #[inline]
     pub(crate) fn render(
         &mut self,
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<(), EmeraldError> {
-        self.consume_draw_queue(ctx, asset_store)?;
+        let mut texture_key = self.render_texture(ctx.as_deref_mut(), asset_store)?;
+
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => return Ok(()),
+        };
 
-        let texture_key = self.render_texture(ctx, asset_store)?;
+        // Every post-process stage but the last renders into a texture sampled by the next stage,
+        // the last one is drawn to the screen.
+        let mut stages = self
+            .post_process
+            .iter()
+            .copied()
+            .filter(|key| self.materials.contains_key(key))
+            .collect::<Vec<MaterialKey>>();
+        let last_stage = stages.pop();
+
+        for (i, material_key) in stages.into_iter().enumerate() {
+            let target = self.post_process_target(ctx, asset_store, i % 2)?;
+            self.begin_texture_pass(Some(ctx), asset_store, target.clone())?;
+            let size = self.current_resolution;
+            self.draw_post_process_stage(ctx, asset_store, texture_key, Some(material_key), size)?;
+            ctx.end_render_pass();
+
+            texture_key = target;
+        }
 
         ctx.begin_default_pass(PassAction::Clear {
             color: Some(self.settings.background_color.to_percentage()),
             depth: None,
             stencil: None,
         });
-        let sprite = Sprite::from_texture(texture_key);
-        let (w, h) = current_window_resolution(ctx);
-        let translation = Translation::new(w as f32 / 2.0, h as f32 / 2.0);
-
-        self.draw_sprite(ctx, asset_store, &sprite, &translation);
+        let size = current_window_resolution(ctx);
+        self.draw_post_process_stage(ctx, asset_store, texture_key, last_stage, size)?;
         ctx.end_render_pass();
 
         Ok(())
     }
 
+    /// Draws a texture over the whole current pass, through the material of a post-process stage.
+    fn draw_post_process_stage(
+        &mut self,
+        ctx: &mut Context,
+        asset_store: &mut AssetStore,
+        texture_key: TextureKey,
+        material_key: Option<MaterialKey>,
+        size: (usize, usize),
+    ) -> Result<(), EmeraldError> {
+        if let Some(material) = material_key.and_then(|key| self.materials.get_mut(&key)) {
+            if material.has_uniform(RESOLUTION_UNIFORM_NAME) {
+                if let Some(texture) = asset_store.get_texture(&texture_key) {
+                    let resolution = vec2(texture.width as f32, texture.height as f32);
+                    material
+                        .set_uniform(RESOLUTION_UNIFORM_NAME, UniformValue::Vec2(resolution))?;
+                }
+            }
+        }
+
+        let mut sprite = Sprite::from_texture(texture_key);
+        sprite.material = material_key;
+        let transform = Transform::from_translation((size.0 as f32 / 2.0, size.1 as f32 / 2.0));
+
+        self.draw_sprite(Some(ctx), asset_store, &sprite, &transform);
+        self.flush_batch(Some(ctx), asset_store);
+
+        Ok(())
+    }
+
+    /// One of the two textures post-process stages alternate rendering into,
+    /// recreated whenever the screen texture changes size.
+    fn post_process_target(
+        &mut self,
+        ctx: &mut Context,
+        asset_store: &mut AssetStore,
+        index: usize,
+    ) -> Result<TextureKey, EmeraldError> {
+        let key = TextureKey::new(format!("emd_post_process_target_{}", index));
+        let (w, h) = self.current_resolution;
+
+        match asset_store.get_texture(&key) {
+            Some(texture) if (texture.width as usize, texture.height as usize) == (w, h) => {
+                return Ok(key)
+            }
+            Some(_) => match self.render_passes.remove(&key) {
+                // Deleting the render pass deletes its texture as well.
+                Some(render_pass) => {
+                    render_pass.delete(ctx);
+                    asset_store.remove_texture(key.clone(), false);
+                }
+                None => {
+                    asset_store.remove_texture(key.clone(), true);
+                }
+            },
+            None => {}
+        }
+
+        create_render_texture(w, h, key, Some(ctx), asset_store)
+    }
+
     #[inline]
     pub(crate) fn render_texture(
         &mut self,
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<TextureKey, EmeraldError> {
-        self.consume_draw_queue(ctx, asset_store)?;
-        ctx.end_render_pass();
+        self.consume_draw_queue(ctx.as_deref_mut(), asset_store)?;
+        if let Some(ctx) = ctx {
+            ctx.end_render_pass();
+        }
 
         Ok(self.current_render_texture_key.clone())
     }
@@ -391,13 +732,11 @@ impl RenderingEngine {
     #[inline]
     fn consume_draw_queue(
         &mut self,
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
     ) -> Result<(), EmeraldError> {
-        ctx.apply_pipeline(self.pipelines.get(EMERALD_TEXTURE_PIPELINE_NAME).unwrap());
-
         while let Some(draw_command) = self.draw_queue.pop_back() {
-            let translation = draw_command.transform.translation;
+            let transform = draw_command.transform;
 
             match draw_command.drawable {
                 Drawable::Tilemap {
@@ -409,7 +748,7 @@ impl RenderingEngine {
                     z_index,
                     visible,
                 } => self.draw_tilemap(
-                    ctx,
+                    ctx.as_deref_mut(),
                     asset_store,
                     texture_key,
                     tiles,
@@ -418,51 +757,101 @@ impl RenderingEngine {
                     height,
                     z_index,
                     visible,
-                    &translation,
+                    &transform,
                 ),
 
-                Drawable::Aseprite {
-                    sprite,
-                    rotation,
-                    offset,
-                    centered,
-                    visible,
-                    scale,
-                    color,
-                    z_index,
-                } => self.draw_aseprite(
-                    ctx,
-                    asset_store,
-                    &sprite,
-                    rotation,
-                    &offset,
-                    centered,
-                    visible,
-                    &scale,
-                    &color,
-                    z_index,
-                    &translation,
-                ),
+                Drawable::Aseprite { aseprite } => {
+                    self.draw_aseprite(ctx.as_deref_mut(), asset_store, &aseprite, &transform)
+                }
                 Drawable::Sprite { sprite } => {
-                    self.draw_sprite(ctx, asset_store, &sprite, &translation)
+                    self.draw_sprite(ctx.as_deref_mut(), asset_store, &sprite, &transform)
                 }
                 Drawable::ColorRect { color_rect } => {
-                    self.draw_color_rect(ctx, asset_store, &color_rect, &translation)
+                    self.draw_color_rect(ctx.as_deref_mut(), asset_store, &color_rect, &transform)
                 }
                 Drawable::Label { label } => {
-                    self.draw_label(ctx, asset_store, &label, &translation)?
+                    self.draw_label(ctx.as_deref_mut(), asset_store, &label, &transform)?
                 }
+                Drawable::Primitive { primitive, color } => self.draw_primitive(
+                    ctx.as_deref_mut(),
+                    asset_store,
+                    &primitive,
+                    color,
+                    &transform,
+                ),
             }
         }
+        self.flush_batch(ctx, asset_store);
+
         Ok(())
     }
 
+    /// Draws the sprites batched so far, with the pipeline of their material.
+    /// Without a context the batch is rasterized on the cpu into the current render target,
+    /// custom shaders can't run there so every material draws like the default shader.
+    #[inline]
+    fn flush_batch(&mut self, ctx: Option<&mut Context>, asset_store: &mut AssetStore) {
+        if self.batch.is_empty() {
+            return;
+        }
+
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => {
+                self.batch
+                    .rasterize(asset_store, &self.current_render_texture_key);
+                return;
+            }
+        };
+
+        let projection = self.projection();
+        let material = self
+            .batch
+            .material_key()
+            .and_then(|key| self.materials.get(&key))
+            .and_then(|material| {
+                self.pipelines
+                    .get(&material.shader_key.get_name())
+                    .map(|pipeline| {
+                        (
+                            *pipeline,
+                            material.uniform_data(&projection),
+                            material.textures(),
+                        )
+                    })
+            });
+
+        let (pipeline, uniforms, textures) = match material {
+            Some(material) => material,
+            None => (
+                self.pipelines[EMERALD_TEXTURE_PIPELINE_NAME],
+                projection.to_cols_array().to_vec(),
+                Vec::new(),
+            ),
+        };
+
+        ctx.apply_pipeline(&pipeline);
+        self.batch.flush(ctx, asset_store, &uniforms, &textures);
+    }
+
+    #[inline]
+    fn projection(&self) -> Mat4 {
+        Mat4::orthographic_rh_gl(
+            0.0,
+            self.current_resolution.0 as f32,
+            0.0,
+            self.current_resolution.1 as f32,
+            -1.0,
+            1.0,
+        )
+    }
+
     pub(crate) fn draw_label(
         &mut self,
-        mut ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         mut asset_store: &mut AssetStore,
         label: &Label,
-        position: &Translation,
+        transform: &Transform,
     ) -> Result<(), EmeraldError> {
         self.layout.reset(&LayoutSettings {
             max_width: label.max_width,
@@ -511,6 +900,20 @@ impl RenderingEngine {
             remaining_char_count = label.text.len() as i64;
         }
 
+        // Caching a glyph may grow the font texture, invalidating the texture coordinates
+        // of the glyphs already in the batch.
+        let need_to_cache_glyphs = match asset_store.get_font(&label.font_key) {
+            Some(font) => self
+                .layout
+                .glyphs()
+                .iter()
+                .any(|glyph| !font.characters.contains_key(&glyph.key)),
+            None => false,
+        };
+        if need_to_cache_glyphs {
+            self.flush_batch(ctx.as_deref_mut(), asset_store);
+        }
+
         for glyph in self.layout.glyphs() {
             let glyph_key = glyph.key;
             let x = glyph.x;
@@ -523,7 +926,7 @@ impl RenderingEngine {
 
             if need_to_cache_glyph {
                 cache_glyph(
-                    &mut ctx,
+                    ctx.as_deref_mut(),
                     &mut asset_store,
                     &label.font_key,
                     glyph_key,
@@ -548,9 +951,7 @@ impl RenderingEngine {
                         label.scale * target.width * font_texture_width as f32,
                         label.scale * target.height * font_texture_height as f32 * -1.0,
                     );
-                    let real_position = Vec2::from(*position)
-                        + Vec2::from(label.offset)
-                        + vec2(left_coord, -top_coord);
+                    let real_position = Vec2::from(label.offset) + vec2(left_coord, -top_coord);
 
                     if remaining_char_count > 0 {
                         draw_calls.push((
@@ -571,6 +972,8 @@ impl RenderingEngine {
         }
 
         if let Some(font_texture_key) = font_texture_key {
+            let transform_matrix = transform_matrix(transform);
+
             for draw_call in draw_calls {
                 let (
                     z_index,
@@ -593,19 +996,19 @@ impl RenderingEngine {
                     color.a = 0;
                 }
 
-                draw_texture(
-                    &self.settings,
-                    &mut ctx,
-                    &mut asset_store,
+                let model = transform_matrix
+                    * Mat4::from_translation(real_position.extend(0.0))
+                    * Mat4::from_scale(real_scale.extend(1.0));
+
+                self.draw_texture(
+                    ctx.as_deref_mut(),
+                    asset_store,
                     &font_texture_key,
+                    None,
                     z_index,
-                    real_scale,
-                    0.0,
-                    Vec2::new(0.0, 0.0),
-                    real_position,
+                    model,
                     target,
                     color,
-                    self.current_resolution,
                 );
             }
         }
@@ -613,73 +1016,90 @@ impl RenderingEngine {
         Ok(())
     }
 
+    #[inline]
+    pub(crate) fn draw_primitive(
+        &mut self,
+        ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
+        primitive: &Primitive,
+        color: Color,
+        transform: &Transform,
+    ) {
+        let model = transform_matrix(transform);
+        let (r, g, b, a) = color.to_percentage();
+        let color = Vec4::new(r, g, b, a);
+        let vertices = primitive
+            .points
+            .iter()
+            .map(|point| Vertex {
+                position: model.transform_point3(point.extend(0.0)).truncate(),
+                uv: vec2(0.5, 0.5),
+                color,
+            })
+            .collect::<Vec<Vertex>>();
+
+        self.push_to_batch(
+            ctx,
+            asset_store,
+            &TextureKey::default(),
+            None,
+            &vertices,
+            &primitive.indices,
+        );
+    }
+
     #[inline]
     pub(crate) fn draw_color_rect(
         &mut self,
-        mut ctx: &mut Context,
-        mut asset_store: &mut AssetStore,
+        ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
         color_rect: &ColorRect,
-        translation: &Translation,
+        transform: &Transform,
     ) {
         if !color_rect.visible {
             return;
         }
 
-        let (width, height) = (color_rect.width, color_rect.height);
-        let mut offset = color_rect.offset;
-
-        if color_rect.centered {
-            offset.x -= (color_rect.width / 2) as f32;
-            offset.y -= (color_rect.height / 2) as f32;
-        }
-
-        let real_scale = Vec2::new(width as f32, height as f32);
-        let real_position = Vec2::from(*translation) + Vec2::from(offset);
-
-        draw_texture(
-            &self.settings,
-            &mut ctx,
-            &mut asset_store,
+        self.draw_texture(
+            ctx,
+            asset_store,
             &TextureKey::default(),
+            color_rect.material,
             color_rect.z_index,
-            real_scale,
-            color_rect.rotation,
-            Vec2::new(0.0, 0.0),
-            real_position,
+            color_rect_model(color_rect, transform),
             Rectangle::new(0.0, 0.0, 1.0, 1.0),
             color_rect.color,
-            self.current_resolution,
         )
     }
 
     #[inline]
     pub(crate) fn draw_tilemap(
         &mut self,
-        ctx: &mut Context,
+        mut ctx: Option<&mut Context>,
         asset_store: &mut AssetStore,
         texture_key: TextureKey,
         tiles: Vec<isize>,
         tile_size: Vector2<usize>,
         width: usize,
         _height: usize,
-        _z_index: f32,
+        z_index: f32,
         visible: bool,
-        translation: &Translation,
+        transform: &Transform,
     ) {
         if !visible {
             return;
         }
 
-        let mut tileset_width = 0;
-
-        if let Some(texture) = asset_store.get_texture(&texture_key) {
-            tileset_width = texture.width as usize / tile_size.x;
-        }
-
-        let mut sprite = Sprite::from_texture(texture_key);
+        let (texture_width, texture_height) = match asset_store.get_texture(&texture_key) {
+            Some(texture) => (texture.width as f32, texture.height as f32),
+            None => return,
+        };
+        let tileset_width = texture_width as usize / tile_size.x;
 
         let tile_width = tile_size.x as f32;
         let tile_height = tile_size.y as f32;
+        let transform_matrix = transform_matrix(transform);
+        let tile_scale = Mat4::from_scale(vec3(tile_width, tile_height, 1.0));
 
         let mut x = 0;
         let mut y = 0;
@@ -690,16 +1110,27 @@ impl RenderingEngine {
                 let tile_x = tile_id % tileset_width;
                 let tile_y = tile_id / tileset_width;
 
-                sprite.target = Rectangle::new(
-                    tile_x as f32 * tile_width,
-                    tile_y as f32 * tile_height,
-                    tile_width,
-                    tile_height,
+                let target = Rectangle::new(
+                    tile_x as f32 * tile_width / texture_width,
+                    tile_y as f32 * tile_height / texture_height,
+                    tile_width / texture_width,
+                    tile_height / texture_height,
                 );
-                let translation = translation.clone()
-                    + Translation::new(tile_width * x as f32, tile_height * y as f32);
+                let tile_translation = vec2(tile_width * x as f32, tile_height * y as f32);
+                let model = transform_matrix
+                    * Mat4::from_translation(tile_translation.extend(0.0))
+                    * tile_scale;
 
-                self.draw_sprite(ctx, asset_store, &sprite, &translation);
+                self.draw_texture(
+                    ctx.as_deref_mut(),
+                    asset_store,
+                    &texture_key,
+                    None,
+                    z_index,
+                    model,
+                    target,
+                    WHITE,
+                );
             }
 
             x += 1;
@@ -713,174 +1144,260 @@ impl RenderingEngine {
     #[inline]
     pub(crate) fn draw_aseprite(
         &mut self,
-        mut ctx: &mut Context,
-        mut asset_store: &mut AssetStore,
-        sprite: &Sprite,
-        rotation: f32,
-        offset: &Vector2<f32>,
-        centered: bool,
-        visible: bool,
-        scale: &Vector2<f32>,
-        color: &Color,
-        z_index: f32,
-        position: &Translation,
+        ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
+        aseprite: &AsepriteDrawable,
+        transform: &Transform,
     ) {
-        if !visible {
+        if !aseprite.visible {
             return;
         }
 
+        let sprite = &aseprite.sprite;
         let texture = asset_store.get_texture(&sprite.texture_key).unwrap();
-        let mut target = Rectangle::new(
-            sprite.target.x / texture.width as f32,
-            sprite.target.y / texture.height as f32,
-            sprite.target.width / texture.width as f32,
-            sprite.target.height / texture.height as f32,
-        );
-
-        if sprite.target.is_zero_sized() {
-            target = Rectangle::new(0.0, 0.0, 1.0, 1.0);
-        }
-
-        let mut offset = Vec2::from(*offset);
-        if centered {
-            let size = if sprite.target.is_zero_sized() {
-                vec2(texture.width.into(), texture.height.into())
-            } else {
-                vec2(sprite.target.width, sprite.target.height)
-            };
-
-            offset -= Vec2::from(*scale) * size / 2.0;
-        }
-
-        let real_scale = Vec2::new(
-            scale.x * target.width * (f32::from(texture.width)),
-            scale.y * target.height * (f32::from(texture.height)),
+        let texture_size = vec2(texture.width.into(), texture.height.into());
+        let target = normalized_target(&sprite.target, texture_size);
+        let model = quad_model(
+            transform,
+            Vec2::from(aseprite.offset),
+            aseprite.rotation,
+            Vec2::from(aseprite.scale) * target_size(&sprite.target, texture_size),
+            aseprite.centered,
         );
-        let real_position = Vec2::from(*position) + offset;
 
-        draw_texture(
-            &self.settings,
-            &mut ctx,
-            &mut asset_store,
+        self.draw_texture(
+            ctx,
+            asset_store,
             &sprite.texture_key,
-            z_index,
-            real_scale,
-            rotation,
-            Vec2::new(0.0, 0.0),
-            real_position,
+            aseprite.material,
+            aseprite.z_index,
+            model,
             target,
-            *color,
-            self.current_resolution,
+            aseprite.color,
         )
     }
 
     #[inline]
     pub(crate) fn draw_sprite(
         &mut self,
-        mut ctx: &mut Context,
-        mut asset_store: &mut AssetStore,
+        ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
         sprite: &Sprite,
-        position: &Translation,
+        transform: &Transform,
     ) {
         if !sprite.visible {
             return;
         }
 
         let texture = asset_store.get_texture(&sprite.texture_key).unwrap();
-        let mut target = Rectangle::new(
-            sprite.target.x / texture.width as f32,
-            sprite.target.y / texture.height as f32,
-            sprite.target.width / texture.width as f32,
-            sprite.target.height / texture.height as f32,
+        let texture_size = vec2(texture.width.into(), texture.height.into());
+        let target = normalized_target(&sprite.target, texture_size);
+        let model = sprite_model(sprite, texture_size, transform);
+
+        self.draw_texture(
+            ctx,
+            asset_store,
+            &sprite.texture_key,
+            sprite.material,
+            sprite.z_index,
+            model,
+            target,
+            sprite.color,
+        )
+    }
+    #[inline]
+    fn draw_texture(
+        &mut self,
+        ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
+        texture_key: &TextureKey,
+        material_key: Option<MaterialKey>,
+        _z_index: f32,
+        mut model: Mat4,
+        source: Rectangle,
+        color: Color,
+    ) {
+        // Bump position up by half a unit then floor, for pixel snap
+        if self.settings.pixel_snap {
+            model.w_axis.x = (model.w_axis.x + 0.5).floor();
+            model.w_axis.y = (model.w_axis.y + 0.5).floor();
+        }
+
+        let vertices = SpriteBatch::quad(&model, &source, color);
+        self.push_to_batch(
+            ctx,
+            asset_store,
+            texture_key,
+            material_key,
+            &vertices,
+            &QUAD_INDICES,
         );
+    }
 
-        if sprite.target.is_zero_sized() {
-            target = Rectangle::new(0.0, 0.0, 1.0, 1.0);
+    /// Adds triangles to the batch, flushing it first when they don't fit.
+    /// Geometry too large for a single batch is split triangle by triangle over as many batches as needed.
+    fn push_to_batch(
+        &mut self,
+        mut ctx: Option<&mut Context>,
+        asset_store: &mut AssetStore,
+        texture_key: &TextureKey,
+        material_key: Option<MaterialKey>,
+        vertices: &[Vertex],
+        indices: &[u16],
+    ) {
+        if SpriteBatch::fits(vertices.len(), indices.len()) {
+            if self
+                .batch
+                .needs_flush(texture_key, material_key, vertices.len(), indices.len())
+            {
+                self.flush_batch(ctx, asset_store);
+            }
+
+            self.batch
+                .push(texture_key, material_key, vertices, indices);
+            return;
         }
 
-        let mut offset = sprite.offset;
-        if sprite.centered {
-            if sprite.target.is_zero_sized() {
-                offset.x -= sprite.scale.x * texture.width as f32 / 2.0;
-                offset.y -= sprite.scale.y * texture.height as f32 / 2.0;
-            } else {
-                offset.x -= sprite.scale.x * sprite.target.width / 2.0;
-                offset.y -= sprite.scale.y * sprite.target.height / 2.0;
+        for triangle in indices.chunks_exact(3) {
+            let triangle_vertices = [
+                vertices[triangle[0] as usize],
+                vertices[triangle[1] as usize],
+                vertices[triangle[2] as usize],
+            ];
+            if self.batch.needs_flush(texture_key, material_key, 3, 3) {
+                self.flush_batch(ctx.as_deref_mut(), asset_store);
             }
+
+            self.batch
+                .push(texture_key, material_key, &triangle_vertices, &[0, 1, 2]);
         }
+    }
+}
 
-        let real_scale = Vec2::new(
-            sprite.scale.x * target.width * (f32::from(texture.width)),
-            sprite.scale.y * target.height * (f32::from(texture.height)),
-        );
-        let real_position = Vec2::new(position.x + offset.x, position.y + offset.y);
+/// Size in pixels of the part of the texture that is drawn, a zero sized target draws the whole texture.
+#[inline]
+fn target_size(target: &Rectangle, texture_size: Vec2) -> Vec2 {
+    if target.is_zero_sized() {
+        texture_size
+    } else {
+        vec2(target.width, target.height)
+    }
+}
 
-        draw_texture(
-            &self.settings,
-            &mut ctx,
-            &mut asset_store,
-            &sprite.texture_key,
-            sprite.z_index,
-            real_scale,
-            sprite.rotation,
-            Vec2::new(0.0, 0.0),
-            real_position,
-            target,
-            sprite.color,
-            self.current_resolution,
-        )
+/// The target in texture coordinates, as expected by the shader.
+#[inline]
+fn normalized_target(target: &Rectangle, texture_size: Vec2) -> Rectangle {
+    if target.is_zero_sized() {
+        return Rectangle::new(0.0, 0.0, 1.0, 1.0);
     }
+
+    Rectangle::new(
+        target.x / texture_size.x,
+        target.y / texture_size.y,
+        target.width / texture_size.x,
+        target.height / texture_size.y,
+    )
 }
 
+/// The entity transform as a matrix, applied on top of the local placement of every drawable.
 #[inline]
-fn draw_texture(
-    settings: &RenderSettings,
-    mut ctx: &mut Context,
-    asset_store: &mut AssetStore,
-    texture_key: &TextureKey,
-    _z_index: f32,
-    scale: Vec2,
-    rotation: f32,
+fn transform_matrix(transform: &Transform) -> Mat4 {
+    Mat4::from_scale_rotation_translation(
+        vec3(transform.scale.x, transform.scale.y, 1.0),
+        Quat::from_rotation_z(transform.rotation),
+        vec3(transform.translation.x, transform.translation.y, 0.0),
+    )
+}
+
+/// Model matrix of a unit quad stretched to `size`.
+/// The quad is placed at `offset` from the entity and rotated by `rotation` around that point,
+/// before the entity transform is applied.
+#[inline]
+fn quad_model(
+    transform: &Transform,
     offset: Vec2,
-    mut position: Vec2,
-    source: Rectangle,
-    color: Color,
-    resolution: (usize, usize),
-) {
-    // Bump position up by half a unit then floor, for pixel snap
-    if settings.pixel_snap {
-        position = Vec2::new((position.x + 0.5).floor(), (position.y + 0.5).floor());
-    }
-
-    let projection = Mat4::orthographic_rh_gl(
-        0.0,
-        resolution.0 as f32,
-        0.0,
-        resolution.1 as f32,
-        -1.0,
-        1.0,
-    );
-
-    let mut uniforms = Uniforms {
-        projection,
-        model: crate::rendering::param_to_instance_transform(rotation, scale, offset, position),
-        ..Default::default()
-    };
+    rotation: f32,
+    size: Vec2,
+    centered: bool,
+) -> Mat4 {
+    let origin = if centered { -size / 2.0 } else { Vec2::ZERO };
+
+    transform_matrix(transform)
+        * Mat4::from_translation(offset.extend(0.0))
+        * Mat4::from_rotation_z(rotation)
+        * Mat4::from_translation(origin.extend(0.0))
+        * Mat4::from_scale(size.extend(1.0))
+}
 
-    let color = color.to_percentage();
-    uniforms.source = Vec4::new(source.x, source.y, source.width, source.height);
-    uniforms.color = Vec4::new(color.0, color.1, color.2, color.3);
+#[inline]
+fn sprite_model(sprite: &Sprite, texture_size: Vec2, transform: &Transform) -> Mat4 {
+    quad_model(
+        transform,
+        Vec2::from(sprite.offset),
+        sprite.rotation,
+        Vec2::from(sprite.scale) * target_size(&sprite.target, texture_size),
+        sprite.centered,
+    )
+}
 
-    if let Some(texture) = asset_store.get_texture(texture_key) {
-        texture.inner.set_filter(&mut ctx, texture.filter);
-        ctx.apply_bindings(&texture.bindings);
-        ctx.apply_uniforms(&uniforms);
-        ctx.draw(0, 6, 1);
+#[inline]
+fn color_rect_model(color_rect: &ColorRect, transform: &Transform) -> Mat4 {
+    quad_model(
+        transform,
+        Vec2::from(color_rect.offset),
+        color_rect.rotation,
+        vec2(color_rect.width as f32, color_rect.height as f32),
+        color_rect.centered,
+    )
+}
+
+/// Axis aligned bounds of the unit quad once transformed by the model matrix.
+#[inline]
+fn quad_bounds(model: &Mat4) -> Rectangle {
+    let corners = [
+        model.transform_point3(vec3(0.0, 0.0, 0.0)),
+        model.transform_point3(vec3(1.0, 0.0, 0.0)),
+        model.transform_point3(vec3(0.0, 1.0, 0.0)),
+        model.transform_point3(vec3(1.0, 1.0, 0.0)),
+    ];
+
+    let mut min = vec2(f32::MAX, f32::MAX);
+    let mut max = vec2(f32::MIN, f32::MIN);
+    for corner in corners.iter() {
+        min = min.min(corner.truncate());
+        max = max.max(corner.truncate());
     }
+
+    Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+fn create_pipeline(ctx: &mut Context, shader: Shader) -> Pipeline {
+    let params = PipelineParams {
+        depth_write: true,
+        color_blend: Some(BlendState::new(
+            Equation::Add,
+            BlendFactor::Value(BlendValue::SourceAlpha),
+            BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+        )),
+        alpha_blend: Some(BlendState::new(
+            Equation::Add,
+            BlendFactor::Zero,
+            BlendFactor::One,
+        )),
+        ..Default::default()
+    };
+
+    Pipeline::with_params(
+        ctx,
+        &[BufferLayout::default()],
+        &vertex_attributes(),
+        shader,
+        params,
+    )
 }
 
 #[inline]
-fn get_camera_and_camera_transform(world: &World) -> (Camera, Transform) {
+fn get_camera_and_camera_transform(world: &World, interpolation_alpha: f32) -> (Camera, Transform) {
     let mut cam = Camera::default();
     let mut cam_transform = Transform::from_translation((0.0, 0.0));
     let mut entity_holding_camera: Option<Entity> = None;
@@ -893,24 +1410,30 @@ fn get_camera_and_camera_transform(world: &World) -> (Camera, Transform) {
     }
 
     if let Some(entity) = entity_holding_camera {
-        if let Ok(transform) = world.get_mut::<Transform>(entity) {
-            cam_transform = *transform;
+        if let Ok(transform) = world.interpolated_global_transform(entity, interpolation_alpha) {
+            cam_transform = transform;
         }
     }
 
     (cam, cam_transform)
 }
 
+/// The current frame of an aseprite, with the settings it is drawn with.
+pub(crate) struct AsepriteDrawable {
+    pub sprite: Sprite,
+    pub rotation: f32,
+    pub color: Color,
+    pub centered: bool,
+    pub scale: Vector2<f32>,
+    pub offset: Vector2<f32>,
+    pub material: Option<MaterialKey>,
+    pub z_index: f32,
+    pub visible: bool,
+}
+
 pub(crate) enum Drawable {
     Aseprite {
-        sprite: Sprite,
-        rotation: f32,
-        color: Color,
-        centered: bool,
-        scale: Vector2<f32>,
-        offset: Vector2<f32>,
-        z_index: f32,
-        visible: bool,
+        aseprite: AsepriteDrawable,
     },
     Sprite {
         sprite: Sprite,
@@ -921,6 +1444,10 @@ pub(crate) enum Drawable {
     Label {
         label: Label,
     },
+    Primitive {
+        primitive: Primitive,
+        color: Color,
+    },
     Tilemap {
         texture_key: TextureKey,
         tiles: Vec<isize>,
@@ -955,14 +1482,10 @@ impl ToDrawable for Tilemap {
     ) -> Option<Rectangle> {
         let width = self.width * self.tile_size.x;
         let height = self.height * self.tile_size.y;
-        let visible_bounds = Rectangle::new(
-            transform.translation.x,
-            transform.translation.y,
-            width as f32,
-            height as f32,
-        );
+        let model =
+            transform_matrix(transform) * Mat4::from_scale(vec3(width as f32, height as f32, 1.0));
 
-        Some(visible_bounds)
+        Some(quad_bounds(&model))
     }
 
     fn to_drawable(&self) -> Drawable {
@@ -1021,14 +1544,17 @@ impl ToDrawable for Aseprite {
 
     fn to_drawable(&self) -> Drawable {
         Drawable::Aseprite {
-            sprite: self.get_sprite().clone(),
-            offset: self.offset,
-            scale: self.scale,
-            centered: self.centered,
-            color: self.color,
-            rotation: self.rotation,
-            z_index: self.z_index,
-            visible: self.visible,
+            aseprite: AsepriteDrawable {
+                sprite: self.get_sprite().clone(),
+                offset: self.offset,
+                scale: self.scale,
+                centered: self.centered,
+                color: self.color,
+                rotation: self.rotation,
+                material: self.material,
+                z_index: self.z_index,
+                visible: self.visible,
+            },
         }
     }
 
@@ -1043,29 +1569,13 @@ impl ToDrawable for Sprite {
         transform: &Transform,
         asset_store: &mut AssetStore,
     ) -> Option<Rectangle> {
-        let mut bounds = self.target.clone();
+        let texture_size = asset_store
+            .get_texture(&self.texture_key)
+            .map(|texture| vec2(texture.width as f32, texture.height as f32))
+            .unwrap_or(Vec2::ZERO);
+        let model = sprite_model(self, texture_size, transform);
 
-        if bounds.is_zero_sized() {
-            if let Some(texture) = asset_store.get_texture(&self.texture_key) {
-                bounds.width = texture.width as f32;
-                bounds.height = texture.height as f32;
-            }
-        }
-
-        // Set the visibility rect at the position of the sprite
-        bounds.x = transform.translation.x + self.offset.x;
-        bounds.y = transform.translation.y + self.offset.y;
-
-        if self.centered {
-            bounds.x -= bounds.width as f32 / 2.0;
-            bounds.y -= bounds.height as f32 / 2.0;
-        }
-
-        // Take the sprite's scale factor into account
-        bounds.width *= self.scale.x;
-        bounds.height *= self.scale.y;
-
-        Some(bounds)
+        Some(quad_bounds(&model))
     }
 
     fn to_drawable(&self) -> Drawable {
@@ -1107,17 +1617,7 @@ impl ToDrawable for ColorRect {
         transform: &Transform,
         _asset_store: &mut AssetStore,
     ) -> Option<Rectangle> {
-        let mut bounds = Rectangle::new(
-            transform.translation.x + self.offset.x,
-            transform.translation.y + self.offset.y,
-            self.width as f32,
-            self.height as f32,
-        );
-        if self.centered {
-            bounds.x -= self.width as f32 / 2.0;
-            bounds.y -= self.height as f32 / 2.0;
-        }
-        Some(bounds)
+        Some(quad_bounds(&color_rect_model(self, transform)))
     }
 
     fn to_drawable(&self) -> Drawable {
@@ -1159,6 +1659,10 @@ struct DrawCommandAdder {
     /// Bounds for culling checks, or None if no culling checks should be
     /// performed.
     camera_bounds: Option<Rectangle>,
+
+    /// Transforms to draw the entities that have a parent or a `PreviousTransform` with.
+    /// The transforms of all other entities are already global.
+    transforms: HashMap<Entity, Transform>,
 }
 
 impl DrawCommandAdder {
@@ -1169,22 +1673,57 @@ impl DrawCommandAdder {
                 engine.current_resolution.1 as f32,
             );
 
-            let (camera, camera_transform) = get_camera_and_camera_transform(world);
-            let mut camera_view_region = Rectangle::new(
-                camera_transform.translation.x - screen_size.0 / 2.0,
-                camera_transform.translation.y - screen_size.1 / 2.0,
-                screen_size.0,
-                screen_size.1,
-            );
-            camera_view_region.width *= camera.zoom;
-            camera_view_region.height *= camera.zoom;
+            // The view can be zoomed and rotated, so bound every corner of the screen in world space.
+            let (camera, camera_transform) =
+                get_camera_and_camera_transform(world, engine.interpolation_alpha);
+            let corners = [
+                Translation::new(0.0, 0.0),
+                Translation::new(screen_size.0, 0.0),
+                Translation::new(0.0, screen_size.1),
+                Translation::new(screen_size.0, screen_size.1),
+            ]
+            .iter()
+            .map(|corner| camera.screen_to_world(&camera_transform, screen_size, corner))
+            .collect::<Vec<Translation>>();
+
+            let mut min = vec2(f32::MAX, f32::MAX);
+            let mut max = vec2(f32::MIN, f32::MIN);
+            for corner in corners {
+                min = min.min(Vec2::from(corner));
+                max = max.max(Vec2::from(corner));
+            }
+            let camera_view_region = Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y);
 
             Some(camera_view_region)
         } else {
             None
         };
 
-        Self { camera_bounds }
+        let mut entities = world
+            .query::<&Parent>()
+            .iter()
+            .map(|(entity, _parent)| entity)
+            .collect::<Vec<Entity>>();
+        entities.extend(
+            world
+                .query::<&PreviousTransform>()
+                .iter()
+                .map(|(entity, _previous)| entity),
+        );
+        let transforms = entities
+            .into_iter()
+            .filter_map(|entity| {
+                world
+                    .interpolated_global_transform(entity, engine.interpolation_alpha)
+                    .ok()
+                    .map(|transform| (entity, transform))
+            })
+            .collect();
+
+        Self {
+            camera_bounds,
+            transforms,
+        }
     }
 
     fn add_draw_commands<'a, D>(
@@ -1199,7 +1738,11 @@ impl DrawCommandAdder {
             world
                 .query::<(&D, &Transform)>()
                 .into_iter()
-                .filter(|(_entity, (to_drawable, transform))| {
+                .map(|(entity, (to_drawable, transform))| {
+                    let transform = self.transforms.get(&entity).unwrap_or(transform);
+                    (to_drawable, transform)
+                })
+                .filter(|(to_drawable, transform)| {
                     if let Some(camera_bounds) = self.camera_bounds {
                         if let Some(drawable_bounds) =
                             to_drawable.get_visible_bounds(transform, asset_store)
@@ -1210,7 +1753,7 @@ impl DrawCommandAdder {
 
                     true
                 })
-                .map(|(_entity, (to_drawable, transform))| {
+                .map(|(to_drawable, transform)| {
                     let drawable = to_drawable.to_drawable();
 
                     DrawCommand {
@@ -1228,21 +1771,36 @@ pub(crate) fn create_render_texture(
     w: usize,
     h: usize,
     key: TextureKey,
-    ctx: &mut Context,
+    ctx: Option<&mut Context>,
     asset_store: &mut AssetStore,
 ) -> Result<TextureKey, EmeraldError> {
-    let color_img = miniquad::Texture::new_render_texture(
-        ctx,
-        TextureParams {
-            width: w as _,
-            height: h as _,
-            format: TextureFormat::RGBA8,
-            wrap: TextureWrap::Clamp,
-            filter: FilterMode::Nearest,
-        },
-    );
-
-    let texture = crate::rendering::Texture::from_texture(ctx, key.clone(), color_img)?;
+    let color_img = match ctx {
+        Some(ctx) => miniquad::Texture::new_render_texture(
+            ctx,
+            TextureParams {
+                width: w as _,
+                height: h as _,
+                format: TextureFormat::RGBA8,
+                wrap: TextureWrap::Clamp,
+                filter: FilterMode::Nearest,
+            },
+        ),
+        None => {
+            let pixels = vec![0; w * h * 4];
+            let texture = crate::rendering::Texture::from_rgba8(
+                None,
+                key.clone(),
+                w as u16,
+                h as u16,
+                &pixels,
+            )?;
+            asset_store.insert_texture(key.clone(), texture);
+
+            return Ok(key);
+        }
+    };
+
+    let texture = crate::rendering::Texture::from_texture(key.clone(), color_img)?;
     asset_store.insert_texture(key.clone(), texture);
 
     Ok(key)
@@ -105,7 +105,7 @@ impl Font {
 }
 
 pub(crate) fn cache_glyph(
-    mut ctx: &mut Context,
+    mut ctx: Option<&mut Context>,
     asset_store: &mut AssetStore,
     font_key: &FontKey,
     glyph_key: GlyphRasterConfig,
@@ -179,7 +179,7 @@ pub(crate) fn cache_glyph(
                 );
 
                 let new_font_texture = Texture::from_rgba8(
-                    ctx,
+                    ctx.as_deref_mut(),
                     font.font_texture_key.clone(),
                     font.font_image.width,
                     font.font_image.height,
@@ -210,13 +210,13 @@ pub(crate) fn cache_glyph(
     }
 
     if update_font_texture {
-        asset_store.update_font_texture(&mut ctx, font_key);
+        asset_store.update_font_texture(ctx.as_deref_mut(), font_key);
     }
 
     if let Some(characters) = recache_characters {
         // recache all previously asset_stored symbols
         for (glyph_key, _) in characters {
-            cache_glyph(&mut ctx, asset_store, font_key, glyph_key, size)?;
+            cache_glyph(ctx.as_deref_mut(), asset_store, font_key, glyph_key, size)?;
         }
     }
 
@@ -1,17 +1,19 @@
 use crate::{
-    transform::Transform, AssetStore, ColorRect, DrawCommand, Drawable, EmeraldError, Label,
-    RenderingEngine, Sprite, TextureKey, World,
+    transform::Transform, AssetStore, Color, ColorRect, DrawCommand, Drawable, EmeraldError, Label,
+    Material, MaterialKey, PostProcessEffect, Primitive, Rectangle, RenderingEngine, ShaderKey,
+    Sprite, TextureKey, UniformType, Vector2, World,
 };
+use glam::Vec2;
 use miniquad::Context;
 
 pub struct GraphicsHandler<'c> {
-    quad_ctx: &'c mut Context,
+    quad_ctx: Option<&'c mut Context>,
     asset_store: &'c mut AssetStore,
     rendering_engine: &'c mut RenderingEngine,
 }
 impl<'c> GraphicsHandler<'c> {
     pub(crate) fn new(
-        quad_ctx: &'c mut Context,
+        quad_ctx: Option<&'c mut Context>,
         asset_store: &'c mut AssetStore,
         rendering_engine: &'c mut RenderingEngine,
     ) -> Self {
@@ -36,6 +38,17 @@ impl<'c> GraphicsHandler<'c> {
         self.rendering_engine.draw_colliders(world, color)
     }
 
+    /// Draws the true outline of every collider, optionally along with contacts and joints.
+    #[cfg(feature = "physics")]
+    pub fn draw_colliders_with_settings(
+        &mut self,
+        world: &World,
+        settings: &crate::ColliderDebugSettings,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine
+            .draw_colliders_with_settings(world, settings)
+    }
+
     pub fn draw_sprite(
         &mut self,
         sprite: &Sprite,
@@ -74,37 +87,205 @@ impl<'c> GraphicsHandler<'c> {
         })
     }
 
+    pub fn draw_line(
+        &mut self,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        thickness: f32,
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        let primitive = Primitive::line(Vec2::from(start), Vec2::from(end), thickness);
+        self.draw_primitive(primitive, color, z_index)
+    }
+
+    /// Draws a line of `thickness` through all the points, closing the loop if `closed` is true.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Vector2<f32>],
+        thickness: f32,
+        closed: bool,
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        let points = points.iter().map(|p| Vec2::from(*p)).collect::<Vec<Vec2>>();
+        let primitive = Primitive::polyline(&points, thickness, closed);
+        self.draw_primitive(primitive, color, z_index)
+    }
+
+    pub fn draw_circle(
+        &mut self,
+        center: Vector2<f32>,
+        radius: f32,
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        let primitive = Primitive::circle(Vec2::from(center), radius);
+        self.draw_primitive(primitive, color, z_index)
+    }
+
+    /// Draws a filled polygon, the points may be in either winding order but must not self intersect.
+    pub fn draw_polygon(
+        &mut self,
+        points: &[Vector2<f32>],
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        let points = points.iter().map(|p| Vec2::from(*p)).collect::<Vec<Vec2>>();
+        let primitive = Primitive::polygon(&points);
+        self.draw_primitive(primitive, color, z_index)
+    }
+
+    /// Draws the outline of the rectangle, centered on its edges.
+    pub fn draw_rect_outline(
+        &mut self,
+        rect: &Rectangle,
+        thickness: f32,
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        let primitive = Primitive::rect_outline(rect, thickness);
+        self.draw_primitive(primitive, color, z_index)
+    }
+
+    fn draw_primitive(
+        &mut self,
+        primitive: Primitive,
+        color: Color,
+        z_index: f32,
+    ) -> Result<(), EmeraldError> {
+        self.rendering_engine.push_draw_command(DrawCommand {
+            drawable: Drawable::Primitive { primitive, color },
+            transform: Transform::default(),
+            z_index,
+        })
+    }
+
+    /// Compiles a shader that materials can be created from.
+    ///
+    /// Shaders are GLSL 100, and receive the same inputs as the default shaders
+    /// ([`crate::DEFAULT_VERTEX_SHADER`] and [`crate::DEFAULT_FRAGMENT_SHADER`]):
+    /// the `position`, `texcoord` and `color0` attributes, the `Projection` uniform and the `tex` sampler.
+    /// `uniforms` are declared after `Projection`, in the given order.
+    pub fn create_shader(
+        &mut self,
+        name: &str,
+        vertex: &str,
+        fragment: &str,
+        uniforms: &[(&str, UniformType)],
+    ) -> Result<ShaderKey, EmeraldError> {
+        self.rendering_engine.create_shader(
+            self.quad_ctx.as_deref_mut(),
+            name,
+            vertex,
+            fragment,
+            uniforms,
+            &[],
+        )
+    }
+
+    /// Compiles a shader sampling `textures` on top of `tex`, see [`GraphicsHandler::create_shader`].
+    /// The textures are `sampler2D`s set per material with [`Material::set_texture`].
+    pub fn create_shader_with_textures(
+        &mut self,
+        name: &str,
+        vertex: &str,
+        fragment: &str,
+        uniforms: &[(&str, UniformType)],
+        textures: &[&str],
+    ) -> Result<ShaderKey, EmeraldError> {
+        self.rendering_engine.create_shader(
+            self.quad_ctx.as_deref_mut(),
+            name,
+            vertex,
+            fragment,
+            uniforms,
+            textures,
+        )
+    }
+
+    /// Creates a material for the given shader, with all of its uniforms zeroed.
+    pub fn create_material(&mut self, shader_key: &ShaderKey) -> Result<MaterialKey, EmeraldError> {
+        self.rendering_engine.create_material(shader_key)
+    }
+
+    pub fn get_material(&self, material_key: MaterialKey) -> Option<&Material> {
+        self.rendering_engine.get_material(material_key)
+    }
+
+    pub fn get_material_mut(&mut self, material_key: MaterialKey) -> Option<&mut Material> {
+        self.rendering_engine.get_material_mut(material_key)
+    }
+
+    pub fn remove_material(&mut self, material_key: MaterialKey) -> Option<Material> {
+        self.rendering_engine.remove_material(material_key)
+    }
+
+    /// Creates a material using one of the built-in post-process effects, with sensible default uniforms.
+    pub fn create_post_process_effect(
+        &mut self,
+        effect: PostProcessEffect,
+    ) -> Result<MaterialKey, EmeraldError> {
+        self.rendering_engine
+            .create_post_process_effect(self.quad_ctx.as_deref_mut(), effect)
+    }
+
+    /// Adds a stage to the post-process stack, applied to the screen texture when calling [`GraphicsHandler::render`].
+    /// Stages run in the order they were added, each one sampling the output of the previous stage through `tex`.
+    /// Any material can be used as a stage, see [`GraphicsHandler::create_shader`].
+    pub fn add_post_process(&mut self, material_key: MaterialKey) {
+        self.rendering_engine.add_post_process(material_key)
+    }
+
+    /// Returns whether the material was part of the post-process stack.
+    pub fn remove_post_process(&mut self, material_key: MaterialKey) -> bool {
+        self.rendering_engine.remove_post_process(material_key)
+    }
+
+    pub fn clear_post_process(&mut self) {
+        self.rendering_engine.clear_post_process()
+    }
+
     /// Begin drawing to the screen
     pub fn begin(&mut self) -> Result<(), EmeraldError> {
         self.rendering_engine
-            .begin(&mut self.quad_ctx, &mut self.asset_store)
+            .begin(self.quad_ctx.as_deref_mut(), &mut self.asset_store)
     }
 
     /// Begin drawing to the screen
     pub fn begin_texture(&mut self, texture_key: TextureKey) -> Result<(), EmeraldError> {
-        self.rendering_engine
-            .begin_texture(&mut self.quad_ctx, texture_key, &mut self.asset_store)
+        self.rendering_engine.begin_texture(
+            self.quad_ctx.as_deref_mut(),
+            texture_key,
+            &mut self.asset_store,
+        )
     }
 
     /// Commit all drawings to the screen
     pub fn render(&mut self) -> Result<(), EmeraldError> {
         self.rendering_engine
-            .render(&mut self.quad_ctx, &mut self.asset_store)
+            .render(self.quad_ctx.as_deref_mut(), &mut self.asset_store)
     }
     /// Commit all drawings to the screen
     pub fn render_texture(&mut self) -> Result<TextureKey, EmeraldError> {
         self.rendering_engine
-            .render_texture(&mut self.quad_ctx, &mut self.asset_store)
+            .render_texture(self.quad_ctx.as_deref_mut(), &mut self.asset_store)
     }
 
+    /// Does nothing when running headless.
     pub fn set_fullscreen(&mut self, fs: bool) -> Result<(), EmeraldError> {
-        self.quad_ctx.set_fullscreen(fs);
+        if let Some(ctx) = self.quad_ctx.as_deref_mut() {
+            ctx.set_fullscreen(fs);
+        }
 
         Ok(())
     }
 
+    /// Does nothing when running headless.
     pub fn set_window_size(&mut self, x: u32, y: u32) -> Result<(), EmeraldError> {
-        self.quad_ctx.set_window_size(x, y);
+        if let Some(ctx) = self.quad_ctx.as_deref_mut() {
+            ctx.set_window_size(x, y);
+        }
 
         Ok(())
     }
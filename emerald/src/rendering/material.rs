@@ -0,0 +1,234 @@
+use crate::*;
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use miniquad::UniformType;
+use std::sync::Arc;
+
+/// Name of the uniform holding the projection matrix, every shader must declare it.
+pub const PROJECTION_UNIFORM_NAME: &str = "Projection";
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct ShaderKey(pub(crate) Arc<String>);
+impl ShaderKey {
+    pub(crate) fn new<T: Into<String>>(name: T) -> Self {
+        ShaderKey(Arc::new(name.into()))
+    }
+
+    pub fn get_name(&self) -> String {
+        self.0.as_ref().clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct MaterialKey(pub(crate) usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UniformValue {
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat4(Mat4),
+}
+impl UniformValue {
+    fn uniform_type(&self) -> UniformType {
+        match self {
+            UniformValue::Float(_) => UniformType::Float1,
+            UniformValue::Vec2(_) => UniformType::Float2,
+            UniformValue::Vec3(_) => UniformType::Float3,
+            UniformValue::Vec4(_) => UniformType::Float4,
+            UniformValue::Mat4(_) => UniformType::Mat4,
+        }
+    }
+
+    fn write(&self, data: &mut [f32]) {
+        match self {
+            UniformValue::Float(value) => data[0] = *value,
+            UniformValue::Vec2(value) => data.copy_from_slice(&value.to_array()),
+            UniformValue::Vec3(value) => data.copy_from_slice(&value.to_array()),
+            UniformValue::Vec4(value) => data.copy_from_slice(&value.to_array()),
+            UniformValue::Mat4(value) => data.copy_from_slice(&value.to_cols_array()),
+        }
+    }
+}
+
+/// Amount of floats a uniform takes, or None for uniform types materials don't support.
+pub(crate) fn uniform_float_count(uniform_type: UniformType) -> Option<usize> {
+    match uniform_type {
+        UniformType::Float1 => Some(1),
+        UniformType::Float2 => Some(2),
+        UniformType::Float3 => Some(3),
+        UniformType::Float4 => Some(4),
+        UniformType::Mat4 => Some(16),
+        _ => None,
+    }
+}
+
+/// A custom shader along with the values of its uniforms and textures.
+/// Uniforms start zeroed, and keep their value until they are set again.
+/// Textures the shader samples on top of `tex` draw the default white texture until they are set.
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub(crate) shader_key: ShaderKey,
+    uniforms: Vec<(String, UniformType, usize)>,
+    data: Vec<f32>,
+    textures: Vec<(String, Option<TextureKey>)>,
+}
+impl Material {
+    /// `uniforms` are the uniforms of the shader following the projection, in declaration order.
+    /// `textures` are the samplers of the shader following `tex`.
+    pub(crate) fn new(
+        shader_key: ShaderKey,
+        uniforms: &[(String, UniformType)],
+        textures: &[String],
+    ) -> Self {
+        let mut offset = 0;
+        let uniforms = uniforms
+            .iter()
+            .map(|(name, uniform_type)| {
+                let uniform = (name.clone(), *uniform_type, offset);
+                offset += uniform_float_count(*uniform_type).unwrap_or(0);
+                uniform
+            })
+            .collect();
+
+        Material {
+            shader_key,
+            uniforms,
+            data: vec![0.0; offset],
+            textures: textures.iter().map(|name| (name.clone(), None)).collect(),
+        }
+    }
+
+    pub fn shader_key(&self) -> &ShaderKey {
+        &self.shader_key
+    }
+
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.uniforms.iter().any(|(n, _, _)| n == name)
+    }
+
+    pub fn set_uniform(&mut self, name: &str, value: UniformValue) -> Result<(), EmeraldError> {
+        let (_, uniform_type, offset) = match self.uniforms.iter().find(|(n, _, _)| n == name) {
+            Some(uniform) => uniform,
+            None => {
+                return Err(EmeraldError::new(format!(
+                    "Shader {:?} has no uniform named {:?}.",
+                    self.shader_key.get_name(),
+                    name
+                )))
+            }
+        };
+
+        if std::mem::discriminant(uniform_type) != std::mem::discriminant(&value.uniform_type()) {
+            return Err(EmeraldError::new(format!(
+                "Uniform {:?} is a {:?}, it cannot be set to {:?}.",
+                name, uniform_type, value
+            )));
+        }
+
+        let count = uniform_float_count(*uniform_type).unwrap_or(0);
+        value.write(&mut self.data[*offset..(*offset + count)]);
+
+        Ok(())
+    }
+
+    pub fn has_texture(&self, name: &str) -> bool {
+        self.textures.iter().any(|(n, _)| n == name)
+    }
+
+    /// Sets the texture sampled by one of the shader's extra samplers.
+    pub fn set_texture(&mut self, name: &str, texture_key: TextureKey) -> Result<(), EmeraldError> {
+        match self.textures.iter_mut().find(|(n, _)| n == name) {
+            Some((_, texture)) => {
+                *texture = Some(texture_key);
+                Ok(())
+            }
+            None => Err(EmeraldError::new(format!(
+                "Shader {:?} has no texture named {:?}.",
+                self.shader_key.get_name(),
+                name
+            ))),
+        }
+    }
+
+    /// Textures bound after `tex`, in declaration order, unset ones falling back to the default texture.
+    pub(crate) fn textures(&self) -> Vec<TextureKey> {
+        self.textures
+            .iter()
+            .map(|(_, texture)| texture.clone().unwrap_or_default())
+            .collect()
+    }
+
+    /// Uniform values laid out the way the shader expects them, projection first.
+    pub(crate) fn uniform_data(&self, projection: &Mat4) -> Vec<f32> {
+        let mut data = Vec::with_capacity(16 + self.data.len());
+        data.extend_from_slice(&projection.to_cols_array());
+        data.extend_from_slice(&self.data);
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Material, ShaderKey, UniformValue};
+    use crate::TextureKey;
+    use glam::{Mat4, Vec2};
+    use miniquad::UniformType;
+
+    fn material() -> Material {
+        Material::new(
+            ShaderKey::new("flash"),
+            &[
+                (String::from("intensity"), UniformType::Float1),
+                (String::from("direction"), UniformType::Float2),
+            ],
+            &[String::from("mask"), String::from("noise")],
+        )
+    }
+
+    #[test]
+    fn uniforms_are_laid_out_after_the_projection() {
+        let mut material = material();
+        material
+            .set_uniform("direction", UniformValue::Vec2(Vec2::new(2.0, 3.0)))
+            .unwrap();
+        material
+            .set_uniform("intensity", UniformValue::Float(0.5))
+            .unwrap();
+
+        let data = material.uniform_data(&Mat4::IDENTITY);
+        assert_eq!(data.len(), 19);
+        assert_eq!(&data[16..], &[0.5, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn mismatched_uniforms_are_rejected() {
+        let mut material = material();
+
+        assert!(material
+            .set_uniform("intensity", UniformValue::Vec2(Vec2::ZERO))
+            .is_err());
+        assert!(material
+            .set_uniform("missing", UniformValue::Float(1.0))
+            .is_err());
+    }
+
+    #[test]
+    fn unset_textures_fall_back_to_the_default_texture() {
+        let mut material = material();
+        material
+            .set_texture("noise", TextureKey::new("noise.png"))
+            .unwrap();
+
+        assert!(material.has_texture("mask"));
+        assert!(material
+            .set_texture("tex", TextureKey::new("noise.png"))
+            .is_err());
+        assert_eq!(
+            material.textures(),
+            vec![TextureKey::default(), TextureKey::new("noise.png")]
+        );
+    }
+}
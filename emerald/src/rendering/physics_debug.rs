@@ -0,0 +1,290 @@
+use crate::*;
+
+use glam::{vec2, Vec2};
+use rapier2d::parry::shape::{Shape, TypedShape};
+use rapier2d::prelude::Isometry;
+use std::f32::consts::PI;
+
+/// Arcs are split in segments of at most this angle.
+const ARC_SEGMENT_ANGLE: f32 = PI / 12.0;
+
+/// How colliders and their interactions are drawn by [`GraphicsHandler::draw_colliders_with_settings`].
+#[derive(Clone, Debug)]
+pub struct ColliderDebugSettings {
+    pub collider_color: Color,
+    pub sensor_color: Color,
+    /// Thickness of the outlines in pixels, regardless of the camera zoom.
+    pub thickness: f32,
+
+    /// Draws the contact points between colliders, along with their normals.
+    pub draw_contacts: bool,
+    pub contact_color: Color,
+    /// Length of the contact normals in pixels.
+    pub normal_length: f32,
+
+    /// Draws a line going from each body of a joint to its anchor.
+    pub draw_joints: bool,
+    pub joint_color: Color,
+
+    pub z_index: f32,
+}
+impl Default for ColliderDebugSettings {
+    fn default() -> ColliderDebugSettings {
+        ColliderDebugSettings {
+            collider_color: Color::new(255, 0, 0, 200),
+            sensor_color: Color::new(0, 160, 255, 200),
+            thickness: 1.0,
+            draw_contacts: false,
+            contact_color: Color::new(255, 220, 0, 255),
+            normal_length: 12.0,
+            draw_joints: false,
+            joint_color: Color::new(0, 255, 120, 255),
+            z_index: 0.0,
+        }
+    }
+}
+
+/// A line through points in world space.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Outline {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+/// Outlines of a shape placed at `position`, compound shapes contribute the outlines of all their parts.
+pub(crate) fn shape_outlines(
+    shape: &dyn Shape,
+    position: &Isometry<f32>,
+    outlines: &mut Vec<Outline>,
+) {
+    let to_world = |point: &rapier2d::prelude::Point<f32>| {
+        let point = position * point;
+        vec2(point.x, point.y)
+    };
+
+    match shape.as_typed_shape() {
+        TypedShape::Ball(ball) => {
+            let center = to_world(&rapier2d::prelude::Point::origin());
+            let edge = to_world(&rapier2d::prelude::Point::new(ball.radius, 0.0));
+            outlines.push(Outline {
+                points: rounded_polygon(&[center], ball.radius),
+                closed: true,
+            });
+            // Shows the rotation of the ball.
+            outlines.push(Outline {
+                points: vec![center, edge],
+                closed: false,
+            });
+        }
+        TypedShape::Cuboid(cuboid) => outlines.push(Outline {
+            points: cuboid_points(cuboid.half_extents.x, cuboid.half_extents.y)
+                .iter()
+                .map(to_world)
+                .collect(),
+            closed: true,
+        }),
+        TypedShape::RoundCuboid(cuboid) => {
+            let half_extents = cuboid.inner_shape.half_extents;
+            let points = cuboid_points(half_extents.x, half_extents.y)
+                .iter()
+                .map(to_world)
+                .collect::<Vec<Vec2>>();
+            outlines.push(Outline {
+                points: rounded_polygon(&points, cuboid.border_radius),
+                closed: true,
+            });
+        }
+        TypedShape::Capsule(capsule) => {
+            let points = [to_world(&capsule.segment.a), to_world(&capsule.segment.b)];
+            outlines.push(Outline {
+                points: rounded_polygon(&points, capsule.radius),
+                closed: true,
+            });
+        }
+        TypedShape::Segment(segment) => outlines.push(Outline {
+            points: vec![to_world(&segment.a), to_world(&segment.b)],
+            closed: false,
+        }),
+        TypedShape::Triangle(triangle) => outlines.push(Outline {
+            points: triangle.vertices().iter().map(to_world).collect(),
+            closed: true,
+        }),
+        TypedShape::RoundTriangle(triangle) => {
+            let points = counter_clockwise(
+                triangle
+                    .inner_shape
+                    .vertices()
+                    .iter()
+                    .map(to_world)
+                    .collect(),
+            );
+            outlines.push(Outline {
+                points: rounded_polygon(&points, triangle.border_radius),
+                closed: true,
+            });
+        }
+        TypedShape::ConvexPolygon(polygon) => outlines.push(Outline {
+            points: polygon.points().iter().map(to_world).collect(),
+            closed: true,
+        }),
+        TypedShape::RoundConvexPolygon(polygon) => {
+            let points = polygon
+                .inner_shape
+                .points()
+                .iter()
+                .map(to_world)
+                .collect::<Vec<Vec2>>();
+            outlines.push(Outline {
+                points: rounded_polygon(&points, polygon.border_radius),
+                closed: true,
+            });
+        }
+        TypedShape::TriMesh(trimesh) => {
+            for triangle in trimesh.triangles() {
+                outlines.push(Outline {
+                    points: triangle.vertices().iter().map(to_world).collect(),
+                    closed: true,
+                });
+            }
+        }
+        TypedShape::Polyline(polyline) => {
+            for segment in polyline.segments() {
+                outlines.push(Outline {
+                    points: vec![to_world(&segment.a), to_world(&segment.b)],
+                    closed: false,
+                });
+            }
+        }
+        TypedShape::HeightField(heightfield) => {
+            for segment in heightfield.segments() {
+                outlines.push(Outline {
+                    points: vec![to_world(&segment.a), to_world(&segment.b)],
+                    closed: false,
+                });
+            }
+        }
+        TypedShape::Compound(compound) => {
+            for (shape_position, shape) in compound.shapes() {
+                shape_outlines(shape.as_ref(), &(position * shape_position), outlines);
+            }
+        }
+        // Half spaces are infinite, and other shapes are 3D only.
+        _ => {}
+    }
+}
+
+/// Corners of a cuboid centered on the origin, counter clockwise.
+fn cuboid_points(half_width: f32, half_height: f32) -> [rapier2d::prelude::Point<f32>; 4] {
+    [
+        rapier2d::prelude::Point::new(-half_width, -half_height),
+        rapier2d::prelude::Point::new(half_width, -half_height),
+        rapier2d::prelude::Point::new(half_width, half_height),
+        rapier2d::prelude::Point::new(-half_width, half_height),
+    ]
+}
+
+fn counter_clockwise(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    if signed_area(&points) < 0.0 {
+        points.reverse();
+    }
+
+    points
+}
+
+/// Outline of a counter clockwise convex polygon grown by `radius`, with rounded corners.
+/// A single point gives a circle and two points give a capsule.
+fn rounded_polygon(points: &[Vec2], radius: f32) -> Vec<Vec2> {
+    if points.len() == 1 {
+        let segments = (PI * 2.0 / ARC_SEGMENT_ANGLE) as usize;
+        return (0..segments)
+            .map(|i| {
+                let angle = i as f32 / segments as f32 * PI * 2.0;
+                points[0] + vec2(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+    }
+
+    let outward_normal = |from: Vec2, to: Vec2| {
+        let direction = (to - from).normalize_or_zero();
+        vec2(direction.y, -direction.x)
+    };
+
+    let count = points.len();
+    let mut outline = Vec::new();
+    for i in 0..count {
+        let previous = points[(i + count - 1) % count];
+        let point = points[i];
+        let next = points[(i + 1) % count];
+
+        let start = outward_normal(previous, point);
+        let end = outward_normal(point, next);
+        let start_angle = start.y.atan2(start.x);
+        let mut sweep = end.y.atan2(end.x) - start_angle;
+        if sweep < 0.0 {
+            sweep += PI * 2.0;
+        }
+
+        let segments = (sweep / ARC_SEGMENT_ANGLE).ceil().max(1.0) as usize;
+        for segment in 0..=segments {
+            let angle = start_angle + sweep * segment as f32 / segments as f32;
+            outline.push(point + vec2(angle.cos(), angle.sin()) * radius);
+        }
+    }
+
+    outline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shape_outlines, Outline};
+    use rapier2d::prelude::{Isometry, SharedShape, Vector};
+
+    #[test]
+    fn cuboids_follow_the_collider_rotation() {
+        let shape = SharedShape::cuboid(2.0, 1.0);
+        let position = Isometry::new(Vector::new(10.0, 0.0), std::f32::consts::FRAC_PI_2);
+
+        let mut outlines = Vec::new();
+        shape_outlines(shape.as_ref(), &position, &mut outlines);
+
+        assert_eq!(outlines.len(), 1);
+        let Outline { points, closed } = &outlines[0];
+        assert!(*closed);
+        for point in points {
+            // Rotated by a quarter turn, the long side is now vertical.
+            assert!((point.x - 10.0).abs() <= 1.0001);
+            assert!((point.y.abs() - 2.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn capsules_are_rounded_at_both_ends() {
+        let shape = SharedShape::capsule_y(2.0, 1.0);
+
+        let mut outlines = Vec::new();
+        shape_outlines(shape.as_ref(), &Isometry::identity(), &mut outlines);
+
+        let points = &outlines[0].points;
+        let top = points.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        let side = points.iter().map(|p| p.x).fold(f32::MIN, f32::max);
+        assert!((top - 3.0).abs() < 0.0001);
+        assert!((side - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn compound_shapes_outline_every_part() {
+        let shape = SharedShape::compound(vec![
+            (Isometry::translation(-2.0, 0.0), SharedShape::ball(1.0)),
+            (
+                Isometry::translation(2.0, 0.0),
+                SharedShape::cuboid(1.0, 1.0),
+            ),
+        ]);
+
+        let mut outlines = Vec::new();
+        shape_outlines(shape.as_ref(), &Isometry::identity(), &mut outlines);
+
+        // The ball has an extra line showing its rotation.
+        assert_eq!(outlines.len(), 3);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::*;
+
+use glam::Vec4;
+use miniquad::UniformType;
+
+/// Post-process stages declaring a `Resolution` uniform of type `Float2`
+/// receive the size in pixels of the texture they sample.
+pub const RESOLUTION_UNIFORM_NAME: &str = "Resolution";
+
+/// Texture of the [`PostProcessEffect::Lut`] effect, set with `material.set_texture(LUT_TEXTURE_NAME, key)`.
+pub const LUT_TEXTURE_NAME: &str = "lut";
+
+/// Effects that ship with emerald, ready to be added to the post-process stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PostProcessEffect {
+    /// Darkens the edges of the screen.
+    /// Uniforms: `intensity`, `radius` and `softness`.
+    Vignette,
+
+    /// Barrel distortion and scanlines.
+    /// Uniforms: `curvature` and `scanline_intensity`.
+    Crt,
+
+    /// Bright areas bleed into their surroundings.
+    /// Uniforms: `threshold`, `intensity` and `radius` (in pixels).
+    Bloom,
+
+    /// Uniforms: `brightness`, `contrast`, `saturation` and `tint`.
+    ColorGrading,
+
+    /// Color grading through a lookup table, the [`LUT_TEXTURE_NAME`] texture.
+    /// The table is a strip of `lut_size` square cells of `lut_size` pixels, one per shade of blue from left to right,
+    /// with red increasing to the right and green increasing downward within each cell,
+    /// such as a 256x16 image for the default size of 16. Load it with linear filtering to blend between entries.
+    /// Uniforms: `lut_size` and `intensity`, blending from the original colors at 0.0 to the graded ones at 1.0.
+    Lut,
+}
+impl PostProcessEffect {
+    pub(crate) fn shader_name(&self) -> &'static str {
+        match self {
+            PostProcessEffect::Vignette => "emerald_post_process_vignette",
+            PostProcessEffect::Crt => "emerald_post_process_crt",
+            PostProcessEffect::Bloom => "emerald_post_process_bloom",
+            PostProcessEffect::ColorGrading => "emerald_post_process_color_grading",
+            PostProcessEffect::Lut => "emerald_post_process_lut",
+        }
+    }
+
+    pub(crate) fn fragment_shader(&self) -> &'static str {
+        match self {
+            PostProcessEffect::Vignette => VIGNETTE_FRAGMENT,
+            PostProcessEffect::Crt => CRT_FRAGMENT,
+            PostProcessEffect::Bloom => BLOOM_FRAGMENT,
+            PostProcessEffect::ColorGrading => COLOR_GRADING_FRAGMENT,
+            PostProcessEffect::Lut => LUT_FRAGMENT,
+        }
+    }
+
+    /// Samplers of the effect's shader on top of `tex`.
+    pub(crate) fn textures(&self) -> Vec<&'static str> {
+        match self {
+            PostProcessEffect::Lut => vec![LUT_TEXTURE_NAME],
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn uniforms(&self) -> Vec<(&'static str, UniformType)> {
+        match self {
+            PostProcessEffect::Vignette => vec![
+                ("intensity", UniformType::Float1),
+                ("radius", UniformType::Float1),
+                ("softness", UniformType::Float1),
+            ],
+            PostProcessEffect::Crt => vec![
+                (RESOLUTION_UNIFORM_NAME, UniformType::Float2),
+                ("curvature", UniformType::Float1),
+                ("scanline_intensity", UniformType::Float1),
+            ],
+            PostProcessEffect::Bloom => vec![
+                (RESOLUTION_UNIFORM_NAME, UniformType::Float2),
+                ("threshold", UniformType::Float1),
+                ("intensity", UniformType::Float1),
+                ("radius", UniformType::Float1),
+            ],
+            PostProcessEffect::ColorGrading => vec![
+                ("brightness", UniformType::Float1),
+                ("contrast", UniformType::Float1),
+                ("saturation", UniformType::Float1),
+                ("tint", UniformType::Float4),
+            ],
+            PostProcessEffect::Lut => vec![
+                ("lut_size", UniformType::Float1),
+                ("intensity", UniformType::Float1),
+            ],
+        }
+    }
+
+    pub(crate) fn default_uniforms(&self) -> Vec<(&'static str, UniformValue)> {
+        match self {
+            PostProcessEffect::Vignette => vec![
+                ("intensity", UniformValue::Float(1.0)),
+                ("radius", UniformValue::Float(0.75)),
+                ("softness", UniformValue::Float(0.45)),
+            ],
+            PostProcessEffect::Crt => vec![
+                ("curvature", UniformValue::Float(0.1)),
+                ("scanline_intensity", UniformValue::Float(0.25)),
+            ],
+            PostProcessEffect::Bloom => vec![
+                ("threshold", UniformValue::Float(0.7)),
+                ("intensity", UniformValue::Float(1.0)),
+                ("radius", UniformValue::Float(4.0)),
+            ],
+            PostProcessEffect::ColorGrading => vec![
+                ("brightness", UniformValue::Float(0.0)),
+                ("contrast", UniformValue::Float(1.0)),
+                ("saturation", UniformValue::Float(1.0)),
+                ("tint", UniformValue::Vec4(Vec4::ONE)),
+            ],
+            PostProcessEffect::Lut => vec![
+                ("lut_size", UniformValue::Float(16.0)),
+                ("intensity", UniformValue::Float(1.0)),
+            ],
+        }
+    }
+}
+
+const VIGNETTE_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform lowp float intensity;
+uniform mediump float radius;
+uniform mediump float softness;
+
+void main() {
+    lowp vec4 texel = texture2D(tex, uv) * color;
+    mediump float dist = distance(uv, vec2(0.5, 0.5));
+    lowp float vignette = 1.0 - smoothstep(radius - softness, radius, dist);
+
+    gl_FragColor = vec4(texel.rgb * mix(1.0, vignette, intensity), texel.a);
+}"#;
+
+const CRT_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform mediump vec2 Resolution;
+uniform mediump float curvature;
+uniform lowp float scanline_intensity;
+
+void main() {
+    mediump vec2 centered = uv * 2.0 - 1.0;
+    centered *= 1.0 + curvature * dot(centered, centered);
+    mediump vec2 curved_uv = centered * 0.5 + 0.5;
+
+    if (curved_uv.x < 0.0 || curved_uv.x > 1.0 || curved_uv.y < 0.0 || curved_uv.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    lowp vec4 texel = texture2D(tex, curved_uv) * color;
+    mediump float scanline = sin(curved_uv.y * Resolution.y * 3.14159) * 0.5 + 0.5;
+
+    gl_FragColor = vec4(texel.rgb * (1.0 - scanline_intensity * scanline), texel.a);
+}"#;
+
+const BLOOM_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform mediump vec2 Resolution;
+uniform lowp float threshold;
+uniform lowp float intensity;
+uniform mediump float radius;
+
+void main() {
+    lowp vec4 texel = texture2D(tex, uv) * color;
+    mediump vec2 offset = radius / 2.0 / Resolution;
+
+    mediump vec3 glow = vec3(0.0);
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            lowp vec3 neighbour = texture2D(tex, uv + vec2(float(x), float(y)) * offset).rgb;
+            glow += max(neighbour - vec3(threshold), vec3(0.0));
+        }
+    }
+
+    gl_FragColor = vec4(texel.rgb + glow / 25.0 * intensity, texel.a);
+}"#;
+
+const COLOR_GRADING_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform lowp float brightness;
+uniform lowp float contrast;
+uniform lowp float saturation;
+uniform lowp vec4 tint;
+
+void main() {
+    lowp vec4 texel = texture2D(tex, uv) * color;
+
+    lowp vec3 graded = texel.rgb + brightness;
+    graded = (graded - 0.5) * contrast + 0.5;
+    lowp float luminance = dot(graded, vec3(0.299, 0.587, 0.114));
+    graded = mix(vec3(luminance), graded, saturation) * tint.rgb;
+
+    gl_FragColor = vec4(clamp(graded, 0.0, 1.0), texel.a);
+}"#;
+
+// Blue picks the two closest cells, red and green are blended by the texture filtering within them.
+// Textures are flipped when loaded, so the first row of the image is at the top of the texture coordinates.
+const LUT_FRAGMENT: &str = r#"
+#version 100
+
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D tex;
+uniform sampler2D lut;
+uniform mediump float lut_size;
+uniform lowp float intensity;
+
+void main() {
+    lowp vec4 texel = texture2D(tex, uv) * color;
+    mediump vec3 scaled = clamp(texel.rgb, 0.0, 1.0) * (lut_size - 1.0);
+
+    mediump float blue_low = floor(scaled.b);
+    mediump float blue_high = min(blue_low + 1.0, lut_size - 1.0);
+    mediump vec2 cell_uv = vec2(
+        (scaled.r + 0.5) / (lut_size * lut_size),
+        1.0 - (scaled.g + 0.5) / lut_size
+    );
+
+    lowp vec3 low = texture2D(lut, cell_uv + vec2(blue_low / lut_size, 0.0)).rgb;
+    lowp vec3 high = texture2D(lut, cell_uv + vec2(blue_high / lut_size, 0.0)).rgb;
+    lowp vec3 graded = mix(low, high, scaled.b - blue_low);
+
+    gl_FragColor = vec4(mix(texel.rgb, graded, intensity), texel.a);
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::PostProcessEffect;
+    use crate::{Material, ShaderKey};
+
+    #[test]
+    fn default_uniforms_match_the_declared_uniforms() {
+        let effects = [
+            PostProcessEffect::Vignette,
+            PostProcessEffect::Crt,
+            PostProcessEffect::Bloom,
+            PostProcessEffect::ColorGrading,
+            PostProcessEffect::Lut,
+        ];
+
+        for effect in effects.iter() {
+            let uniforms = effect
+                .uniforms()
+                .into_iter()
+                .map(|(name, uniform_type)| (name.to_string(), uniform_type))
+                .collect::<Vec<_>>();
+            let textures = effect
+                .textures()
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let mut material =
+                Material::new(ShaderKey::new(effect.shader_name()), &uniforms, &textures);
+
+            for (name, value) in effect.default_uniforms() {
+                assert!(material.set_uniform(name, value).is_ok(), "{:?}", effect);
+            }
+        }
+    }
+}
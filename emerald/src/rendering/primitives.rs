@@ -0,0 +1,285 @@
+use crate::*;
+
+use glam::{vec2, Vec2};
+use std::f32::consts::PI;
+
+/// Miters longer than this many times the half thickness are cut short, to avoid spikes on sharp corners.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Triangles in local space, drawn with a flat color.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Primitive {
+    pub points: Vec<Vec2>,
+    pub indices: Vec<u16>,
+}
+impl Primitive {
+    pub(crate) fn line(start: Vec2, end: Vec2, thickness: f32) -> Self {
+        Self::polyline(&[start, end], thickness, false)
+    }
+
+    /// A strip of quads following the points, joined with miters.
+    pub(crate) fn polyline(points: &[Vec2], thickness: f32, closed: bool) -> Self {
+        let points = dedup_points(points, closed);
+        if points.len() < 2 {
+            return Primitive::default();
+        }
+
+        let half_thickness = thickness / 2.0;
+        let count = points.len();
+        let segment_normal = |from: Vec2, to: Vec2| {
+            let direction = (to - from).normalize();
+            vec2(-direction.y, direction.x)
+        };
+
+        let mut vertices = Vec::with_capacity(count * 2);
+        for i in 0..count {
+            let previous = if i > 0 {
+                Some(points[i - 1])
+            } else if closed {
+                Some(points[count - 1])
+            } else {
+                None
+            };
+            let next = if i + 1 < count {
+                Some(points[i + 1])
+            } else if closed {
+                Some(points[0])
+            } else {
+                None
+            };
+
+            let point = points[i];
+            let offset = match (previous, next) {
+                (Some(previous), Some(next)) => {
+                    let normal_in = segment_normal(previous, point);
+                    let normal_out = segment_normal(point, next);
+                    let miter = (normal_in + normal_out).normalize_or_zero();
+                    let cos = miter.dot(normal_out);
+
+                    if miter == Vec2::ZERO || cos.abs() < 1.0 / MITER_LIMIT {
+                        normal_out * half_thickness
+                    } else {
+                        miter * (half_thickness / cos)
+                    }
+                }
+                (Some(previous), None) => segment_normal(previous, point) * half_thickness,
+                (None, Some(next)) => segment_normal(point, next) * half_thickness,
+                (None, None) => Vec2::ZERO,
+            };
+
+            vertices.push(point + offset);
+            vertices.push(point - offset);
+        }
+
+        let segment_count = if closed { count } else { count - 1 };
+        let mut indices = Vec::with_capacity(segment_count * 6);
+        for i in 0..segment_count {
+            let a = (i * 2) as u16;
+            let b = (((i + 1) % count) * 2) as u16;
+            indices.extend_from_slice(&[a, a + 1, b + 1, a, b + 1, b]);
+        }
+
+        Primitive {
+            points: vertices,
+            indices,
+        }
+    }
+
+    /// A filled circle, as a fan with enough segments to look round at its size.
+    pub(crate) fn circle(center: Vec2, radius: f32) -> Self {
+        let segments = ((radius.abs() * PI / 4.0).ceil() as usize).clamp(8, 128);
+
+        let mut points = Vec::with_capacity(segments + 1);
+        points.push(center);
+        for i in 0..segments {
+            let angle = i as f32 / segments as f32 * PI * 2.0;
+            points.push(center + vec2(angle.cos(), angle.sin()) * radius);
+        }
+
+        let mut indices = Vec::with_capacity(segments * 3);
+        for i in 0..segments {
+            let current = (i + 1) as u16;
+            let next = ((i + 1) % segments + 1) as u16;
+            indices.extend_from_slice(&[0, current, next]);
+        }
+
+        Primitive { points, indices }
+    }
+
+    /// A filled simple polygon, convex or not. Self intersecting polygons are not supported.
+    pub(crate) fn polygon(points: &[Vec2]) -> Self {
+        let points = dedup_points(points, true);
+        let indices = triangulate(&points);
+
+        Primitive { points, indices }
+    }
+
+    pub(crate) fn rect_outline(rect: &Rectangle, thickness: f32) -> Self {
+        let corners = [
+            vec2(rect.x, rect.y),
+            vec2(rect.x + rect.width, rect.y),
+            vec2(rect.x + rect.width, rect.y + rect.height),
+            vec2(rect.x, rect.y + rect.height),
+        ];
+
+        Self::polyline(&corners, thickness, true)
+    }
+}
+
+/// Removes consecutive duplicates, which have no direction to extrude or clip along.
+fn dedup_points(points: &[Vec2], closed: bool) -> Vec<Vec2> {
+    let mut deduped: Vec<Vec2> = Vec::with_capacity(points.len());
+    for point in points {
+        if deduped.last() != Some(point) {
+            deduped.push(*point);
+        }
+    }
+
+    if closed && deduped.len() > 1 && deduped.first() == deduped.last() {
+        deduped.pop();
+    }
+
+    deduped
+}
+
+pub(crate) fn signed_area(points: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    area / 2.0
+}
+
+#[inline]
+fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a)
+}
+
+fn is_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
+
+/// Ear clipping triangulation of a simple polygon.
+fn triangulate(points: &[Vec2]) -> Vec<u16> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    let mut remaining = (0..points.len()).collect::<Vec<usize>>();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|i| {
+            let a = points[remaining[(i + count - 1) % count]];
+            let b = points[remaining[*i]];
+            let c = points[remaining[(i + 1) % count]];
+
+            cross(a, b, c) > 0.0
+                && remaining
+                    .iter()
+                    .map(|index| points[*index])
+                    .filter(|point| *point != a && *point != b && *point != c)
+                    .all(|point| !is_in_triangle(point, a, b, c))
+        });
+
+        // Only degenerate polygons have no ear left, fall back to a fan for what remains.
+        let ear = match ear {
+            Some(ear) => ear,
+            None => break,
+        };
+
+        indices.extend_from_slice(&[
+            remaining[(ear + count - 1) % count] as u16,
+            remaining[ear] as u16,
+            remaining[(ear + 1) % count] as u16,
+        ]);
+        remaining.remove(ear);
+    }
+
+    for i in 1..(remaining.len() - 1) {
+        indices.extend_from_slice(&[
+            remaining[0] as u16,
+            remaining[i] as u16,
+            remaining[i + 1] as u16,
+        ]);
+    }
+
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{signed_area, Primitive};
+    use crate::Rectangle;
+    use glam::{vec2, Vec2};
+
+    fn triangles_area(primitive: &Primitive) -> f32 {
+        primitive
+            .indices
+            .chunks(3)
+            .map(|triangle| {
+                let points = triangle
+                    .iter()
+                    .map(|index| primitive.points[*index as usize])
+                    .collect::<Vec<Vec2>>();
+                signed_area(&points).abs()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn concave_polygons_are_triangulated() {
+        let l_shape = [
+            vec2(0.0, 0.0),
+            vec2(2.0, 0.0),
+            vec2(2.0, 1.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, 2.0),
+            vec2(0.0, 2.0),
+        ];
+        let primitive = Primitive::polygon(&l_shape);
+
+        assert_eq!(primitive.indices.len(), 4 * 3);
+        assert!((triangles_area(&primitive) - 3.0).abs() < 0.0001);
+
+        let mut clockwise = l_shape.to_vec();
+        clockwise.reverse();
+        let primitive = Primitive::polygon(&clockwise);
+        assert!((triangles_area(&primitive) - 3.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn lines_are_extruded_by_half_their_thickness() {
+        let primitive = Primitive::line(vec2(0.0, 0.0), vec2(10.0, 0.0), 4.0);
+
+        assert_eq!(primitive.indices.len(), 6);
+        assert!((triangles_area(&primitive) - 40.0).abs() < 0.0001);
+        assert!(primitive.points.iter().all(|point| point.y.abs() == 2.0));
+    }
+
+    #[test]
+    fn rect_outlines_have_mitered_corners() {
+        let primitive = Primitive::rect_outline(&Rectangle::new(0.0, 0.0, 10.0, 10.0), 2.0);
+
+        assert_eq!(primitive.indices.len(), 4 * 6);
+        // The outline covers the band between the 12x12 and 8x8 squares.
+        assert!((triangles_area(&primitive) - (144.0 - 64.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn degenerate_shapes_are_empty() {
+        assert!(Primitive::line(vec2(1.0, 1.0), vec2(1.0, 1.0), 2.0)
+            .indices
+            .is_empty());
+        assert!(Primitive::polygon(&[vec2(0.0, 0.0), vec2(1.0, 1.0)])
+            .indices
+            .is_empty());
+    }
+}
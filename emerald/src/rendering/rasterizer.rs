@@ -0,0 +1,261 @@
+use crate::rendering::*;
+use crate::*;
+
+use glam::{vec2, Vec2, Vec4};
+
+/// Fills an RGBA8 image with a single color.
+pub(crate) fn clear_pixels(pixels: &mut [u8], color: Color) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+}
+
+/// Draws triangles into an RGBA8 target the same way the default pipeline does on the gpu:
+/// the texture is sampled with nearest filtering, multiplied by the vertex color,
+/// then alpha blended over the target while keeping the target's alpha.
+///
+/// Vertex positions are in pixels of the target, with the first row of the target at the bottom.
+/// A texture without pixels on the cpu is sampled as plain white.
+pub(crate) fn rasterize_triangles(
+    target: &mut [u8],
+    target_size: (usize, usize),
+    texture: &Texture,
+    vertices: &[Vertex],
+    indices: &[u16],
+) {
+    let (target_width, target_height) = target_size;
+
+    for triangle in indices.chunks_exact(3) {
+        let mut triangle = [
+            vertices[triangle[0] as usize],
+            vertices[triangle[1] as usize],
+            vertices[triangle[2] as usize],
+        ];
+
+        // Both windings are drawn, flipped geometry such as labels winds clockwise.
+        let mut area = edge(
+            triangle[0].position,
+            triangle[1].position,
+            triangle[2].position,
+        );
+        if area == 0.0 {
+            continue;
+        }
+        if area < 0.0 {
+            triangle.swap(1, 2);
+            area = -area;
+        }
+
+        let [v0, v1, v2] = triangle;
+        let min = v0.position.min(v1.position).min(v2.position);
+        let max = v0.position.max(v1.position).max(v2.position);
+        let min_x = min.x.floor().max(0.0) as usize;
+        let min_y = min.y.floor().max(0.0) as usize;
+        let max_x = (max.x.ceil().max(0.0) as usize).min(target_width);
+        let max_y = (max.y.ceil().max(0.0) as usize).min(target_height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let point = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(v1.position, v2.position, point);
+                let w1 = edge(v2.position, v0.position, point);
+                let w2 = edge(v0.position, v1.position, point);
+
+                if !covers(w0, v1.position, v2.position)
+                    || !covers(w1, v2.position, v0.position)
+                    || !covers(w2, v0.position, v1.position)
+                {
+                    continue;
+                }
+
+                let (b0, b1, b2) = (w0 / area, w1 / area, w2 / area);
+                let uv = v0.uv * b0 + v1.uv * b1 + v2.uv * b2;
+                let color = v0.color * b0 + v1.color * b1 + v2.color * b2;
+
+                let index = (y * target_width + x) * 4;
+                blend(&mut target[index..index + 4], sample(texture, uv) * color);
+            }
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`, positive when `p` is left of `a` -> `b`.
+#[inline]
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether a pixel center at the given edge distance belongs to the triangle.
+/// Centers lying exactly on an edge only belong to one side of it, so triangles
+/// sharing an edge never blend the same pixel twice.
+#[inline]
+fn covers(distance: f32, a: Vec2, b: Vec2) -> bool {
+    if distance != 0.0 {
+        return distance > 0.0;
+    }
+
+    let direction = b - a;
+    direction.y < 0.0 || (direction.y == 0.0 && direction.x > 0.0)
+}
+
+#[inline]
+fn sample(texture: &Texture, uv: Vec2) -> Vec4 {
+    let pixels = match texture.pixels.as_ref() {
+        Some(pixels) => pixels,
+        None => return Vec4::ONE,
+    };
+
+    let width = texture.width as usize;
+    let height = texture.height as usize;
+    if width == 0 || height == 0 {
+        return Vec4::ZERO;
+    }
+
+    let x = ((uv.x * width as f32).floor().max(0.0) as usize).min(width - 1);
+    let y = ((uv.y * height as f32).floor().max(0.0) as usize).min(height - 1);
+    let index = (y * width + x) * 4;
+
+    Vec4::new(
+        pixels[index] as f32,
+        pixels[index + 1] as f32,
+        pixels[index + 2] as f32,
+        pixels[index + 3] as f32,
+    ) / 255.0
+}
+
+#[inline]
+fn blend(destination: &mut [u8], source: Vec4) {
+    let source = source.max(Vec4::ZERO).min(Vec4::ONE);
+
+    for channel in 0..3 {
+        let value =
+            source[channel] * source.w + destination[channel] as f32 / 255.0 * (1.0 - source.w);
+        destination[channel] = (value * 255.0).round() as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{vec2, Mat4, Vec4};
+
+    use crate::rendering::*;
+    use crate::*;
+
+    fn white_texture() -> Texture {
+        Texture::from_rgba8(None, TextureKey::default(), 1, 1, &[255, 255, 255, 255]).unwrap()
+    }
+
+    fn pixel(target: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * width + x) * 4;
+        [
+            target[index],
+            target[index + 1],
+            target[index + 2],
+            target[index + 3],
+        ]
+    }
+
+    #[test]
+    fn quad_covers_exactly_its_pixels() {
+        let mut target = vec![0; 8 * 8 * 4];
+        let model = Mat4::from_translation(vec2(2.0, 3.0).extend(0.0))
+            * Mat4::from_scale(vec2(4.0, 2.0).extend(1.0));
+        let vertices = SpriteBatch::quad(
+            &model,
+            &Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            Color::new(255, 0, 0, 255),
+        );
+
+        rasterize_triangles(
+            &mut target,
+            (8, 8),
+            &white_texture(),
+            &vertices,
+            &QUAD_INDICES,
+        );
+
+        let covered = (0..8)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .filter(|(x, y)| pixel(&target, 8, *x, *y) != [0, 0, 0, 0])
+            .collect::<Vec<_>>();
+        assert_eq!(covered.len(), 8);
+        assert!(covered
+            .iter()
+            .all(|(x, y)| (2..6).contains(x) && (3..5).contains(y)));
+        assert_eq!(pixel(&target, 8, 2, 3), [255, 0, 0, 0]);
+    }
+
+    #[test]
+    fn shared_edges_are_blended_once() {
+        let mut target = vec![0; 4 * 4 * 4];
+        let model = Mat4::from_scale(vec2(4.0, 4.0).extend(1.0));
+        let half_transparent = Color::new(255, 255, 255, 128);
+        let vertices = SpriteBatch::quad(
+            &model,
+            &Rectangle::new(0.0, 0.0, 1.0, 1.0),
+            half_transparent,
+        );
+
+        rasterize_triangles(
+            &mut target,
+            (4, 4),
+            &white_texture(),
+            &vertices,
+            &QUAD_INDICES,
+        );
+
+        // The diagonal of the quad runs through pixel centers.
+        for i in 0..4 {
+            assert_eq!(pixel(&target, 4, i, i), [128, 128, 128, 0]);
+        }
+    }
+
+    #[test]
+    fn samples_nearest_texel() {
+        // Two texels wide, the first one black and the second one white.
+        let texture = Texture::from_rgba8(
+            None,
+            TextureKey::default(),
+            2,
+            1,
+            &[0, 0, 0, 255, 255, 255, 255, 255],
+        )
+        .unwrap();
+        let mut target = vec![0; 4 * 4];
+        let model = Mat4::from_scale(vec2(4.0, 1.0).extend(1.0));
+        let vertices = SpriteBatch::quad(&model, &Rectangle::new(0.0, 0.0, 1.0, 1.0), WHITE);
+
+        rasterize_triangles(&mut target, (4, 1), &texture, &vertices, &QUAD_INDICES);
+
+        assert_eq!(pixel(&target, 4, 1, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&target, 4, 2, 0), [255, 255, 255, 0]);
+    }
+
+    #[test]
+    fn empty_textures_draw_nothing() {
+        let texture = Texture::from_rgba8(None, TextureKey::default(), 0, 4, &[]).unwrap();
+        let mut target = vec![0; 4 * 4];
+        let model = Mat4::from_scale(vec2(4.0, 1.0).extend(1.0));
+        let vertices = SpriteBatch::quad(&model, &Rectangle::new(0.0, 0.0, 1.0, 1.0), WHITE);
+
+        rasterize_triangles(&mut target, (4, 1), &texture, &vertices, &QUAD_INDICES);
+
+        assert!(target.iter().all(|channel| *channel == 0));
+    }
+
+    #[test]
+    fn clockwise_triangles_are_drawn() {
+        let mut target = vec![0; 4 * 4 * 4];
+        let vertex = |x, y| Vertex {
+            position: vec2(x, y),
+            uv: vec2(0.0, 0.0),
+            color: Vec4::ONE,
+        };
+        let vertices = [vertex(0.0, 0.0), vertex(0.0, 4.0), vertex(4.0, 0.0)];
+
+        rasterize_triangles(&mut target, (4, 4), &white_texture(), &vertices, &[0, 1, 2]);
+
+        assert_eq!(pixel(&target, 4, 0, 0), [255, 255, 255, 0]);
+        assert_eq!(pixel(&target, 4, 3, 3), [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::rendering::*;
+use crate::*;
+
+use image::codecs::png::PngEncoder;
+use image::ColorType;
+use std::path::Path;
+
+/// An RGBA8 image of a frame rendered by the software rasterizer, see [`crate::HeadlessRunner`].
+/// Pixels are stored row by row from the top of the image, as in a PNG file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Screenshot {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+impl Screenshot {
+    /// `None` when the texture has no pixels on the cpu.
+    pub(crate) fn from_texture(texture: &Texture) -> Option<Self> {
+        let pixels = texture.pixels.as_ref()?;
+        let row_len = texture.width as usize * 4;
+
+        // Textures store their first row at the bottom.
+        let pixels = pixels
+            .chunks_exact(row_len)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Some(Screenshot {
+            width: texture.width as u32,
+            height: texture.height as u32,
+            pixels,
+        })
+    }
+
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, EmeraldError> {
+        let img = image::load_from_memory(bytes)?.to_rgba8();
+
+        Ok(Screenshot {
+            width: img.width(),
+            height: img.height(),
+            pixels: img.into_raw(),
+        })
+    }
+
+    pub fn load_png<P: AsRef<Path>>(path: P) -> Result<Self, EmeraldError> {
+        Self::from_png_bytes(&std::fs::read(path)?)
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA8 pixels, row by row from the top of the image.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Color of a pixel in screen space, with the origin at the bottom left like the rest of the engine.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let row = (self.height - 1 - y) as usize;
+        let index = (row * self.width as usize + x as usize) * 4;
+
+        Some(Color::new(
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+            self.pixels[index + 3],
+        ))
+    }
+
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>, EmeraldError> {
+        let mut bytes = Vec::new();
+        PngEncoder::new(&mut bytes).encode(
+            &self.pixels,
+            self.width,
+            self.height,
+            ColorType::Rgba8,
+        )?;
+
+        Ok(bytes)
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), EmeraldError> {
+        std::fs::write(path, self.to_png_bytes()?)?;
+
+        Ok(())
+    }
+
+    /// Amount of pixels with a channel that differs from the reference by more than `tolerance`.
+    pub fn diff(&self, reference: &Screenshot, tolerance: u8) -> Result<usize, EmeraldError> {
+        if (self.width, self.height) != (reference.width, reference.height) {
+            return Err(EmeraldError::new(format!(
+                "Cannot compare a {}x{} screenshot to a {}x{} reference.",
+                self.width, self.height, reference.width, reference.height
+            )));
+        }
+
+        let differing_pixels = self
+            .pixels
+            .chunks_exact(4)
+            .zip(reference.pixels.chunks_exact(4))
+            .filter(|(pixel, reference_pixel)| {
+                pixel
+                    .iter()
+                    .zip(reference_pixel.iter())
+                    .any(|(a, b)| (*a as i16 - *b as i16).abs() > tolerance as i16)
+            })
+            .count();
+
+        Ok(differing_pixels)
+    }
+
+    /// Compares against a reference PNG, see [`Screenshot::diff`].
+    pub fn matches_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        tolerance: u8,
+    ) -> Result<bool, EmeraldError> {
+        let reference = Self::load_png(path)?;
+
+        Ok(self.diff(&reference, tolerance)? == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rendering::*;
+    use crate::*;
+
+    use super::Screenshot;
+
+    fn screenshot() -> Screenshot {
+        // 1x2, a red pixel on the bottom row and a blue one above it.
+        let texture = Texture::from_rgba8(
+            None,
+            TextureKey::default(),
+            1,
+            2,
+            &[255, 0, 0, 255, 0, 0, 255, 255],
+        )
+        .unwrap();
+
+        Screenshot::from_texture(&texture).unwrap()
+    }
+
+    #[test]
+    fn pixels_are_read_from_the_bottom() {
+        let screenshot = screenshot();
+
+        assert_eq!(screenshot.get_pixel(0, 0), Some(Color::new(255, 0, 0, 255)));
+        assert_eq!(screenshot.get_pixel(0, 1), Some(Color::new(0, 0, 255, 255)));
+        assert_eq!(screenshot.get_pixel(0, 2), None);
+        assert_eq!(&screenshot.pixels()[0..4], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let screenshot = screenshot();
+        let bytes = screenshot.to_png_bytes().unwrap();
+
+        assert_eq!(Screenshot::from_png_bytes(&bytes).unwrap(), screenshot);
+    }
+
+    #[test]
+    fn diff_counts_pixels_beyond_tolerance() {
+        let reference = screenshot();
+        let mut screenshot = reference.clone();
+        screenshot.pixels[0] = 10;
+        screenshot.pixels[4] = 252;
+
+        assert_eq!(screenshot.diff(&reference, 0).unwrap(), 2);
+        assert_eq!(screenshot.diff(&reference, 5).unwrap(), 1);
+        assert_eq!(screenshot.diff(&reference, 10).unwrap(), 0);
+    }
+}
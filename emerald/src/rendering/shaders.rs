@@ -1,52 +1,32 @@
-use glam::{Mat4, Vec2, Vec4};
+use crate::rendering::PROJECTION_UNIFORM_NAME;
+use glam::{Vec2, Vec4};
 use miniquad::*;
 
+/// Vertices are transformed on the cpu, so that sprites sharing a texture can be drawn in a single batch.
 #[repr(C)]
+#[derive(Clone, Copy, Debug)]
 pub struct Vertex {
     pub position: Vec2,
-}
-
-#[repr(C)]
-#[derive(Debug)]
-pub struct Uniforms {
-    pub projection: Mat4,
-    pub model: Mat4,
-    pub source: Vec4,
+    pub uv: Vec2,
     pub color: Vec4,
-    pub z_index: f32,
-}
-impl Default for Uniforms {
-    fn default() -> Uniforms {
-        Uniforms {
-            projection: Mat4::orthographic_lh(0.0, 1.0, 0.0, 1.0, -100.0, 100.0),
-            model: Mat4::IDENTITY,
-            source: Vec4::new(0.0, 0.0, 1.0, 1.0),
-            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
-            z_index: 0.0,
-        }
-    }
 }
 
 pub const VERTEX: &str = r#"
 #version 100
 
 attribute vec2 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
 
 varying lowp vec4 color;
 varying lowp vec2 uv;
 
 uniform mat4 Projection;
-uniform vec4 Source;
-uniform vec4 Color;
-uniform mat4 Model;
-
-uniform float z_index;
 
 void main() {
-    gl_Position = Projection * Model * vec4(position, 0, 1);
-    gl_Position.z = z_index;
-    color = Color;
-    uv = position * Source.zw + Source.xy;
+    gl_Position = Projection * vec4(position, 0, 1);
+    color = color0;
+    uv = texcoord;
 }"#;
 
 pub const FRAGMENT: &str = r#"
@@ -62,40 +42,35 @@ void main() {
 }"#;
 
 pub fn meta() -> ShaderMeta {
+    meta_with_uniforms(&[], &[])
+}
+
+/// Layout of a shader declaring `uniforms` on top of the projection and `textures` on top of `tex`.
+pub(crate) fn meta_with_uniforms(
+    uniforms: &[(String, UniformType)],
+    textures: &[String],
+) -> ShaderMeta {
+    let mut uniform_descs = vec![UniformDesc::new(PROJECTION_UNIFORM_NAME, UniformType::Mat4)];
+    uniform_descs.extend(
+        uniforms
+            .iter()
+            .map(|(name, uniform_type)| UniformDesc::new(name, *uniform_type)),
+    );
+
     ShaderMeta {
-        images: vec![String::from("tex")],
+        images: std::iter::once(String::from("tex"))
+            .chain(textures.iter().cloned())
+            .collect(),
         uniforms: UniformBlockLayout {
-            uniforms: vec![
-                UniformDesc::new("Projection", UniformType::Mat4),
-                UniformDesc::new("Model", UniformType::Mat4),
-                UniformDesc::new("Source", UniformType::Float4),
-                UniformDesc::new("Color", UniformType::Float4),
-                UniformDesc::new("z_index", UniformType::Float1),
-            ],
+            uniforms: uniform_descs,
         },
     }
 }
 
-// Credit(https://github.com/not-fl3/good-web-game/blob/master/src/graphics/image.rs#L129)
-pub(crate) fn param_to_instance_transform(
-    rotation: f32,
-    scale: Vec2,
-    offset: Vec2,
-    dest: Vec2,
-) -> Mat4 {
-    let cosr = rotation.cos();
-    let sinr = rotation.sin();
-    let m00 = cosr * scale.x;
-    let m01 = -sinr * scale.y;
-    let m10 = sinr * scale.x;
-    let m11 = cosr * scale.y;
-    let m03 = offset.x * (1.0 - m00) - offset.y * m01 + dest.x;
-    let m13 = offset.y * (1.0 - m11) - offset.x * m10 + dest.y;
-
-    Mat4::from_cols(
-        Vec4::new(m00, m10, 0.0, 0.0),
-        Vec4::new(m01, m11, 0.0, 0.0),
-        Vec4::new(0.0, 0.0, 1.0, 0.0),
-        Vec4::new(m03, m13, 0.0, 1.0),
-    )
+pub(crate) fn vertex_attributes() -> [VertexAttribute; 3] {
+    [
+        VertexAttribute::new("position", VertexFormat::Float2),
+        VertexAttribute::new("texcoord", VertexFormat::Float2),
+        VertexAttribute::new("color0", VertexFormat::Float4),
+    ]
 }
@@ -1,8 +1,7 @@
 use crate::rendering::font::*;
-use crate::rendering::shaders::*;
 use crate::*;
-use glam::Vec2;
-use miniquad::{Bindings, Buffer, BufferType, Context, FilterMode};
+use miniquad::{Context, FilterMode};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::Arc;
 
 pub const EMERALD_DEFAULT_TEXTURE_NAME: &str = "emerald_default_texture";
@@ -14,18 +13,20 @@ pub struct Texture {
     pub(crate) width: u16,
     pub(crate) height: u16,
     pub(crate) filter: FilterMode,
-    pub(crate) bindings: Bindings,
+    /// RGBA8 pixels of a headless texture, with the first row at the bottom as on the gpu.
+    /// Read and written by the software rasterizer.
+    pub(crate) pixels: Option<Vec<u8>>,
 }
 impl Texture {
     pub(crate) fn new(
-        mut ctx: &mut Context,
+        ctx: Option<&mut Context>,
         key: TextureKey,
         data: Vec<u8>,
     ) -> Result<Self, EmeraldError> {
-        Self::from_png_bytes(&mut ctx, key, &data)
+        Self::from_png_bytes(ctx, key, &data)
     }
 
-    pub fn default(mut ctx: &mut Context) -> Result<Self, EmeraldError> {
+    pub fn default(ctx: Option<&mut Context>) -> Result<Self, EmeraldError> {
         let pixels: [u8; 4 * 4 * 4] = [
             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
@@ -34,17 +35,18 @@ impl Texture {
             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
         ];
 
-        let texture = miniquad::Texture::from_rgba8(ctx, 4, 4, &pixels);
-
-        Self::from_texture(
-            &mut ctx,
+        Self::from_rgba8(
+            ctx,
             TextureKey::new(EMERALD_DEFAULT_TEXTURE_NAME),
-            texture,
+            4,
+            4,
+            &pixels,
         )
     }
 
+    /// Without a rendering context, as in headless mode, the pixels are kept on the cpu instead.
     pub fn from_png_bytes(
-        ctx: &mut Context,
+        ctx: Option<&mut Context>,
         key: TextureKey,
         bytes: &[u8],
     ) -> Result<Self, EmeraldError> {
@@ -59,57 +61,67 @@ impl Texture {
     }
 
     pub(crate) fn from_rgba8(
-        mut ctx: &mut Context,
+        ctx: Option<&mut Context>,
         key: TextureKey,
         width: u16,
         height: u16,
         bytes: &[u8],
     ) -> Result<Self, EmeraldError> {
-        let texture = miniquad::Texture::from_rgba8(&mut ctx, width, height, bytes);
-
-        Self::from_texture(&mut ctx, key, texture)
+        match ctx {
+            Some(ctx) => {
+                let texture = miniquad::Texture::from_rgba8(ctx, width, height, bytes);
+                Self::from_texture(key, texture)
+            }
+            None => {
+                let mut texture =
+                    Self::from_texture(key, headless_texture(width as u32, height as u32))?;
+                texture.pixels = Some(bytes.to_vec());
+
+                Ok(texture)
+            }
+        }
     }
 
     pub(crate) fn from_texture(
-        ctx: &mut miniquad::Context,
         key: TextureKey,
         texture: miniquad::Texture,
     ) -> Result<Self, EmeraldError> {
-        #[rustfmt::skip]
-        let vertices: [Vertex; 4] = [
-            Vertex { position: Vec2::new(0.0, 0.0) },
-            Vertex { position: Vec2::new(1.0, 0.0) },
-            Vertex { position: Vec2::new(1.0, 1.0) },
-            Vertex { position: Vec2::new(0.0, 1.0) },
-        ];
-
-        let vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &vertices);
-        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
-        let index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
-        let bindings = Bindings {
-            vertex_buffers: vec![vertex_buffer],
-            index_buffer,
-            images: vec![texture],
-        };
-
         Ok(Texture {
             key,
             width: texture.width as u16,
             height: texture.height as u16,
             inner: texture,
-            bindings,
             filter: FilterMode::Nearest,
+            pixels: None,
         })
     }
 
-    pub(crate) fn update(&mut self, ctx: &mut miniquad::Context, font_image: &FontImage) {
+    pub(crate) fn update(&mut self, ctx: Option<&mut Context>, font_image: &FontImage) {
         assert_eq!(self.inner.width, font_image.width as u32);
         assert_eq!(self.inner.height, font_image.height as u32);
 
-        self.inner.update(ctx, &font_image.bytes);
+        match ctx {
+            Some(ctx) => self.inner.update(ctx, &font_image.bytes),
+            None => self.pixels = Some(font_image.bytes.clone()),
+        }
+    }
+
+    /// Whether the texture only exists on the cpu side, having been created without a rendering context.
+    #[inline]
+    pub(crate) fn is_headless(&self) -> bool {
+        self.inner.gl_internal_id() == 0
     }
 }
 
+/// A texture handle that doesn't refer to any gpu texture.
+pub(crate) fn headless_texture(width: u32, height: u32) -> miniquad::Texture {
+    let mut texture = miniquad::Texture::empty();
+    texture.width = width;
+    texture.height = height;
+
+    texture
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct TextureKey(pub(crate) Arc<String>);
 impl TextureKey {
@@ -126,3 +138,15 @@ impl Default for TextureKey {
         TextureKey(Arc::new(String::from(EMERALD_DEFAULT_TEXTURE_NAME)))
     }
 }
+/// Serialized as the name of the texture, which has to be loaded again under that name
+/// for components holding the key to draw after being deserialized.
+impl Serialize for TextureKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+impl<'de> Deserialize<'de> for TextureKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(TextureKey::new(String::deserialize(deserializer)?))
+    }
+}
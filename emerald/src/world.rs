@@ -2,16 +2,26 @@
 pub mod physics;
 
 pub mod ent;
+pub mod snapshot;
+pub mod wrld;
+
+pub use snapshot::WorldSnapshot;
 
 use std::collections::HashMap;
 
 use crate::rendering::components::Camera;
-use crate::EmeraldError;
+use crate::world::ent::{save_ent, EntSaveConfig};
+use crate::world::snapshot::SnapshotRegistry;
+use crate::world::wrld::{save_wrld, WorldSaveConfig};
+use crate::{
+    parent_global_transform, Children, EmeraldError, Parent, PreviousTransform, Transform,
+};
 
 use hecs::{
     Bundle, Component, DynamicBundle, Entity, NoSuchEntity, Query, QueryBorrow, QueryItem,
     QueryOne, Ref, RefMut, SpawnBatchIter,
 };
+use serde::{de::DeserializeOwned, Serialize};
 
 #[cfg(feature = "physics")]
 use crate::world::physics::*;
@@ -22,6 +32,7 @@ pub struct World {
     #[cfg(feature = "physics")]
     pub(crate) physics_engine: PhysicsEngine,
     pub(crate) inner: hecs::World,
+    snapshot_registry: SnapshotRegistry,
 }
 impl Default for World {
     fn default() -> Self {
@@ -29,6 +40,7 @@ impl Default for World {
             #[cfg(feature = "physics")]
             physics_engine: PhysicsEngine::new(),
             inner: hecs::World::default(),
+            snapshot_registry: SnapshotRegistry::default(),
         }
     }
 }
@@ -42,6 +54,7 @@ impl World {
     /// All entities are placed into this world at their current transform.
     /// The camera of the primary world will remain the current camera.
     /// If physics is enabled, will keep its own physics settings.
+    /// Parent/child relationships within the other world are preserved.
     /// Returns a map of OldEntity -> NewEntity. If you have components that store Entity references, use this map to update your references.
     pub fn merge(
         &mut self,
@@ -53,6 +66,9 @@ impl World {
             .iter()
             .map(|entity_ref| entity_ref.entity())
             .collect::<Vec<Entity>>();
+        // Joints go away along with the bodies of the other world, so they're collected beforehand.
+        #[cfg(feature = "physics")]
+        let other_joints = other_world.physics_engine.get_entity_joints();
 
         for old_id in other_entities {
             match other_world.inner.take(old_id.clone()) {
@@ -72,9 +88,41 @@ impl World {
             }
         }
 
+        self.remap_hierarchy(&entity_id_shift_map);
+
+        #[cfg(feature = "physics")]
+        for (old_entity_one, old_entity_two, joint) in other_joints {
+            if let (Some(entity_one), Some(entity_two)) = (
+                entity_id_shift_map.get(&old_entity_one),
+                entity_id_shift_map.get(&old_entity_two),
+            ) {
+                self.physics_engine
+                    .build_joint(*entity_one, *entity_two, joint)?;
+            }
+        }
+
         Ok(entity_id_shift_map)
     }
 
+    /// Helper function for [`merge`], points the merged hierarchy components at the new entity ids.
+    fn remap_hierarchy(&mut self, entity_id_shift_map: &HashMap<Entity, Entity>) {
+        for new_id in entity_id_shift_map.values() {
+            if let Ok(mut parent) = self.inner.get_mut::<Parent>(*new_id) {
+                if let Some(new_parent) = entity_id_shift_map.get(&parent.entity) {
+                    parent.entity = *new_parent;
+                }
+            }
+
+            if let Ok(mut children) = self.inner.get_mut::<Children>(*new_id) {
+                for child in children.entities.iter_mut() {
+                    if let Some(new_child) = entity_id_shift_map.get(child) {
+                        *child = *new_child;
+                    }
+                }
+            }
+        }
+    }
+
     /// Helper function for [`merge`]
     #[cfg(feature = "physics")]
     fn merge_physics_entity(
@@ -86,17 +134,24 @@ impl World {
         let mut colliders = Vec::new();
         for c_id in other_world_physics.get_colliders(old_id.clone()) {
             if let Some(collider) = other_world_physics.remove_collider(c_id) {
-                colliders.push(collider);
+                colliders.push((c_id, collider));
             }
         }
 
-        if let Some(rigid_body) = other_world_physics.remove_body(old_id.clone()) {
+        if let Some(removed_body) = other_world_physics.remove_body(old_id.clone()) {
             let new_rbh =
                 self.physics_engine
-                    .add_body(new_id.clone(), rigid_body, &mut self.inner)?;
+                    .add_body(new_id.clone(), removed_body.body, &mut self.inner)?;
 
-            for collider in colliders {
-                self.physics_engine.add_collider(new_rbh, collider);
+            let mut collider_handles = HashMap::new();
+            for (old_handle, collider) in colliders {
+                let new_handle = self.physics_engine.add_collider(new_rbh, collider);
+                collider_handles.insert(old_handle, new_handle);
+            }
+
+            // The colliders built for a tilemap are now known under their handles in this world.
+            if let Ok(mut tilemap_colliders) = self.inner.get_mut::<TilemapColliders>(new_id) {
+                tilemap_colliders.remap_colliders(&collider_handles);
             }
         }
 
@@ -167,7 +222,21 @@ impl World {
         self.inner.spawn_batch::<I>(iter)
     }
 
+    /// Despawns the entity along with all of its descendants.
     pub fn despawn(&mut self, entity: Entity) -> Result<(), EmeraldError> {
+        if self.contains(entity) {
+            self.remove_parent(entity)?;
+        }
+
+        self.despawn_recursive(entity)
+    }
+
+    /// Helper function for [`despawn`]
+    fn despawn_recursive(&mut self, entity: Entity) -> Result<(), EmeraldError> {
+        for child in self.get_children(entity) {
+            self.despawn_recursive(child)?;
+        }
+
         #[cfg(feature = "physics")]
         self.physics_engine.remove_body(entity);
 
@@ -180,6 +249,140 @@ impl World {
         }
     }
 
+    /// Attaches the child to the parent, replacing any previous parent of the child.
+    /// From then on the child's `Transform` is relative to the parent, see [`World::global_transform`].
+    /// Fails if either entity does not exist, or if the child is the parent itself or one of its ancestors.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), EmeraldError> {
+        if !self.contains(child) || !self.contains(parent) {
+            return Err(EmeraldError::new(format!(
+                "Unable to parent {:?} to {:?}, both entities must exist.",
+                child, parent
+            )));
+        }
+
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            if entity == child {
+                return Err(EmeraldError::new(format!(
+                    "Unable to parent {:?} to {:?}, it would create a cycle.",
+                    child, parent
+                )));
+            }
+
+            ancestor = self.get_parent(entity);
+        }
+
+        self.remove_parent(child)?;
+        self.inner.insert_one(child, Parent { entity: parent })?;
+
+        let has_children = self.inner.get_mut::<Children>(parent).map(|mut children| {
+            children.entities.push(child);
+        });
+        if has_children.is_err() {
+            self.inner.insert_one(
+                parent,
+                Children {
+                    entities: vec![child],
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Detaches the entity from its parent, returning the former parent.
+    /// The entity's `Transform` is left untouched, so it is now read as a global transform.
+    pub fn remove_parent(&mut self, child: Entity) -> Result<Option<Entity>, EmeraldError> {
+        let parent = match self.inner.remove_one::<Parent>(child) {
+            Ok(parent) => parent.entity,
+            Err(hecs::ComponentError::MissingComponent(_)) => return Ok(None),
+            Err(e) => {
+                return Err(EmeraldError::new(format!(
+                    "Error removing parent of entity {:?}. {:?}",
+                    child, e
+                )))
+            }
+        };
+
+        if let Ok(mut children) = self.inner.get_mut::<Children>(parent) {
+            children.entities.retain(|entity| *entity != child);
+        }
+
+        Ok(Some(parent))
+    }
+
+    pub fn get_parent(&self, entity: Entity) -> Option<Entity> {
+        self.inner
+            .get::<Parent>(entity)
+            .ok()
+            .map(|parent| parent.entity)
+    }
+
+    pub fn get_children(&self, entity: Entity) -> Vec<Entity> {
+        self.inner
+            .get::<Children>(entity)
+            .map(|children| children.entities.clone())
+            .unwrap_or_default()
+    }
+
+    /// Computes the world-space transform of the entity by applying the transforms of all of its ancestors.
+    /// An ancestor without a `Transform` is treated as the identity.
+    pub fn global_transform(&self, entity: Entity) -> Result<Transform, EmeraldError> {
+        let transform = *self.get::<Transform>(entity)?;
+
+        Ok(match parent_global_transform(&self.inner, entity) {
+            Some(parent_transform) => parent_transform.mul_transform(&transform),
+            None => transform,
+        })
+    }
+
+    /// Copies the `Transform` of every entity that has a [`PreviousTransform`] into it.
+    /// The engine calls this before every fixed step for the worlds returned by
+    /// [`crate::Game::interpolated_worlds`].
+    pub fn store_previous_transforms(&mut self) {
+        for (_, (transform, previous_transform)) in self
+            .inner
+            .query_mut::<(&Transform, &mut PreviousTransform)>()
+        {
+            previous_transform.0 = *transform;
+        }
+    }
+
+    /// The transform of the entity blended from its [`PreviousTransform`], if it has one,
+    /// to its current `Transform` by `alpha`.
+    pub fn interpolated_transform(
+        &self,
+        entity: Entity,
+        alpha: f32,
+    ) -> Result<Transform, EmeraldError> {
+        let transform = *self.get::<Transform>(entity)?;
+
+        match self.inner.get::<PreviousTransform>(entity) {
+            Ok(previous_transform) => Ok(previous_transform.0.lerp(&transform, alpha)),
+            Err(_) => Ok(transform),
+        }
+    }
+
+    /// Same as [`World::global_transform`], using the interpolated transform of the entity and its ancestors.
+    pub fn interpolated_global_transform(
+        &self,
+        entity: Entity,
+        alpha: f32,
+    ) -> Result<Transform, EmeraldError> {
+        let mut global_transform = self.interpolated_transform(entity, alpha)?;
+        let mut ancestor = self.get_parent(entity);
+
+        while let Some(parent) = ancestor {
+            if let Ok(parent_transform) = self.interpolated_transform(parent, alpha) {
+                global_transform = parent_transform.mul_transform(&global_transform);
+            }
+
+            ancestor = self.get_parent(parent);
+        }
+
+        Ok(global_transform)
+    }
+
     pub fn clear(&mut self) {
         self.inner.clear();
 
@@ -300,6 +503,45 @@ impl World {
         }
     }
 
+    /// Serializes the entity into the toml format read by `AssetLoader::ent`.
+    /// Components that are not built into the ent format can be written via the `custom_component_saver` of the config.
+    pub fn save_ent(
+        &self,
+        entity: Entity,
+        config: EntSaveConfig<'_>,
+    ) -> Result<String, EmeraldError> {
+        save_ent(self, entity, config)
+    }
+
+    /// Serializes the entire world into the toml format read by `AssetLoader::world`.
+    pub fn save(&self, config: WorldSaveConfig<'_>) -> Result<String, EmeraldError> {
+        save_wrld(self, config)
+    }
+
+    /// Includes the `T` components of the entities in the snapshots of this world, stored under `name`.
+    /// A world restoring a snapshot needs the same components registered under the same names.
+    pub fn register_snapshot_component<T>(&mut self, name: &str)
+    where
+        T: Component + Serialize + DeserializeOwned,
+    {
+        self.snapshot_registry.register::<T>(name);
+    }
+
+    /// Captures the entities, their registered components and the physics state of the world.
+    pub fn snapshot(&self) -> Result<WorldSnapshot, EmeraldError> {
+        snapshot::snapshot(&self.snapshot_registry, self)
+    }
+
+    /// Puts the world back in the state of the snapshot.
+    /// Entities spawned since are despawned, despawned ones come back under the same ids,
+    /// and the registered components and the physics are overwritten.
+    /// Components that aren't registered are left as they are.
+    /// The world is left untouched if the snapshot can't be restored.
+    pub fn restore(&mut self, snapshot: &WorldSnapshot) -> Result<(), EmeraldError> {
+        let registry = self.snapshot_registry.clone();
+        snapshot::restore(&registry, self, snapshot)
+    }
+
     #[cfg(feature = "physics")]
     pub fn physics(&mut self) -> PhysicsHandler<'_> {
         PhysicsHandler::new(&mut self.physics_engine, &mut self.inner)
@@ -313,7 +555,8 @@ impl World {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Camera, Transform, World};
+    use crate::tilemap::Tilemap;
+    use crate::{Camera, PreviousTransform, TextureKey, Transform, Translation, Vector2, World};
 
     #[test]
     fn make_active_camera_succeeds_on_entity_with_camera() {
@@ -492,11 +735,478 @@ mod tests {
         assert!(world.remove_one::<TestStruct>(entity).is_err());
     }
 
+    #[test]
+    fn set_parent_fails_on_cycle() {
+        let mut world = World::new();
+        let parent = world.spawn((Transform::default(),));
+        let child = world.spawn((Transform::default(),));
+        world.set_parent(child, parent).unwrap();
+
+        assert!(world.set_parent(parent, child).is_err());
+        assert!(world.set_parent(parent, parent).is_err());
+    }
+
+    #[test]
+    fn set_parent_moves_child_between_parents() {
+        let mut world = World::new();
+        let first_parent = world.spawn((Transform::default(),));
+        let second_parent = world.spawn((Transform::default(),));
+        let child = world.spawn((Transform::default(),));
+
+        world.set_parent(child, first_parent).unwrap();
+        world.set_parent(child, second_parent).unwrap();
+
+        assert_eq!(world.get_parent(child), Some(second_parent));
+        assert!(world.get_children(first_parent).is_empty());
+        assert_eq!(world.get_children(second_parent), vec![child]);
+    }
+
+    #[test]
+    fn global_transform_applies_ancestors() {
+        let mut world = World::new();
+        let mut parent_transform = Transform::from_translation((10.0, 0.0));
+        parent_transform.rotation = std::f32::consts::FRAC_PI_2;
+        let parent = world.spawn((parent_transform,));
+        let child = world.spawn((Transform::from_translation((5.0, 0.0)),));
+        let grandchild = world.spawn((Transform::from_translation((1.0, 0.0)),));
+        world.set_parent(child, parent).unwrap();
+        world.set_parent(grandchild, child).unwrap();
+
+        let global_transform = world.global_transform(grandchild).unwrap();
+
+        assert!((global_transform.translation.x - 10.0).abs() < 0.001);
+        assert!((global_transform.translation.y - 6.0).abs() < 0.001);
+        assert!((global_transform.rotation - std::f32::consts::FRAC_PI_2).abs() < 0.001);
+    }
+
+    #[test]
+    fn inverse_mul_transform_undoes_mul_transform() {
+        let mut parent = Transform::from_translation((10.0, -4.0));
+        parent.rotation = 0.7;
+        parent.scale.x = 2.0;
+        parent.scale.y = 0.5;
+        let mut local = Transform::from_translation((3.0, 5.0));
+        local.rotation = -0.2;
+        local.scale.x = 1.5;
+
+        let back = parent.inverse_mul_transform(&parent.mul_transform(&local));
+
+        assert!((back.translation.x - 3.0).abs() < 0.001);
+        assert!((back.translation.y - 5.0).abs() < 0.001);
+        assert!((back.rotation + 0.2).abs() < 0.001);
+        assert!((back.scale.x - 1.5).abs() < 0.001);
+        assert!((back.scale.y - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn interpolated_global_transform_blends_previous_transforms() {
+        let mut world = World::new();
+        let parent = world.spawn((
+            Transform::default(),
+            PreviousTransform(Transform::default()),
+        ));
+        let child = world.spawn((Transform::from_translation((4.0, 0.0)),));
+        world.set_parent(child, parent).unwrap();
+
+        world.store_previous_transforms();
+        world.get_mut::<Transform>(parent).unwrap().translation = Translation::new(10.0, 2.0);
+
+        let halfway = world.interpolated_global_transform(child, 0.5).unwrap();
+        assert_eq!((halfway.translation.x, halfway.translation.y), (9.0, 1.0));
+
+        let current = world.interpolated_global_transform(child, 1.0).unwrap();
+        assert_eq!((current.translation.x, current.translation.y), (14.0, 2.0));
+    }
+
+    #[test]
+    fn despawn_removes_descendants() {
+        let mut world = World::new();
+        let root = world.spawn((Transform::default(),));
+        let parent = world.spawn((Transform::default(),));
+        let child = world.spawn((Transform::default(),));
+        world.set_parent(parent, root).unwrap();
+        world.set_parent(child, parent).unwrap();
+
+        world.despawn(parent).unwrap();
+
+        assert!(!world.contains(parent));
+        assert!(!world.contains(child));
+        assert!(world.contains(root));
+        assert!(world.get_children(root).is_empty());
+    }
+
+    #[test]
+    fn merge_preserves_hierarchy() {
+        let mut world = World::new();
+        world.spawn((Transform::default(),));
+
+        let mut other_world = World::new();
+        let parent = other_world.spawn((Transform::default(),));
+        let child = other_world.spawn((Transform::default(),));
+        other_world.set_parent(child, parent).unwrap();
+
+        let entity_map = world.merge(other_world).unwrap();
+        let new_parent = entity_map[&parent];
+        let new_child = entity_map[&child];
+
+        assert_eq!(world.get_parent(new_child), Some(new_parent));
+        assert_eq!(world.get_children(new_parent), vec![new_child]);
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Health(u32);
+
+    #[test]
+    fn restore_puts_back_entities_and_registered_components() {
+        let mut world = World::new();
+        world.register_snapshot_component::<Health>("health");
+        let hurt = world.spawn((Transform::from_translation((1.0, 2.0)), Health(3)));
+        let despawned = world.spawn((Transform::default(), Health(5), "unregistered"));
+        let snapshot = world.snapshot().unwrap();
+
+        world.get_mut::<Health>(hurt).unwrap().0 = 1;
+        world.get_mut::<Transform>(hurt).unwrap().translation = Translation::new(5.0, 5.0);
+        world.despawn(despawned).unwrap();
+        let spawned = world.spawn((Transform::default(),));
+        world.restore(&snapshot).unwrap();
+
+        assert_eq!(*world.get::<Health>(hurt).unwrap(), Health(3));
+        assert_eq!(world.get::<Transform>(hurt).unwrap().translation.x, 1.0);
+        assert_eq!(*world.get::<Health>(despawned).unwrap(), Health(5));
+        assert!(world.get::<&str>(despawned).is_err());
+        assert!(!world.inner.contains(spawned));
+
+        let mut other_world = World::new();
+        assert!(other_world.restore(&snapshot).is_err());
+        other_world.register_snapshot_component::<Health>("health");
+        other_world.restore(&snapshot).unwrap();
+        assert_eq!(*other_world.get::<Health>(despawned).unwrap(), Health(5));
+    }
+
+    #[test]
+    fn restore_puts_back_hierarchies_and_tilemaps() {
+        let mut world = World::new();
+        let parent = world.spawn((Transform::default(),));
+        let child = world.spawn((Transform::from_translation((1.0, 0.0)),));
+        world.set_parent(child, parent).unwrap();
+        let mut tilemap = Tilemap::new(TextureKey::new("tiles"), Vector2::new(16, 16), 4, 2);
+        tilemap.set_tile(1, 1, Some(3)).unwrap();
+        let level = world.spawn((Transform::default(), tilemap));
+        let snapshot = world.snapshot().unwrap();
+
+        world.remove_parent(child).unwrap();
+        let grandchild = world.spawn((Transform::default(),));
+        world.set_parent(grandchild, parent).unwrap();
+        let other_child = world.spawn((Transform::default(),));
+        world.set_parent(other_child, grandchild).unwrap();
+        world
+            .get_mut::<Tilemap>(level)
+            .unwrap()
+            .set_tile(1, 1, None)
+            .unwrap();
+        world.restore(&snapshot).unwrap();
+
+        assert_eq!(world.get_parent(child), Some(parent));
+        assert_eq!(world.get_children(parent), vec![child]);
+        assert!(!world.inner.contains(grandchild));
+        assert!(!world.inner.contains(other_child));
+        let tilemap = world.get::<Tilemap>(level).unwrap();
+        assert_eq!(tilemap.get_tile(1, 1).unwrap(), Some(3));
+        assert_eq!(tilemap.tilesheet, TextureKey::new("tiles"));
+    }
+
+    #[test]
+    fn restore_keeps_entities_reparented_under_new_ones() {
+        let mut world = World::new();
+        let parent = world.spawn((Transform::default(),));
+        let child = world.spawn((Transform::default(), "unregistered"));
+        world.set_parent(child, parent).unwrap();
+        let snapshot = world.snapshot().unwrap();
+
+        let new_parent = world.spawn((Transform::default(),));
+        world.set_parent(child, new_parent).unwrap();
+        world.restore(&snapshot).unwrap();
+
+        assert!(!world.inner.contains(new_parent));
+        assert_eq!(*world.get::<&str>(child).unwrap(), "unregistered");
+        assert_eq!(world.get_parent(child), Some(parent));
+        assert_eq!(world.get_children(parent), vec![child]);
+    }
+
+    #[test]
+    fn failed_restores_leave_the_world_untouched() {
+        let mut world = World::new();
+        world.register_snapshot_component::<Health>("health");
+        world.spawn((Transform::default(), Health(3)));
+        world.spawn((Transform::default(),));
+        let snapshot = world.snapshot().unwrap();
+
+        let mut other_world = World::new();
+        other_world.register_snapshot_component::<String>("health");
+        let other_entity = other_world.spawn((Transform::from_translation((1.0, 0.0)),));
+
+        assert!(other_world.restore(&snapshot).is_err());
+        assert!(other_world.inner.contains(other_entity));
+        assert_eq!(other_world.inner.len(), 1);
+        assert_eq!(
+            other_world
+                .get::<Transform>(other_entity)
+                .unwrap()
+                .translation
+                .x,
+            1.0
+        );
+    }
+
     #[cfg(feature = "physics")]
     mod physics_tests {
-        use rapier2d::prelude::RigidBodyBuilder;
+        use hecs::Entity;
+        use nalgebra::Point2;
+        use rapier2d::prelude::{
+            ActiveHooks, Ball, ColliderBuilder, Cuboid, Ray, RevoluteJointBuilder,
+            RigidBodyBuilder, RigidBodyHandle, SolverFlags,
+        };
+
+        use crate::tilemap::Tilemap;
+        use crate::transform::Translation;
+        use crate::{
+            CharacterController, ColliderPair, Collision, CollisionLayers, ContactHooks,
+            ContactMaterial, ConveyorBelt, DistanceJoint, InteractionGroups, OneWayPlatform,
+            PhysicsEvent, PhysicsSettings, PhysicsSync, PointQuery, RayCastQuery, Rectangle,
+            ShapeCastQuery, ShapeQuery, TextureKey, TileColliderShape, TilemapColliders, Transform,
+            Vector2, World, WorldSnapshot,
+        };
+
+        /// Spawns an entity at `(x, y)` with a body and a single collider.
+        fn spawn_body(
+            world: &mut World,
+            body: RigidBodyBuilder,
+            x: f32,
+            y: f32,
+            collider: ColliderBuilder,
+        ) -> (Entity, RigidBodyHandle) {
+            let (entity, body) = world
+                .spawn_with_body((Transform::from_translation((x, y)),), body)
+                .unwrap();
+            world.physics().build_collider(body, collider);
 
-        use crate::{Transform, World};
+            (entity, body)
+        }
+
+        fn spawn_fixed_box(
+            world: &mut World,
+            x: f32,
+            y: f32,
+            half_width: f32,
+            half_height: f32,
+        ) -> Entity {
+            spawn_body(
+                world,
+                RigidBodyBuilder::fixed(),
+                x,
+                y,
+                ColliderBuilder::cuboid(half_width, half_height),
+            )
+            .0
+        }
+
+        /// A dynamic box of half extent 0.5.
+        fn spawn_dynamic_box(world: &mut World, x: f32, y: f32) -> (Entity, RigidBodyHandle) {
+            spawn_body(
+                world,
+                RigidBodyBuilder::dynamic(),
+                x,
+                y,
+                ColliderBuilder::cuboid(0.5, 0.5),
+            )
+        }
+
+        fn spawn_in_layer(
+            world: &mut World,
+            body: RigidBodyBuilder,
+            y: f32,
+            layer: &str,
+        ) -> Entity {
+            let groups = world.physics().collision_layers().groups(layer).unwrap();
+
+            spawn_body(
+                world,
+                body,
+                0.0,
+                y,
+                ColliderBuilder::cuboid(0.5, 0.5).collision_groups(groups),
+            )
+            .0
+        }
+
+        /// A fixed anchor at the origin and a ball hanging 10 units to its right, under gravity.
+        fn pendulum(world: &mut World) -> (Entity, Entity) {
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let (anchor, _) = world
+                .spawn_with_body((Transform::default(),), RigidBodyBuilder::fixed())
+                .unwrap();
+            let (ball, _) = spawn_body(
+                world,
+                RigidBodyBuilder::dynamic(),
+                10.0,
+                0.0,
+                ColliderBuilder::ball(1.0),
+            );
+
+            (anchor, ball)
+        }
+
+        /// A character of half extent 0.5 above the ground, whose top is at y = 1.
+        fn character_on_ground(world: &mut World, x: f32, y: f32) -> Entity {
+            spawn_fixed_box(world, 0.0, 0.0, 50.0, 1.0);
+            let (character, _) = spawn_body(
+                world,
+                RigidBodyBuilder::kinematic_position_based(),
+                x,
+                y,
+                ColliderBuilder::cuboid(0.5, 0.5),
+            );
+            world
+                .insert_one(character, CharacterController::default())
+                .unwrap();
+
+            character
+        }
+
+        /// A slope of `angle` going up from x = 5 on the ground of `character_on_ground`,
+        /// to a plateau. Returns the height of the plateau.
+        fn hill(world: &mut World, angle: f32) -> f32 {
+            let (half_length, half_thickness) = (5.0, 0.5);
+            let (sin, cos) = angle.sin_cos();
+            let mut transform = Transform::from_translation((
+                5.0 + half_length * cos + half_thickness * sin,
+                1.0 + half_length * sin - half_thickness * cos,
+            ));
+            transform.rotation = angle;
+            let (_, body) = world
+                .spawn_with_body((transform,), RigidBodyBuilder::fixed())
+                .unwrap();
+            world
+                .physics()
+                .build_collider(body, ColliderBuilder::cuboid(half_length, half_thickness));
+
+            let top = 1.0 + 2.0 * half_length * sin;
+            spawn_fixed_box(
+                world,
+                5.0 + 2.0 * half_length * cos + 10.0,
+                top - 1.0,
+                10.0,
+                1.0,
+            );
+
+            top
+        }
+
+        /// A level of 8 by 4 tiles of 16 pixels, with a solid floor and a solid 2 by 2 block
+        /// at the second and third columns, and a decoration that isn't solid above the floor.
+        fn level(world: &mut World, tilemap_colliders: TilemapColliders) -> Entity {
+            let mut tilemap = Tilemap::new(TextureKey::default(), Vector2::new(16, 16), 8, 4);
+            for x in 0..8 {
+                tilemap.set_tile(x, 0, Some(1)).unwrap();
+            }
+            for (x, y) in [(2, 1), (3, 1), (2, 2), (3, 2)] {
+                tilemap.set_tile(x, y, Some(2)).unwrap();
+            }
+            tilemap.set_tile(6, 1, Some(5)).unwrap();
+
+            let level = world.spawn((Transform::default(), tilemap));
+            world
+                .physics()
+                .build_tilemap_colliders(level, tilemap_colliders.solid_tiles(vec![1, 2]))
+                .unwrap();
+
+            level
+        }
+
+        /// A box of half extent 1 falling on the ground, and a sensor overlapping the box.
+        fn falling_box(world: &mut World) -> (Entity, Entity, Entity) {
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let ground = spawn_fixed_box(world, 0.0, 0.0, 50.0, 1.0);
+            let (falling, _) = spawn_body(
+                world,
+                RigidBodyBuilder::dynamic(),
+                0.0,
+                2.0,
+                ColliderBuilder::cuboid(1.0, 1.0),
+            );
+            let (sensor, _) = spawn_body(
+                world,
+                RigidBodyBuilder::fixed(),
+                0.0,
+                2.0,
+                ColliderBuilder::cuboid(0.5, 0.5).sensor(true),
+            );
+
+            (ground, falling, sensor)
+        }
+
+        /// Fixed boxes of half extent 1 at x = 5 and x = 10, the second one a sensor.
+        fn boxes_in_a_row(world: &mut World) -> (Entity, Entity) {
+            let near = spawn_fixed_box(world, 5.0, 0.0, 1.0, 1.0);
+            let (far, _) = spawn_body(
+                world,
+                RigidBodyBuilder::fixed(),
+                10.0,
+                0.0,
+                ColliderBuilder::cuboid(1.0, 1.0).sensor(true),
+            );
+
+            // Queries only see colliders that existed during the last step.
+            world.physics().step(1.0 / 60.0);
+
+            (near, far)
+        }
+
+        fn pile_of_boxes(world: &mut World) -> Vec<Entity> {
+            world.physics().set_gravity(Vector2::new(0.0, -10.0));
+            spawn_fixed_box(world, 0.0, 0.0, 20.0, 1.0);
+
+            (0..6)
+                .map(|i| spawn_dynamic_box(world, (i % 3) as f32 * 0.7, 2.0 + i as f32 * 1.1).0)
+                .collect()
+        }
+
+        /// Sets the velocity before every step, as a game would during its updates.
+        fn move_character(
+            world: &mut World,
+            character: Entity,
+            velocity: Vector2<f32>,
+            steps: u32,
+        ) {
+            for _ in 0..steps {
+                world
+                    .get_mut::<CharacterController>(character)
+                    .unwrap()
+                    .velocity = velocity;
+                world.physics().step(1.0 / 60.0);
+            }
+        }
+
+        fn distance(world: &World, entity_one: Entity, entity_two: Entity) -> f32 {
+            let one = world.get::<Transform>(entity_one).unwrap().translation;
+            let two = world.get::<Transform>(entity_two).unwrap().translation;
+
+            Vector2::new(two.x - one.x, two.y - one.y).norm()
+        }
+
+        fn translations(world: &World, entities: &[Entity]) -> Vec<(f32, f32, f32)> {
+            entities
+                .iter()
+                .map(|entity| {
+                    let transform = world.get::<Transform>(*entity).unwrap();
+                    (
+                        transform.translation.x,
+                        transform.translation.y,
+                        transform.rotation,
+                    )
+                })
+                .collect()
+        }
 
         #[test]
         fn add_body_on_preexisting_entity() {
@@ -528,5 +1238,1096 @@ mod tests {
             let entity = world.spawn((Transform::default(),));
             assert!(world.physics().remove_body(entity).is_none());
         }
+
+        #[test]
+        fn revolute_joint_keeps_bodies_together() {
+            let mut world = World::new();
+            let (anchor, ball) = pendulum(&mut world);
+            let joint = world
+                .physics()
+                .build_joint(
+                    anchor,
+                    ball,
+                    RevoluteJointBuilder::new().local_anchor2(Point2::new(-10.0, 0.0)),
+                )
+                .unwrap();
+
+            world.physics().step_n(60, 1.0 / 60.0);
+
+            assert!((distance(&world, anchor, ball) - 10.0).abs() < 0.1);
+            assert!(world.get::<Transform>(ball).unwrap().translation.y < -1.0);
+            assert_eq!(world.physics().get_joints(ball), vec![joint]);
+            assert_eq!(
+                world.physics_ref().get_joint_entities(joint),
+                Some((anchor, ball))
+            );
+        }
+
+        #[test]
+        fn rope_joint_limits_distance() {
+            let mut world = World::new();
+            let (anchor, ball) = pendulum(&mut world);
+            world
+                .physics()
+                .build_joint(anchor, ball, DistanceJoint::rope(12.0))
+                .unwrap();
+
+            // Slack at first, so the ball falls freely.
+            world.physics().step_n(5, 1.0 / 60.0);
+            assert!(world.get::<Transform>(ball).unwrap().translation.y < -0.2);
+
+            for _ in 0..60 {
+                world.physics().step(1.0 / 60.0);
+                assert!(distance(&world, anchor, ball) < 12.01);
+            }
+            assert!((distance(&world, anchor, ball) - 12.0).abs() < 0.01);
+        }
+
+        #[test]
+        fn rope_joints_stop_bodies_against_walls() {
+            let mut world = World::new();
+            let (anchor, ball) = pendulum(&mut world);
+            world.physics().set_gravity(Vector2::new(0.0, 0.0));
+            spawn_fixed_box(&mut world, 8.5, 0.0, 0.5, 5.0);
+            world.physics().step(1.0 / 60.0);
+            world
+                .physics()
+                .build_joint(anchor, ball, DistanceJoint::rope(4.0))
+                .unwrap();
+
+            // The ball touches the far side of the wall, pulled towards the anchor behind it.
+            world.physics().step_n(60, 1.0 / 60.0);
+
+            assert!(world.get::<Transform>(ball).unwrap().translation.x > 9.5);
+        }
+
+        #[test]
+        fn distance_joints_rotate_bodies_held_off_center() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let (anchor, _) = world
+                .spawn_with_body((Transform::default(),), RigidBodyBuilder::fixed())
+                .unwrap();
+            let (held, _) = spawn_dynamic_box(&mut world, 0.0, -2.0);
+            world
+                .physics()
+                .build_joint(
+                    anchor,
+                    held,
+                    DistanceJoint::rod(1.5).local_anchor2(Point2::new(0.5, 0.5)),
+                )
+                .unwrap();
+
+            world.physics().step_n(120, 1.0 / 60.0);
+
+            // Hangs with its center of mass below the corner it is held by.
+            let transform = *world.get::<Transform>(held).unwrap();
+            assert!((transform.rotation.abs() - std::f32::consts::FRAC_PI_4).abs() < 0.1);
+            assert!(transform.translation.x.abs() < 0.1);
+        }
+
+        #[test]
+        fn despawn_removes_joints() {
+            let mut world = World::new();
+            let (anchor, ball) = pendulum(&mut world);
+            let (other, _) = world
+                .spawn_with_body((Transform::default(),), RigidBodyBuilder::dynamic())
+                .unwrap();
+            let revolute = world
+                .physics()
+                .build_joint(anchor, ball, RevoluteJointBuilder::new())
+                .unwrap();
+            let rope = world
+                .physics()
+                .build_joint(ball, other, DistanceJoint::rope(1.0))
+                .unwrap();
+
+            world.despawn(ball).unwrap();
+
+            assert!(world.physics().get_joints(anchor).is_empty());
+            assert!(world.physics().get_joints(other).is_empty());
+            assert!(world.physics().remove_joint(revolute).is_none());
+            assert!(world.physics().remove_joint(rope).is_none());
+
+            let joint = world
+                .physics()
+                .build_joint(anchor, other, DistanceJoint::rope(1.0))
+                .unwrap();
+            let removed = world.physics().remove_body(other).unwrap();
+            assert_eq!(removed.joints, vec![joint]);
+        }
+
+        #[test]
+        fn merge_carries_joints() {
+            let mut world = World::new();
+            world.spawn((Transform::default(),));
+
+            let mut other_world = World::new();
+            let (anchor, ball) = pendulum(&mut other_world);
+            other_world
+                .physics()
+                .build_joint(anchor, ball, DistanceJoint::rod(10.0))
+                .unwrap();
+
+            let entity_map = world.merge(other_world).unwrap();
+            let joints = world.physics().get_joints(entity_map[&ball]);
+
+            assert_eq!(joints.len(), 1);
+            assert_eq!(
+                world.physics().get_joint_entities(joints[0]),
+                Some((entity_map[&anchor], entity_map[&ball]))
+            );
+        }
+
+        #[test]
+        fn character_lands_on_ground() {
+            let mut world = World::new();
+            let character = character_on_ground(&mut world, 0.0, 5.0);
+
+            move_character(&mut world, character, Vector2::new(0.0, -60.0), 10);
+
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            let translation = world.get::<Transform>(character).unwrap().translation;
+            assert!(controller.is_grounded());
+            assert!(!controller.is_on_wall());
+            assert_eq!(controller.velocity.y, 0.0);
+            assert!((translation.y - 1.51).abs() < 0.01);
+        }
+
+        #[test]
+        fn character_slides_along_walls() {
+            let mut world = World::new();
+            let character = character_on_ground(&mut world, 0.0, 1.6);
+            spawn_fixed_box(&mut world, 5.0, 5.0, 1.0, 4.0);
+
+            move_character(&mut world, character, Vector2::new(60.0, -1.0), 10);
+
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            let translation = world.get::<Transform>(character).unwrap().translation;
+            assert!(controller.is_on_wall());
+            assert!(controller.is_grounded());
+            assert_eq!(controller.velocity.x, 0.0);
+            assert!((translation.x - 3.49).abs() < 0.01);
+            assert!((translation.y - 1.51).abs() < 0.01);
+        }
+
+        #[test]
+        fn character_jumps_through_one_way_platforms() {
+            let mut world = World::new();
+            let character = character_on_ground(&mut world, 0.0, 1.51);
+            let platform = spawn_fixed_box(&mut world, 0.0, 4.0, 5.0, 0.25);
+            world
+                .insert_one(platform, OneWayPlatform::default())
+                .unwrap();
+
+            move_character(&mut world, character, Vector2::new(0.0, 60.0), 6);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            assert!(!controller.is_on_ceiling());
+            assert!((world.get::<Transform>(character).unwrap().translation.y - 7.51).abs() < 0.01);
+
+            move_character(&mut world, character, Vector2::new(0.0, -60.0), 6);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            assert!(controller.is_grounded());
+            assert!((world.get::<Transform>(character).unwrap().translation.y - 4.76).abs() < 0.01);
+        }
+
+        #[test]
+        fn characters_walk_slopes_up_to_their_max_slope_angle() {
+            let angle = std::f32::consts::FRAC_PI_6;
+            let mut world = World::new();
+            let character = character_on_ground(&mut world, 0.0, 1.51);
+            let top = hill(&mut world, angle);
+
+            move_character(&mut world, character, Vector2::new(10.0, 0.0), 40);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            let normal = controller.ground_normal().unwrap();
+            assert!(controller.is_grounded());
+            assert!(!controller.is_on_wall());
+            assert!((normal.x + angle.sin()).abs() < 0.01);
+
+            move_character(&mut world, character, Vector2::new(10.0, 0.0), 60);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            assert!(controller.is_grounded());
+            assert!(
+                (world.get::<Transform>(character).unwrap().translation.y - top - 0.51).abs()
+                    < 0.01
+            );
+
+            // Snapped to the slope all the way down.
+            for _ in 0..120 {
+                move_character(&mut world, character, Vector2::new(-10.0, 0.0), 1);
+                assert!(world
+                    .get::<CharacterController>(character)
+                    .unwrap()
+                    .is_grounded());
+            }
+            assert!((world.get::<Transform>(character).unwrap().translation.y - 1.51).abs() < 0.01);
+        }
+
+        #[test]
+        fn characters_stop_at_slopes_steeper_than_their_max_slope_angle() {
+            let mut world = World::new();
+            let character = character_on_ground(&mut world, 0.0, 1.51);
+            let top = hill(&mut world, std::f32::consts::FRAC_PI_3);
+
+            move_character(&mut world, character, Vector2::new(10.0, 0.0), 60);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            let translation = world.get::<Transform>(character).unwrap().translation;
+            assert!(controller.is_on_wall());
+            assert!(translation.x < 5.0);
+            assert!((translation.y - 1.51).abs() < 0.01);
+
+            // Walks off the plateau, which starts at x = 10, instead of being snapped to the slope.
+            world.get_mut::<Transform>(character).unwrap().translation =
+                Translation::new(11.0, top + 0.51);
+            move_character(&mut world, character, Vector2::new(0.0, -1.0), 1);
+            assert!(world
+                .get::<CharacterController>(character)
+                .unwrap()
+                .is_grounded());
+
+            move_character(&mut world, character, Vector2::new(-10.0, 0.0), 30);
+            let controller = *world.get::<CharacterController>(character).unwrap();
+            assert!(!controller.is_grounded());
+            assert!(
+                (world.get::<Transform>(character).unwrap().translation.y - top - 0.51).abs()
+                    < 0.02
+            );
+        }
+
+        #[test]
+        fn characters_snap_down_steps_within_their_snap_distance() {
+            for snap_to_ground in [Some(2.0), None] {
+                let mut world = World::new();
+                let character = character_on_ground(&mut world, -2.0, 2.51);
+                spawn_fixed_box(&mut world, -10.0, 1.5, 10.0, 0.5);
+                world
+                    .get_mut::<CharacterController>(character)
+                    .unwrap()
+                    .snap_to_ground = snap_to_ground;
+                move_character(&mut world, character, Vector2::new(0.0, -1.0), 1);
+                assert!(world
+                    .get::<CharacterController>(character)
+                    .unwrap()
+                    .is_grounded());
+
+                move_character(&mut world, character, Vector2::new(10.0, 0.0), 30);
+
+                let controller = *world.get::<CharacterController>(character).unwrap();
+                let translation = world.get::<Transform>(character).unwrap().translation;
+                assert!(translation.x > 2.0);
+                if snap_to_ground.is_some() {
+                    assert!(controller.is_grounded());
+                    assert!((translation.y - 1.51).abs() < 0.01);
+                } else {
+                    assert!(!controller.is_grounded());
+                    assert!((translation.y - 2.51).abs() < 0.02);
+                }
+            }
+        }
+
+        struct IgnoredPair(Entity, Entity);
+        impl ContactHooks for IgnoredPair {
+            fn filter_contact_pair(&self, pair: &ColliderPair) -> Option<SolverFlags> {
+                let ignored = (pair.entity_one == self.0 && pair.entity_two == self.1)
+                    || (pair.entity_one == self.1 && pair.entity_two == self.0);
+
+                if ignored {
+                    None
+                } else {
+                    Some(SolverFlags::default())
+                }
+            }
+        }
+
+        #[test]
+        fn contact_hooks_filter_entity_pairs() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let (ground, _) = spawn_body(
+                &mut world,
+                RigidBodyBuilder::fixed(),
+                0.0,
+                0.0,
+                ColliderBuilder::cuboid(50.0, 1.0).active_hooks(ActiveHooks::FILTER_CONTACT_PAIRS),
+            );
+            let (ghost, _) = spawn_dynamic_box(&mut world, -5.0, 2.0);
+            let (solid, _) = spawn_dynamic_box(&mut world, 5.0, 2.0);
+
+            world
+                .physics()
+                .set_contact_hooks(IgnoredPair(ghost, ground));
+            world.physics().step_n(30, 1.0 / 60.0);
+
+            assert!(world.get::<Transform>(ghost).unwrap().translation.y < -1.0);
+            assert!((world.get::<Transform>(solid).unwrap().translation.y - 1.5).abs() < 0.1);
+        }
+
+        #[test]
+        fn bodies_jump_through_one_way_platforms() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let platform = spawn_fixed_box(&mut world, 0.0, 0.0, 5.0, 0.25);
+            world
+                .insert_one(platform, OneWayPlatform::default())
+                .unwrap();
+            let (jumping, body) = spawn_dynamic_box(&mut world, 0.0, -2.0);
+            world
+                .physics()
+                .rigid_body_mut(body)
+                .unwrap()
+                .set_linvel(Vector2::new(0.0, 40.0), true);
+
+            // Peaks above the platform, then lands on it.
+            world.physics().step_n(24, 1.0 / 60.0);
+            assert!(world.get::<Transform>(jumping).unwrap().translation.y > 5.0);
+
+            world.physics().step_n(96, 1.0 / 60.0);
+            assert!((world.get::<Transform>(jumping).unwrap().translation.y - 0.75).abs() < 0.1);
+        }
+
+        #[test]
+        fn removed_surfaces_stop_modifying_contacts() {
+            let mut world = World::new();
+            let platform = spawn_fixed_box(&mut world, 0.0, 0.0, 5.0, 0.25);
+            let (belt, _) = spawn_body(
+                &mut world,
+                RigidBodyBuilder::fixed(),
+                0.0,
+                -5.0,
+                ColliderBuilder::cuboid(5.0, 0.25)
+                    .active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+            );
+            world
+                .insert_one(platform, OneWayPlatform::default())
+                .unwrap();
+            world.insert_one(belt, ConveyorBelt::new(5.0)).unwrap();
+            let modifies_contacts = |world: &mut World, entity| {
+                let collider = world.physics().get_colliders(entity)[0];
+                world
+                    .physics()
+                    .get_collider_desc(collider)
+                    .unwrap()
+                    .active_hooks()
+                    .contains(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+            };
+
+            world.physics().step(1.0 / 60.0);
+            assert!(modifies_contacts(&mut world, platform));
+            assert!(modifies_contacts(&mut world, belt));
+
+            world.remove_one::<OneWayPlatform>(platform).unwrap();
+            world.remove_one::<ConveyorBelt>(belt).unwrap();
+            world.physics().step(1.0 / 60.0);
+            assert!(!modifies_contacts(&mut world, platform));
+            // Built with the flag, so it keeps it.
+            assert!(modifies_contacts(&mut world, belt));
+        }
+
+        #[test]
+        fn conveyor_belts_carry_bodies() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let belt = spawn_fixed_box(&mut world, 0.0, 0.0, 50.0, 1.0);
+            world.insert_one(belt, ConveyorBelt::new(5.0)).unwrap();
+            let (carried, body) = spawn_dynamic_box(&mut world, 0.0, 1.5);
+
+            world.physics().step_n(60, 1.0 / 60.0);
+
+            let velocity = *world.physics().rigid_body(body).unwrap().linvel();
+            assert!((velocity.x - 5.0).abs() < 0.1);
+            assert!(world.get::<Transform>(carried).unwrap().translation.x > 2.0);
+        }
+
+        #[test]
+        fn contact_materials_override_restitution() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let ground = spawn_fixed_box(&mut world, 0.0, 0.0, 50.0, 1.0);
+            let (bouncy, _) = spawn_dynamic_box(&mut world, -5.0, 4.5);
+            let (dull, _) = spawn_dynamic_box(&mut world, 5.0, 4.5);
+
+            world.physics().set_contact_material(
+                ground,
+                bouncy,
+                ContactMaterial::new().restitution(1.0),
+            );
+            world.physics().step_n(30, 1.0 / 60.0);
+
+            assert!(world.get::<Transform>(bouncy).unwrap().translation.y > 3.0);
+            assert!((world.get::<Transform>(dull).unwrap().translation.y - 1.5).abs() < 0.1);
+            assert_eq!(
+                world.physics().remove_contact_material(bouncy, ground),
+                Some(ContactMaterial::new().restitution(1.0))
+            );
+        }
+
+        #[test]
+        fn bodies_start_with_the_transform_rotation() {
+            let mut world = World::new();
+            let mut transform = Transform::from_translation((3.0, 4.0));
+            transform.rotation = 0.5;
+            let (_, body) = world
+                .spawn_with_body((transform,), RigidBodyBuilder::dynamic())
+                .unwrap();
+
+            let body = world.physics().rigid_body(body).unwrap().clone();
+            assert_eq!(*body.translation(), Vector2::new(3.0, 4.0));
+            assert!((body.rotation().angle() - 0.5).abs() < 0.0001);
+        }
+
+        #[test]
+        fn spinning_bodies_rotate_their_transform() {
+            let mut world = World::new();
+            let (spinning, body) = spawn_dynamic_box(&mut world, 0.0, 0.0);
+            world
+                .physics()
+                .rigid_body_mut(body)
+                .unwrap()
+                .set_angvel(1.0, true);
+
+            world.physics().step_n(30, 1.0 / 60.0);
+
+            let rotation = world.get::<Transform>(spinning).unwrap().rotation;
+            assert!((rotation - 0.5).abs() < 0.01);
+        }
+
+        #[test]
+        fn rotating_transforms_rotate_kinematic_bodies() {
+            let mut world = World::new();
+            let (rotating, body) = world
+                .spawn_with_body(
+                    (Transform::default(),),
+                    RigidBodyBuilder::kinematic_position_based(),
+                )
+                .unwrap();
+
+            world.get_mut::<Transform>(rotating).unwrap().rotation = 1.0;
+            world.physics().step(1.0 / 60.0);
+
+            let angle = world.physics().rigid_body(body).unwrap().rotation().angle();
+            assert!((angle - 1.0).abs() < 0.0001);
+            assert!((world.get::<Transform>(rotating).unwrap().rotation - 1.0).abs() < 0.0001);
+        }
+
+        #[test]
+        fn physics_sync_modes_choose_the_source_of_positions() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -60.0));
+            let (physics_driven, _) = spawn_dynamic_box(&mut world, -10.0, 0.0);
+            let (transform_driven, transform_driven_body) = spawn_dynamic_box(&mut world, 0.0, 0.0);
+            let (unsynced, unsynced_body) = spawn_dynamic_box(&mut world, 10.0, 0.0);
+            world
+                .insert_one(physics_driven, PhysicsSync::PhysicsDriven)
+                .unwrap();
+            world
+                .insert_one(transform_driven, PhysicsSync::TransformDriven)
+                .unwrap();
+            world.insert_one(unsynced, PhysicsSync::None).unwrap();
+
+            for entity in [physics_driven, transform_driven, unsynced] {
+                world.get_mut::<Transform>(entity).unwrap().translation.x += 1.0;
+            }
+            world.physics().step(1.0 / 60.0);
+
+            // Moved by gravity without teleporting.
+            let translation = world.get::<Transform>(physics_driven).unwrap().translation;
+            assert_eq!(translation.x, -10.0);
+            assert!(translation.y < 0.0);
+
+            // Teleported without being moved by gravity.
+            let translation = world
+                .get::<Transform>(transform_driven)
+                .unwrap()
+                .translation;
+            let body_translation = *world
+                .physics()
+                .rigid_body(transform_driven_body)
+                .unwrap()
+                .translation();
+            assert_eq!((translation.x, translation.y), (1.0, 0.0));
+            assert_eq!(body_translation.x, 1.0);
+            assert!(body_translation.y < 0.0);
+
+            let translation = world.get::<Transform>(unsynced).unwrap().translation;
+            let body_translation = *world
+                .physics()
+                .rigid_body(unsynced_body)
+                .unwrap()
+                .translation();
+            assert_eq!((translation.x, translation.y), (11.0, 0.0));
+            assert_eq!(body_translation.x, 10.0);
+        }
+
+        #[test]
+        fn physics_settings_apply_to_the_world() {
+            let mut world = World::new();
+            let settings = PhysicsSettings {
+                gravity: Vector2::new(0.0, -10.0),
+                velocity_iterations: 12,
+                ccd_enabled: true,
+                pixels_per_meter: 100.0,
+                collision_groups: InteractionGroups::new(2, 1),
+                ..Default::default()
+            };
+            world.physics().set_settings(settings);
+
+            let (entity, body) = spawn_dynamic_box(&mut world, -100.0, 0.0);
+            let default_level = level(&mut world, TilemapColliders::new());
+            let other_level = level(
+                &mut world,
+                TilemapColliders::new().collision_groups(InteractionGroups::new(4, 4)),
+            );
+            world.physics().step(1.0);
+
+            assert_eq!(world.physics().settings(), settings);
+            assert_eq!(world.physics_ref().settings(), settings);
+            let parameters = &world.physics_engine.integration_parameters;
+            assert_eq!(parameters.max_velocity_iterations, 12);
+            assert!((parameters.allowed_linear_error - 0.1).abs() < 0.0001);
+            assert!((parameters.prediction_distance - 0.2).abs() < 0.0001);
+
+            let mut physics = world.physics();
+            let body = physics.rigid_body(body).unwrap();
+            assert!(body.is_ccd_enabled());
+            assert_eq!(body.linvel().y, -10.0);
+
+            // Only colliders without groups of their own get the default ones.
+            let collision_groups = |entity| {
+                let collider = physics.get_colliders(entity)[0];
+                physics
+                    .get_collider_desc(collider)
+                    .unwrap()
+                    .collision_groups()
+            };
+            assert_eq!(collision_groups(entity), InteractionGroups::all());
+            assert_eq!(
+                collision_groups(default_level),
+                InteractionGroups::new(2, 1)
+            );
+            assert_eq!(collision_groups(other_level), InteractionGroups::new(4, 4));
+        }
+
+        #[test]
+        fn tilemap_colliders_merge_solid_tiles() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -100.0));
+            let level = level(&mut world, TilemapColliders::new());
+            let (falling, _) = spawn_dynamic_box(&mut world, 104.0, 40.0);
+
+            world.physics().step_n(60, 1.0 / 60.0);
+
+            assert_eq!(world.physics().get_colliders(level).len(), 2);
+            assert_eq!(
+                world
+                    .get::<TilemapColliders>(level)
+                    .unwrap()
+                    .collider_count(),
+                2
+            );
+            assert!((world.get::<Transform>(falling).unwrap().translation.y - 16.5).abs() < 0.1);
+            assert_eq!(
+                world
+                    .physics()
+                    .intersections_with_point(PointQuery {
+                        point: Translation::new(56.0, 40.0),
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                vec![level]
+            );
+            assert!(world
+                .physics()
+                .intersections_with_point(PointQuery {
+                    point: Translation::new(72.0, 40.0),
+                    ..Default::default()
+                })
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn tilemap_colliders_rebuild_changed_chunks() {
+            let mut world = World::new();
+            let level = level(&mut world, TilemapColliders::new().chunk_size(4));
+            let colliders = world.physics().get_colliders(level);
+            assert_eq!(colliders.len(), 3);
+
+            world
+                .get_mut::<Tilemap>(level)
+                .unwrap()
+                .set_tile(7, 3, Some(1))
+                .unwrap();
+            world.physics().step(1.0 / 60.0);
+
+            let rebuilt_colliders = world.physics().get_colliders(level);
+            assert_eq!(rebuilt_colliders.len(), 4);
+            assert_eq!(
+                colliders
+                    .iter()
+                    .filter(|collider| rebuilt_colliders.contains(collider))
+                    .count(),
+                2
+            );
+        }
+
+        #[test]
+        fn tilemap_colliders_outline_solid_tiles() {
+            let mut world = World::new();
+            let level = level(
+                &mut world,
+                TilemapColliders::new().shape(TileColliderShape::Outline),
+            );
+            world.physics().step(1.0 / 60.0);
+
+            assert_eq!(world.physics().get_colliders(level).len(), 1);
+            let hit = world
+                .physics()
+                .cast_ray(RayCastQuery {
+                    ray: Ray::new(Point2::new(40.0, 100.0), Vector2::new(0.0, -1.0)),
+                    max_toi: 100.0,
+                    ..Default::default()
+                })
+                .unwrap()
+                .unwrap();
+            assert_eq!(hit.entity, level);
+            assert!((hit.point.y - 48.0).abs() < 0.0001);
+
+            // Outlines are hollow.
+            assert!(world
+                .physics()
+                .intersections_with_point(PointQuery {
+                    point: Translation::new(56.0, 40.0),
+                    ..Default::default()
+                })
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn tilemap_colliders_need_a_tilemap() {
+            let mut world = World::new();
+            let entity = world.spawn((Transform::default(),));
+
+            assert!(world
+                .physics()
+                .build_tilemap_colliders(entity, TilemapColliders::new())
+                .is_err());
+        }
+
+        #[test]
+        fn step_queues_collision_events() {
+            let mut world = World::new();
+            let (ground, falling, sensor) = falling_box(&mut world);
+
+            world.physics().step(1.0 / 60.0);
+            let started = world
+                .physics()
+                .events()
+                .iter()
+                .filter_map(|event| match event {
+                    PhysicsEvent::CollisionStarted(collision) => Some(*collision),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(started.len(), 2);
+            let involves = |collision: &Collision, a: Entity, b: Entity| {
+                (collision.entity_one, collision.entity_two) == (a, b)
+                    || (collision.entity_one, collision.entity_two) == (b, a)
+            };
+            assert!(started
+                .iter()
+                .any(|collision| involves(collision, ground, falling) && !collision.sensor));
+            assert!(started
+                .iter()
+                .any(|collision| involves(collision, sensor, falling) && collision.sensor));
+
+            // Events only cover the last step.
+            world.physics().step(1.0 / 60.0);
+            assert!(!world
+                .physics()
+                .events()
+                .iter()
+                .any(|event| matches!(event, PhysicsEvent::CollisionStarted(_))));
+            assert_eq!(
+                world.physics().get_colliding_entities(ground),
+                vec![falling]
+            );
+        }
+
+        #[test]
+        fn ray_casts_report_hit_details() {
+            let mut world = World::new();
+            let (near, far) = boxes_in_a_row(&mut world);
+            let query = || RayCastQuery {
+                ray: Ray::new(Point2::new(0.0, 0.0), Vector2::new(1.0, 0.0)),
+                max_toi: 100.0,
+                ..RayCastQuery::default()
+            };
+
+            let hit = world.physics().cast_ray(query()).unwrap().unwrap();
+            assert_eq!(hit.entity, near);
+            assert_eq!(world.physics().get_colliders(near), vec![hit.collider]);
+            assert!((hit.toi - 4.0).abs() < 0.001);
+            assert!((hit.point.x - 4.0).abs() < 0.001);
+            assert!((hit.normal.x + 1.0).abs() < 0.001);
+
+            let hits = world.physics_ref().cast_ray_all(query()).unwrap();
+            assert_eq!(
+                hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+                vec![near, far]
+            );
+            assert!((hits[1].toi - 9.0).abs() < 0.001);
+        }
+
+        #[test]
+        fn shape_casts_report_hit_details() {
+            let mut world = World::new();
+            let (near, _) = boxes_in_a_row(&mut world);
+
+            let hit = world
+                .physics()
+                .cast_shape(
+                    &Ball::new(0.5),
+                    ShapeCastQuery {
+                        velocity: Vector2::new(1.0, 0.0),
+                        max_toi: 100.0,
+                        ..ShapeCastQuery::default()
+                    },
+                )
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(hit.entity, near);
+            assert!((hit.toi - 3.5).abs() < 0.001);
+            assert!((hit.point.x - 4.0).abs() < 0.001);
+            assert!((hit.normal.x + 1.0).abs() < 0.001);
+        }
+
+        #[test]
+        fn intersection_queries_find_entities() {
+            let mut world = World::new();
+            let (near, far) = boxes_in_a_row(&mut world);
+
+            let at_point = |x: f32| {
+                world
+                    .physics_ref()
+                    .intersections_with_point(PointQuery {
+                        point: Translation::new(x, 0.5),
+                        ..PointQuery::default()
+                    })
+                    .unwrap()
+            };
+            assert_eq!(at_point(5.5), vec![near]);
+            assert_eq!(at_point(10.5), vec![far]);
+            assert!(at_point(7.5).is_empty());
+
+            let mut in_shape = world
+                .physics()
+                .intersections_with_shape(
+                    &Cuboid::new(Vector2::new(3.0, 1.0)),
+                    ShapeQuery {
+                        origin_translation: Translation::new(7.5, 0.0),
+                        ..ShapeQuery::default()
+                    },
+                )
+                .unwrap();
+            in_shape.sort();
+            assert_eq!(in_shape, vec![near, far]);
+
+            assert_eq!(
+                world
+                    .physics()
+                    .intersections_with_aabb(Rectangle::new(0.0, -1.0, 4.5, 2.0)),
+                vec![near]
+            );
+            assert!(world
+                .physics()
+                .intersections_with_aabb(Rectangle::new(0.0, 5.0, 20.0, 2.0))
+                .is_empty());
+        }
+
+        #[test]
+        fn contact_force_events_are_opt_in() {
+            let mut world = World::new();
+            falling_box(&mut world);
+
+            world.physics().step_n(10, 1.0 / 60.0);
+            assert!(!world
+                .physics()
+                .events()
+                .iter()
+                .any(|event| matches!(event, PhysicsEvent::ContactForce(_))));
+
+            world.physics().set_contact_force_event_threshold(Some(1.0));
+            world.physics().step(1.0 / 60.0);
+            let contact_force = world
+                .physics()
+                .events()
+                .iter()
+                .find_map(|event| match event {
+                    PhysicsEvent::ContactForce(contact_force) => Some(contact_force.clone()),
+                    _ => None,
+                })
+                .unwrap();
+
+            assert!(contact_force.total_force_magnitude >= 1.0);
+            assert!(!contact_force.contacts.is_empty());
+            assert!(contact_force
+                .contacts
+                .iter()
+                .all(|contact| contact.normal.y.abs() > 0.99 && contact.point.y.abs() < 1.1));
+        }
+
+        #[test]
+        fn restored_snapshots_resume_the_simulation_exactly() {
+            let mut world = World::new();
+            let boxes = pile_of_boxes(&mut world);
+            world.physics().step_n(20, 1.0 / 60.0);
+            let snapshot = world.snapshot().unwrap();
+
+            world.physics().step_n(60, 1.0 / 60.0);
+            let expected = translations(&world, &boxes);
+
+            world.restore(&snapshot).unwrap();
+            world.physics().step_n(60, 1.0 / 60.0);
+            assert_eq!(translations(&world, &boxes), expected);
+
+            // A new world with the same components registered resumes the same way.
+            let mut other_world = World::new();
+            other_world.restore(&snapshot).unwrap();
+            other_world.physics().step_n(60, 1.0 / 60.0);
+            assert_eq!(translations(&other_world, &boxes), expected);
+        }
+
+        #[test]
+        fn serialized_snapshots_resume_the_simulation_exactly() {
+            let mut world = World::new();
+            let boxes = pile_of_boxes(&mut world);
+            world.physics().step_n(20, 1.0 / 60.0);
+            let bytes = bincode::serialize(&world.snapshot().unwrap()).unwrap();
+
+            world.physics().step_n(60, 1.0 / 60.0);
+            let expected = translations(&world, &boxes);
+
+            let snapshot: WorldSnapshot = bincode::deserialize(&bytes).unwrap();
+            let mut other_world = World::new();
+            other_world.restore(&snapshot).unwrap();
+            other_world.physics().step_n(60, 1.0 / 60.0);
+            assert_eq!(translations(&other_world, &boxes), expected);
+        }
+
+        #[test]
+        fn restore_brings_back_despawned_bodies() {
+            let mut world = World::new();
+            let boxes = pile_of_boxes(&mut world);
+            let colliders = world.physics().get_colliders(boxes[0]);
+            world.physics().step_n(10, 1.0 / 60.0);
+            let snapshot = world.snapshot().unwrap();
+
+            world.despawn(boxes[0]).unwrap();
+            let (spawned, _) = spawn_dynamic_box(&mut world, 10.0, 10.0);
+            world.physics().step(1.0 / 60.0);
+            world.restore(&snapshot).unwrap();
+
+            assert!(world.get::<RigidBodyHandle>(boxes[0]).is_ok());
+            assert_eq!(world.physics().get_colliders(boxes[0]), colliders);
+            assert!(!world.inner.contains(spawned));
+            assert!(world.physics().get_colliders(spawned).is_empty());
+            assert_eq!(
+                world.physics_engine.bodies.len(),
+                world.physics_engine.entity_bodies.len()
+            );
+
+            let before = translations(&world, &boxes[..1])[0];
+            world.physics().step_n(10, 1.0 / 60.0);
+            assert!(translations(&world, &boxes[..1])[0].1 < before.1);
+        }
+
+        #[test]
+        fn bodies_of_children_live_at_their_global_transform() {
+            let mut world = World::new();
+            world.physics().set_gravity(Vector2::new(0.0, -10.0));
+            let mut parent_transform = Transform::from_translation((100.0, 0.0));
+            parent_transform.rotation = std::f32::consts::FRAC_PI_2;
+            let parent = world.spawn((parent_transform,));
+            let child = world.spawn((Transform::from_translation((10.0, 0.0)),));
+            world.set_parent(child, parent).unwrap();
+            let body = world
+                .physics()
+                .build_body(child, RigidBodyBuilder::dynamic())
+                .unwrap();
+            world
+                .physics()
+                .build_collider(body, ColliderBuilder::ball(0.5));
+
+            let position = *world.physics().rigid_body(body).unwrap().position();
+            assert!((position.translation.x - 100.0).abs() < 0.001);
+            assert!((position.translation.y - 10.0).abs() < 0.001);
+
+            world.physics().step_n(10, 1.0 / 60.0);
+
+            // The body fell in world space, which is along the local x axis of the turned parent.
+            let position = *world.physics().rigid_body(body).unwrap().position();
+            let transform = *world.get::<Transform>(child).unwrap();
+            let global_transform = world.global_transform(child).unwrap();
+            assert!(position.translation.y < 10.0);
+            assert!((global_transform.translation.x - position.translation.x).abs() < 0.001);
+            assert!((global_transform.translation.y - position.translation.y).abs() < 0.001);
+            assert!(transform.translation.x < 10.0);
+            assert!(transform.translation.y.abs() < 0.001);
+
+            // Moving the parent carries the body along.
+            world.get_mut::<Transform>(parent).unwrap().translation.x = 200.0;
+            world.physics().step(1.0 / 60.0);
+            let position = *world.physics().rigid_body(body).unwrap().position();
+            assert!((position.translation.x - 200.0).abs() < 0.001);
+        }
+
+        #[test]
+        fn merged_tilemap_colliders_keep_rebuilding() {
+            let mut world = World::new();
+            let (other_box, _) = spawn_dynamic_box(&mut world, 100.0, 100.0);
+            let mut other_world = World::new();
+            let level = level(&mut other_world, TilemapColliders::new());
+            let collider_count = other_world.physics().get_colliders(level).len();
+
+            let level = world.merge(other_world).unwrap()[&level];
+            world
+                .get_mut::<Tilemap>(level)
+                .unwrap()
+                .set_tile(6, 2, Some(1))
+                .unwrap();
+            world.physics().step(1.0 / 60.0);
+
+            assert_eq!(world.physics().get_colliders(other_box).len(), 1);
+            assert_eq!(
+                world.physics().get_colliders(level).len(),
+                collider_count + 1
+            );
+            assert_eq!(
+                world.physics_engine.colliders.len(),
+                world.physics_engine.collider_body.len()
+            );
+        }
+
+        #[test]
+        fn saved_ents_leave_out_tilemap_colliders() {
+            let mut world = World::new();
+            let level = level(&mut world, TilemapColliders::new());
+            let body = *world.get::<RigidBodyHandle>(level).unwrap();
+            world
+                .physics()
+                .build_collider(body, ColliderBuilder::ball(1.0));
+            assert!(world.physics().get_colliders(level).len() > 1);
+
+            let saved = world
+                .save_ent(level, crate::ent::EntSaveConfig::default())
+                .unwrap();
+            let toml = saved.parse::<toml::Value>().unwrap();
+            let colliders = toml["rigid_body"]["colliders"].as_array().unwrap();
+            assert_eq!(colliders.len(), 1);
+            assert_eq!(colliders[0]["shape"].as_str(), Some("ball"));
+        }
+
+        #[test]
+        fn restore_puts_back_tilemap_colliders() {
+            let mut world = World::new();
+            let level = level(
+                &mut world,
+                TilemapColliders::new().collider(ColliderBuilder::cuboid(0.0, 0.0).friction(0.25)),
+            );
+            let colliders = world.physics().get_colliders(level);
+            let snapshot = world.snapshot().unwrap();
+
+            world
+                .get_mut::<Tilemap>(level)
+                .unwrap()
+                .set_tile(6, 2, Some(1))
+                .unwrap();
+            world.physics().step(1.0 / 60.0);
+            assert_ne!(world.physics().get_colliders(level), colliders);
+            world.restore(&snapshot).unwrap();
+
+            assert_eq!(world.physics().get_colliders(level), colliders);
+            world
+                .get_mut::<Tilemap>(level)
+                .unwrap()
+                .set_tile(6, 2, Some(1))
+                .unwrap();
+            world.physics().step(1.0 / 60.0);
+            let rebuilt = world.physics().get_colliders(level);
+            assert_eq!(rebuilt.len(), colliders.len() + 1);
+            for collider in rebuilt {
+                assert_eq!(world.physics_engine.colliders[collider].friction(), 0.25);
+            }
+            assert_eq!(
+                world.physics_engine.colliders.len(),
+                world.physics_engine.collider_body.len()
+            );
+        }
+
+        #[test]
+        fn collision_layers_filter_contacts_and_queries() {
+            let mut world = World::new();
+            let mut layers = CollisionLayers::new();
+            for layer in ["player", "enemy", "terrain"] {
+                layers.add_layer(layer).unwrap();
+            }
+            layers.set_collides("enemy", "enemy", false).unwrap();
+            assert!(layers.add_layer("enemy").is_err());
+            assert!(layers.collides("enemy", "terrain"));
+            assert!(!layers.collides("enemy", "enemy"));
+            world.physics().set_collision_layers(layers);
+            world.physics().set_gravity(Vector2::new(0.0, -10.0));
+
+            let terrain = spawn_in_layer(&mut world, RigidBodyBuilder::fixed(), 0.0, "terrain");
+            let enemy = spawn_in_layer(&mut world, RigidBodyBuilder::fixed(), 3.0, "enemy");
+            let falling = spawn_in_layer(&mut world, RigidBodyBuilder::dynamic(), 6.0, "enemy");
+            world.physics().step_n(180, 1.0 / 60.0);
+
+            // The falling enemy went through the other one and landed on the terrain.
+            let y = world.get::<Transform>(falling).unwrap().translation.y;
+            assert!((y - 1.0).abs() < 0.1);
+
+            let ray_down = |layers| RayCastQuery {
+                ray: Ray::new(Point2::new(0.0, 10.0), Vector2::new(0.0, -1.0)),
+                max_toi: 20.0,
+                layers,
+                ..RayCastQuery::default()
+            };
+            let hit = |layers| {
+                world
+                    .physics_ref()
+                    .cast_ray(ray_down(layers))
+                    .unwrap()
+                    .unwrap()
+                    .entity
+            };
+            assert_eq!(hit(&["terrain"]), terrain);
+            assert_eq!(hit(&["player", "enemy"]), enemy);
+            assert!(world
+                .physics_ref()
+                .cast_ray(ray_down(&["player"]))
+                .unwrap()
+                .is_none());
+            assert!(world
+                .physics_ref()
+                .cast_ray(ray_down(&["no_such_layer"]))
+                .is_err());
+
+            let cast_down = world.physics_ref().cast_shape(
+                &Ball::new(0.25),
+                ShapeCastQuery {
+                    origin_translation: Translation::new(0.0, 10.0),
+                    velocity: Vector2::new(0.0, -1.0),
+                    max_toi: 20.0,
+                    layers: &["terrain"],
+                    ..ShapeCastQuery::default()
+                },
+            );
+            assert_eq!(cast_down.unwrap().unwrap().entity, terrain);
+        }
     }
 }
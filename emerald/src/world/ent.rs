@@ -1,34 +1,82 @@
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, Transform, World};
+use crate::{AssetLoader, Color, EmeraldError, Rectangle, Transform, World};
 
-use self::ent_sprite_loader::load_ent_sprite;
+use self::ent_camera_loader::{load_ent_camera, save_ent_camera};
+use self::ent_color_rect_loader::{load_ent_color_rect, save_ent_color_rect};
+pub use self::ent_component_registry::EntComponentRegistry;
+use self::ent_label_loader::{load_ent_label, save_ent_label};
+use self::ent_sprite_loader::{load_ent_sprite, save_ent_sprite};
+use self::ent_tilemap_loader::{load_ent_tilemap, save_ent_tilemap};
+use self::ent_transform_loader::{load_ent_transform, save_ent_transform};
 #[cfg(feature = "aseprite")]
 pub(crate) mod ent_aseprite_loader;
+pub(crate) mod ent_camera_loader;
+pub(crate) mod ent_color_rect_loader;
+pub(crate) mod ent_component_registry;
+pub(crate) mod ent_label_loader;
 pub(crate) mod ent_sprite_loader;
+pub(crate) mod ent_tilemap_loader;
+pub(crate) mod ent_transform_loader;
 
 #[cfg(feature = "physics")]
 pub(crate) mod ent_rigid_body_loader;
 
+pub(crate) const TRANSFORM_SCHEMA_KEY: &str = "transform";
+
 const SPRITE_SCHEMA_KEY: &str = "sprite";
 
 const RIGID_BODY_SCHEMA_KEY: &str = "rigid_body";
 
 const ASEPRITE_SCHEMA_KEY: &str = "aseprite";
 
+const LABEL_SCHEMA_KEY: &str = "label";
+
+const COLOR_RECT_SCHEMA_KEY: &str = "color_rect";
+
+pub(crate) const CAMERA_SCHEMA_KEY: &str = "camera";
+
+const TILEMAP_SCHEMA_KEY: &str = "tilemap";
+
+/// Keys read by `load_ent` itself, which custom components can't be registered under.
+pub(crate) const BUILT_IN_SCHEMA_KEYS: [&str; 8] = [
+    TRANSFORM_SCHEMA_KEY,
+    SPRITE_SCHEMA_KEY,
+    RIGID_BODY_SCHEMA_KEY,
+    ASEPRITE_SCHEMA_KEY,
+    LABEL_SCHEMA_KEY,
+    COLOR_RECT_SCHEMA_KEY,
+    CAMERA_SCHEMA_KEY,
+    TILEMAP_SCHEMA_KEY,
+];
+
+/// Loads a custom component from its toml value, given with the key it was found under.
+pub type CustomComponentLoader<'a> = dyn Fn(&mut AssetLoader<'_>, Entity, &mut World, toml::Value, String) -> Result<(), EmeraldError>
+    + 'a;
+
+/// Inserts the custom components of an entity into the table of its ent.
+pub type CustomComponentSaver<'a> =
+    dyn Fn(&World, Entity, &mut toml::value::Table) -> Result<(), EmeraldError> + 'a;
+
 #[derive(Default)]
 pub struct EntLoadConfig<'a> {
+    /// The transform given to the entity. When the ent file contains its own `[transform]`,
+    /// that transform is applied relative to this one, as if the ent were a child placed at it.
     pub transform: Transform,
-    pub custom_component_loader: Option<
-        &'a dyn Fn(
-            &mut AssetLoader<'_>,
-            Entity,
-            &mut World,
-            toml::Value,
-            String,
-        ) -> Result<(), EmeraldError>,
-    >,
+    /// Called with the keys that are neither built in nor registered in the game's
+    /// [`EntComponentRegistry`], which otherwise fail to load.
+    pub custom_component_loader: Option<&'a CustomComponentLoader<'a>>,
+}
+
+#[derive(Default)]
+pub struct EntSaveConfig<'a> {
+    /// Components written after the built-in ones, usually the game's registry from `emd.ent_components()`.
+    pub components: Option<&'a EntComponentRegistry>,
+    /// Called after the built-in components have been written.
+    /// Insert any custom components into the given table under their own keys,
+    /// mirroring what the `custom_component_loader` of [`EntLoadConfig`] expects.
+    pub custom_component_saver: Option<&'a CustomComponentSaver<'a>>,
 }
 
 pub(crate) fn load_ent(
@@ -37,10 +85,47 @@ pub(crate) fn load_ent(
     toml: String,
     config: EntLoadConfig<'_>,
 ) -> Result<Entity, EmeraldError> {
-    let entity = world.spawn((config.transform,));
+    let toml = toml.parse::<toml::Value>()?;
+    load_ent_from_toml(loader, world, toml, config)
+}
+
+pub(crate) fn load_ent_from_toml(
+    loader: &mut AssetLoader<'_>,
+    world: &mut World,
+    mut toml: toml::Value,
+    config: EntLoadConfig<'_>,
+) -> Result<Entity, EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load an ent from a non-table toml value.",
+        ));
+    }
+
+    let mut transform = config.transform;
+    if let Some(table) = toml.as_table_mut() {
+        if let Some(transform_value) = table.remove(TRANSFORM_SCHEMA_KEY) {
+            transform = transform.mul_transform(&load_ent_transform(&transform_value)?);
+        }
+    }
+
+    let entity = world.spawn((transform,));
+
+    // A failing component leaves no half-built entity behind.
+    if let Err(e) = load_ent_components(loader, world, entity, toml, config) {
+        world.despawn(entity).ok();
+        return Err(e);
+    }
 
-    let mut toml = toml.parse::<toml::Value>()?;
+    Ok(entity)
+}
 
+fn load_ent_components(
+    loader: &mut AssetLoader<'_>,
+    world: &mut World,
+    entity: Entity,
+    mut toml: toml::Value,
+    config: EntLoadConfig<'_>,
+) -> Result<(), EmeraldError> {
     if let Some(table) = toml.as_table_mut() {
         let table_keys = table
             .keys()
@@ -66,6 +151,8 @@ pub(crate) fn load_ent(
                             )?;
                         }
                     }
+                    #[cfg(not(feature = "physics"))]
+                    return Err(missing_feature_error(&key, "physics"));
                 }
                 ASEPRITE_SCHEMA_KEY => {
                     #[cfg(feature = "aseprite")]
@@ -79,11 +166,43 @@ pub(crate) fn load_ent(
                             )?;
                         }
                     }
+                    #[cfg(not(feature = "aseprite"))]
+                    return Err(missing_feature_error(&key, "aseprite"));
+                }
+                LABEL_SCHEMA_KEY => {
+                    if let Some(label_value) = table.remove(LABEL_SCHEMA_KEY) {
+                        load_ent_label(loader, entity, world, &label_value)?;
+                    }
+                }
+                COLOR_RECT_SCHEMA_KEY => {
+                    if let Some(color_rect_value) = table.remove(COLOR_RECT_SCHEMA_KEY) {
+                        load_ent_color_rect(entity, world, &color_rect_value)?;
+                    }
+                }
+                CAMERA_SCHEMA_KEY => {
+                    if let Some(camera_value) = table.remove(CAMERA_SCHEMA_KEY) {
+                        load_ent_camera(entity, world, &camera_value)?;
+                    }
+                }
+                TILEMAP_SCHEMA_KEY => {
+                    if let Some(tilemap_value) = table.remove(TILEMAP_SCHEMA_KEY) {
+                        load_ent_tilemap(loader, entity, world, &tilemap_value)?;
+                    }
                 }
                 _ => {
-                    if let Some(custom_component_loader) = config.custom_component_loader {
-                        if let Some(value) = table.remove(&key) {
-                            custom_component_loader(loader, entity, world, value, key)?;
+                    if let Some(value) = table.remove(&key) {
+                        let value = loader
+                            .asset_store
+                            .ent_components
+                            .load(world, entity, &key, value)?;
+
+                        if let Some(value) = value {
+                            match config.custom_component_loader {
+                                Some(custom_component_loader) => {
+                                    custom_component_loader(loader, entity, world, value, key)?;
+                                }
+                                None => return Err(unknown_component_error(&key)),
+                            }
                         }
                     }
                 }
@@ -91,11 +210,763 @@ pub(crate) fn load_ent(
         }
     }
 
-    Ok(entity)
+    Ok(())
+}
+
+#[cfg(any(not(feature = "physics"), not(feature = "aseprite")))]
+fn missing_feature_error(key: &str, feature: &str) -> EmeraldError {
+    EmeraldError::new(format!(
+        "Ent component {:?} requires emerald's {:?} feature.",
+        key, feature
+    ))
+}
+
+fn unknown_component_error(key: &str) -> EmeraldError {
+    EmeraldError::new(format!(
+        "Unknown ent component {:?}, register it with `emd.loader().ent_components()` or handle it in a custom_component_loader.",
+        key
+    ))
+}
+
+/// Serializes the components of an entity into the same toml schema that [`load_ent`] reads.
+pub(crate) fn save_ent(
+    world: &World,
+    entity: Entity,
+    config: EntSaveConfig<'_>,
+) -> Result<String, EmeraldError> {
+    let table = save_ent_to_table(world, entity, &config)?;
+    let toml = toml::to_string(&toml::Value::Table(table))?;
+
+    Ok(toml)
+}
+
+pub(crate) fn save_ent_to_table(
+    world: &World,
+    entity: Entity,
+    config: &EntSaveConfig<'_>,
+) -> Result<toml::value::Table, EmeraldError> {
+    if !world.contains(entity) {
+        return Err(EmeraldError::new(format!(
+            "Entity {:?} does not exist, cannot save it.",
+            entity
+        )));
+    }
+
+    let mut table = toml::value::Table::new();
+
+    insert_schema(
+        &mut table,
+        TRANSFORM_SCHEMA_KEY,
+        save_ent_transform(world, entity)?,
+    );
+    insert_schema(
+        &mut table,
+        SPRITE_SCHEMA_KEY,
+        save_ent_sprite(world, entity)?,
+    );
+    insert_schema(&mut table, LABEL_SCHEMA_KEY, save_ent_label(world, entity)?);
+    insert_schema(
+        &mut table,
+        COLOR_RECT_SCHEMA_KEY,
+        save_ent_color_rect(world, entity)?,
+    );
+    insert_schema(
+        &mut table,
+        CAMERA_SCHEMA_KEY,
+        save_ent_camera(world, entity)?,
+    );
+    insert_schema(
+        &mut table,
+        TILEMAP_SCHEMA_KEY,
+        save_ent_tilemap(world, entity)?,
+    );
+
+    #[cfg(feature = "aseprite")]
+    insert_schema(
+        &mut table,
+        ASEPRITE_SCHEMA_KEY,
+        ent_aseprite_loader::save_ent_aseprite(world, entity)?,
+    );
+
+    #[cfg(feature = "physics")]
+    insert_schema(
+        &mut table,
+        RIGID_BODY_SCHEMA_KEY,
+        ent_rigid_body_loader::save_ent_rigid_body(world, entity)?,
+    );
+
+    if let Some(components) = config.components {
+        components.save(world, entity, &mut table)?;
+    }
+
+    if let Some(custom_component_saver) = config.custom_component_saver {
+        custom_component_saver(world, entity, &mut table)?;
+    }
+
+    Ok(table)
+}
+
+fn insert_schema(table: &mut toml::value::Table, key: &str, value: Option<toml::Value>) {
+    if let Some(value) = value {
+        table.insert(key.to_string(), value);
+    }
 }
 
 #[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct Vec2f32Schema {
     pub x: f32,
     pub y: f32,
 }
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct Vec2usizeSchema {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct RectangleSchema {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+impl From<RectangleSchema> for Rectangle {
+    fn from(schema: RectangleSchema) -> Self {
+        Rectangle::new(schema.x, schema.y, schema.width, schema.height)
+    }
+}
+impl From<&Rectangle> for RectangleSchema {
+    fn from(rect: &Rectangle) -> Self {
+        RectangleSchema {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+}
+
+/// Bit masks of the groups a collider belongs to and the groups it interacts with.
+#[cfg(feature = "physics")]
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct InteractionGroupsSchema {
+    pub memberships: u32,
+    pub filter: u32,
+}
+#[cfg(feature = "physics")]
+impl From<InteractionGroupsSchema> for crate::InteractionGroups {
+    fn from(schema: InteractionGroupsSchema) -> Self {
+        crate::InteractionGroups::new(schema.memberships, schema.filter)
+    }
+}
+#[cfg(feature = "physics")]
+impl From<&crate::InteractionGroups> for InteractionGroupsSchema {
+    fn from(groups: &crate::InteractionGroups) -> Self {
+        InteractionGroupsSchema {
+            memberships: groups.memberships,
+            filter: groups.filter,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct ColorSchema {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: Option<u8>,
+}
+impl From<ColorSchema> for Color {
+    fn from(schema: ColorSchema) -> Self {
+        Color::new(schema.r, schema.g, schema.b, schema.a.unwrap_or(255))
+    }
+}
+impl From<&Color> for ColorSchema {
+    fn from(color: &Color) -> Self {
+        ColorSchema {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: Some(color.a),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Camera, ColorRect, Transform, World, WHITE};
+
+    use super::{
+        ent_camera_loader::load_ent_camera, ent_color_rect_loader::load_ent_color_rect,
+        ent_transform_loader::load_ent_transform, EntComponentRegistry, EntSaveConfig,
+    };
+    use serde::{Deserialize, Serialize};
+
+    /// Runs `f` with the asset loader of a headless game, from its `initialize`.
+    #[cfg(feature = "headless")]
+    fn with_loader(f: impl FnOnce(&mut crate::AssetLoader<'_>) + 'static) {
+        use crate::{Emerald, Game, GameSettings, HeadlessRunner};
+
+        struct LoaderGame(Option<Box<dyn FnOnce(&mut crate::AssetLoader<'_>)>>);
+        impl Game for LoaderGame {
+            fn initialize(&mut self, mut emd: Emerald<'_>) {
+                if let Some(f) = self.0.take() {
+                    f(&mut emd.loader());
+                }
+            }
+        }
+
+        HeadlessRunner::new(
+            Box::new(LoaderGame(Some(Box::new(f)))),
+            GameSettings::default(),
+        );
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn ent_transform_is_relative_to_the_config_transform() {
+        use super::{load_ent, EntLoadConfig};
+
+        with_loader(|loader| {
+            let mut world = World::new();
+            let ent = "[transform]\ntranslation = { x = 10.0, y = 0.0 }\nrotation = 0.5\n";
+            let config = EntLoadConfig {
+                transform: Transform {
+                    rotation: std::f32::consts::FRAC_PI_2,
+                    ..Transform::from_translation((100.0, 50.0))
+                },
+                ..Default::default()
+            };
+
+            let entity = load_ent(loader, &mut world, ent.to_string(), config).unwrap();
+            let transform = *world.get::<Transform>(entity).unwrap();
+            assert!((transform.translation.x - 100.0).abs() < 1e-4);
+            assert!((transform.translation.y - 60.0).abs() < 1e-4);
+            assert!((transform.rotation - (std::f32::consts::FRAC_PI_2 + 0.5)).abs() < 1e-6);
+
+            let entity = load_ent(
+                loader,
+                &mut world,
+                String::new(),
+                EntLoadConfig {
+                    transform: Transform::from_translation((3.0, 4.0)),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(world.get::<Transform>(entity).unwrap().translation.x, 3.0);
+        });
+    }
+
+    #[cfg(feature = "headless")]
+    #[test]
+    fn failing_ents_leave_no_entity_behind() {
+        use super::{load_ent, EntLoadConfig};
+
+        with_loader(|loader| {
+            let mut world = World::new();
+            world.spawn((Transform::default(),));
+            // Keys load in order, the unknown one fails after the others are built.
+            let ent = r#"
+                [color_rect]
+                color = { r = 255, g = 255, b = 255 }
+                width = 8
+                height = 8
+
+                [rigid_body]
+                body_type = "dynamic"
+
+                [unknown_component]
+                value = 3
+            "#;
+
+            assert!(load_ent(
+                loader,
+                &mut world,
+                ent.to_string(),
+                EntLoadConfig::default()
+            )
+            .is_err());
+            assert_eq!(world.inner.len(), 1);
+            #[cfg(feature = "physics")]
+            assert_eq!(world.physics().body_count(), 0);
+        });
+    }
+
+    #[cfg(all(feature = "headless", not(feature = "physics")))]
+    #[test]
+    fn rigid_bodies_fail_to_load_without_the_physics_feature() {
+        use super::{load_ent, EntLoadConfig};
+
+        with_loader(|loader| {
+            let mut world = World::new();
+            let ent = "[rigid_body]\nbody_type = \"dynamic\"\n";
+
+            let error = load_ent(
+                loader,
+                &mut world,
+                ent.to_string(),
+                EntLoadConfig::default(),
+            )
+            .err()
+            .unwrap();
+            assert!(error.message.contains("physics"));
+            assert_eq!(world.inner.len(), 0);
+        });
+    }
+
+    #[cfg(all(feature = "headless", not(feature = "aseprite")))]
+    #[test]
+    fn aseprites_fail_to_load_without_the_aseprite_feature() {
+        use super::{load_ent, EntLoadConfig};
+
+        with_loader(|loader| {
+            let mut world = World::new();
+            let ent = "[aseprite]\naseprite = \"player.aseprite\"\n";
+
+            let error = load_ent(
+                loader,
+                &mut world,
+                ent.to_string(),
+                EntLoadConfig::default(),
+            )
+            .err()
+            .unwrap();
+            assert!(error.message.contains("aseprite"));
+            assert_eq!(world.inner.len(), 0);
+        });
+    }
+
+    #[test]
+    fn save_ent_fails_on_nonexisting_entity() {
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(),));
+        world.despawn(entity).unwrap();
+
+        assert!(world.save_ent(entity, EntSaveConfig::default()).is_err());
+    }
+
+    #[test]
+    fn saved_components_load_back_into_a_world() {
+        let mut world = World::new();
+        let mut transform = Transform::from_translation((10.0, -20.0));
+        transform.rotation = 1.5;
+        let mut camera = Camera::default();
+        camera.zoom = 2.0;
+        let entity = world.spawn((transform, ColorRect::new(WHITE, 8, 16), camera));
+        world.make_active_camera(entity).unwrap();
+
+        let saved = world.save_ent(entity, EntSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+
+        let mut other_world = World::new();
+        let loaded_transform = load_ent_transform(&toml["transform"]).unwrap();
+        let other_entity = other_world.spawn((loaded_transform,));
+        load_ent_color_rect(other_entity, &mut other_world, &toml["color_rect"]).unwrap();
+        load_ent_camera(other_entity, &mut other_world, &toml["camera"]).unwrap();
+
+        assert_eq!(loaded_transform.translation.x, 10.0);
+        assert_eq!(loaded_transform.translation.y, -20.0);
+        assert_eq!(loaded_transform.rotation, 1.5);
+        let color_rect = other_world.get::<ColorRect>(other_entity).unwrap();
+        assert_eq!(color_rect.width, 8);
+        assert_eq!(color_rect.height, 16);
+        assert_eq!(other_world.get::<Camera>(other_entity).unwrap().zoom, 2.0);
+        assert_eq!(other_world.get_active_camera(), Some(other_entity));
+    }
+
+    #[test]
+    fn custom_component_saver_writes_into_ent() {
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(), 50_i64));
+        let saver = |world: &World, entity, table: &mut toml::value::Table| {
+            let max_hp = *world.get::<i64>(entity)?;
+            table.insert(String::from("max_hp"), toml::Value::Integer(max_hp));
+            Ok(())
+        };
+        let config = EntSaveConfig {
+            custom_component_saver: Some(&saver),
+            ..Default::default()
+        };
+
+        let saved = world.save_ent(entity, config).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+
+        assert_eq!(toml["max_hp"].as_integer(), Some(50));
+        assert!(toml.get("sprite").is_none());
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health {
+        max_hp: i64,
+        regeneration: Option<f32>,
+    }
+
+    #[test]
+    fn registered_components_round_trip_through_ent() {
+        let mut registry = EntComponentRegistry::new();
+        registry.register::<Health>("health").unwrap();
+
+        let mut world = World::new();
+        let health = Health {
+            max_hp: 50,
+            regeneration: Some(0.5),
+        };
+        let entity = world.spawn((Transform::default(), health));
+        let bare_entity = world.spawn((Transform::default(),));
+        let config = EntSaveConfig {
+            components: Some(&registry),
+            ..Default::default()
+        };
+
+        let saved = world.save_ent(entity, config).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        assert_eq!(toml["health"]["max_hp"].as_integer(), Some(50));
+
+        let config = EntSaveConfig {
+            components: Some(&registry),
+            ..Default::default()
+        };
+        let saved = world.save_ent(bare_entity, config).unwrap();
+        assert!(saved
+            .parse::<toml::Value>()
+            .unwrap()
+            .get("health")
+            .is_none());
+
+        let mut other_world = World::new();
+        let other_entity = other_world.spawn((Transform::default(),));
+        let leftover = registry
+            .load(
+                &mut other_world,
+                other_entity,
+                "health",
+                toml["health"].clone(),
+            )
+            .unwrap();
+
+        assert!(leftover.is_none());
+        assert_eq!(
+            *other_world.get::<Health>(other_entity).unwrap(),
+            Health {
+                max_hp: 50,
+                regeneration: Some(0.5),
+            }
+        );
+    }
+
+    #[test]
+    fn registry_rejects_built_in_keys_and_malformed_components() {
+        let mut registry = EntComponentRegistry::new();
+        assert!(registry.register::<Health>("sprite").is_err());
+        assert!(registry.register::<Health>("transform").is_err());
+        registry.register::<Health>("health").unwrap();
+        assert_eq!(registry.keys().collect::<Vec<_>>(), vec!["health"]);
+
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(),));
+        let unknown = toml::Value::Integer(3);
+        let leftover = registry
+            .load(&mut world, entity, "mana", unknown.clone())
+            .unwrap();
+        assert_eq!(leftover, Some(unknown));
+
+        let mut malformed = toml::value::Table::new();
+        malformed.insert(String::from("max_hp"), toml::Value::from("lots"));
+        let error = registry
+            .load(&mut world, entity, "health", toml::Value::Table(malformed))
+            .unwrap_err();
+        assert!(error.message.contains("health"));
+        assert!(world.get::<Health>(entity).is_err());
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn rigid_body_is_saved_with_colliders() {
+        use crate::{ColliderBuilder, RigidBodyBuilder};
+
+        let mut world = World::new();
+        let (entity, rbh) = world
+            .spawn_with_body((Transform::default(),), RigidBodyBuilder::fixed())
+            .unwrap();
+        world
+            .physics()
+            .build_collider(rbh, ColliderBuilder::cuboid(4.0, 2.0).sensor(true));
+
+        let saved = world.save_ent(entity, EntSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        let rigid_body = &toml["rigid_body"];
+        let collider = &rigid_body["colliders"][0];
+
+        assert_eq!(rigid_body["body_type"].as_str(), Some("fixed"));
+        assert_eq!(collider["shape"].as_str(), Some("cuboid"));
+        assert_eq!(collider["half_width"].as_float(), Some(4.0));
+        assert_eq!(collider["sensor"].as_bool(), Some(true));
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn colliders_reference_collision_layers_by_name() {
+        use super::ent_rigid_body_loader::load_ent_collider;
+        use crate::{CollisionLayers, RigidBodyBuilder};
+
+        let mut world = World::new();
+        let mut layers = CollisionLayers::new();
+        layers.add_layer("player").unwrap();
+        layers.add_layer("enemy").unwrap();
+        layers.set_collides("enemy", "enemy", false).unwrap();
+        world.physics().set_collision_layers(layers);
+        let (entity, rbh) = world
+            .spawn_with_body((Transform::default(),), RigidBodyBuilder::fixed())
+            .unwrap();
+
+        let schema = |layer: &str| {
+            toml::from_str(&format!(
+                "shape = \"ball\"\nradius = 1.0\nlayer = \"{}\"",
+                layer
+            ))
+            .unwrap()
+        };
+        let collider = load_ent_collider(rbh, &mut world, schema("enemy")).unwrap();
+        assert!(load_ent_collider(rbh, &mut world, schema("ghost")).is_err());
+
+        let enemy_groups = world.physics().collision_layers().groups("enemy").unwrap();
+        let groups = world
+            .physics()
+            .get_collider_desc(collider)
+            .unwrap()
+            .collision_groups();
+        assert_eq!(groups, enemy_groups);
+
+        let saved = world.save_ent(entity, EntSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        assert_eq!(
+            toml["rigid_body"]["colliders"][0]["layer"].as_str(),
+            Some("enemy")
+        );
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn colliders_without_groups_get_the_default_groups() {
+        use super::ent_rigid_body_loader::load_ent_collider;
+        use crate::{InteractionGroups, PhysicsSettings, RigidBodyBuilder};
+
+        let mut world = World::new();
+        world.physics().set_settings(PhysicsSettings {
+            collision_groups: InteractionGroups::new(2, 1),
+            ..Default::default()
+        });
+        let (entity, rbh) = world
+            .spawn_with_body((Transform::default(),), RigidBodyBuilder::fixed())
+            .unwrap();
+
+        let mut collision_groups = |schema: &str| {
+            let collider =
+                load_ent_collider(rbh, &mut world, toml::from_str(schema).unwrap()).unwrap();
+            world
+                .physics()
+                .get_collider_desc(collider)
+                .unwrap()
+                .collision_groups()
+        };
+        assert_eq!(
+            collision_groups("shape = \"ball\"\nradius = 1.0"),
+            InteractionGroups::new(2, 1)
+        );
+        assert_eq!(
+            collision_groups(
+                "shape = \"ball\"\nradius = 1.0\ncollision_groups = { memberships = 4, filter = 4 }"
+            ),
+            InteractionGroups::new(4, 4)
+        );
+        let all = "shape = \"ball\"\nradius = 1.0\ncollision_groups = { memberships = 4294967295, filter = 4294967295 }";
+        assert_eq!(collision_groups(all), InteractionGroups::all());
+
+        // Only groups other than the default ones are saved.
+        let saved = world.save_ent(entity, EntSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        let colliders = toml["rigid_body"]["colliders"].as_array().unwrap();
+        assert!(colliders[0].get("collision_groups").is_none());
+        assert_eq!(
+            colliders[1]["collision_groups"]["memberships"].as_integer(),
+            Some(4)
+        );
+        assert_eq!(
+            colliders[2]["collision_groups"]["memberships"].as_integer(),
+            Some(u32::MAX as i64)
+        );
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn rigid_body_options_and_shapes_round_trip_through_ent() {
+        use super::ent_rigid_body_loader::build_ent_rigid_body;
+        use crate::{InteractionGroups, RigidBodyHandle};
+        use rapier2d::prelude::ShapeType;
+
+        let schema = r#"
+            body_type = "dynamic"
+            linear_velocity = { x = 3.0, y = -1.0 }
+            angular_velocity = 0.5
+            linear_damping = 0.25
+            angular_damping = 2.0
+            gravity_scale = 0.5
+            ccd_enabled = true
+            lock_rotations = true
+            sleeping = false
+
+            [[colliders]]
+            shape = "capsule"
+            half_height = 2.0
+            radius = 1.0
+            rotation = 1.5
+            friction = 0.1
+            restitution = 0.9
+            density = 4.0
+            collision_groups = { memberships = 2, filter = 5 }
+
+            [[colliders]]
+            shape = "convex_polygon"
+            points = [{ x = 0.0, y = 0.0 }, { x = 2.0, y = 0.0 }, { x = 1.0, y = 0.5 }, { x = 0.0, y = 2.0 }]
+
+            [[colliders]]
+            shape = "polyline"
+            points = [{ x = 0.0, y = 0.0 }, { x = 1.0, y = 1.0 }, { x = 2.0, y = 0.0 }]
+
+            [[colliders]]
+            shape = "triangle"
+            points = [{ x = 0.0, y = 0.0 }, { x = 1.0, y = 0.0 }, { x = 0.0, y = 1.0 }]
+
+            [[colliders]]
+            shape = "heightfield"
+            heights = [0.0, 1.0, 0.5, 2.0]
+            scale = { x = 30.0, y = 2.0 }
+
+            [[colliders]]
+            shape = "compound"
+            shapes = [
+                { shape = "cuboid", half_width = 1.0, half_height = 1.0 },
+                { shape = "ball", radius = 0.5, translation = { x = 0.0, y = 2.0 } },
+            ]
+        "#;
+
+        let mut world = World::new();
+        let entity = world.spawn((Transform::default(),));
+        build_ent_rigid_body(entity, &mut world, toml::from_str(schema).unwrap()).unwrap();
+
+        let shape_types = |world: &mut World, entity| {
+            let physics = world.physics();
+            physics
+                .get_colliders(entity)
+                .into_iter()
+                .map(|collider| {
+                    physics
+                        .get_collider_desc(collider)
+                        .unwrap()
+                        .shape()
+                        .shape_type()
+                })
+                .collect::<Vec<ShapeType>>()
+        };
+        let expected_shapes = vec![
+            ShapeType::Capsule,
+            ShapeType::ConvexPolygon,
+            ShapeType::Polyline,
+            ShapeType::Triangle,
+            ShapeType::HeightField,
+            ShapeType::Compound,
+        ];
+        assert_eq!(shape_types(&mut world, entity), expected_shapes);
+
+        let saved = world.save_ent(entity, EntSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        assert_eq!(
+            toml["rigid_body"]["colliders"][1]["points"]
+                .as_array()
+                .map(|points| points.len()),
+            Some(3)
+        );
+
+        let mut other_world = World::new();
+        let other_entity = other_world.spawn((Transform::default(),));
+        let rigid_body = toml["rigid_body"].clone().try_into().unwrap();
+        build_ent_rigid_body(other_entity, &mut other_world, rigid_body).unwrap();
+        assert_eq!(shape_types(&mut other_world, other_entity), expected_shapes);
+
+        let rbh = *other_world.get::<RigidBodyHandle>(other_entity).unwrap();
+        let mut physics = other_world.physics();
+        let body = physics.rigid_body(rbh).unwrap();
+        assert_eq!(body.linvel().x, 3.0);
+        assert_eq!(body.angvel(), 0.5);
+        assert_eq!(body.linear_damping(), 0.25);
+        assert_eq!(body.angular_damping(), 2.0);
+        assert_eq!(body.gravity_scale(), 0.5);
+        assert!(body.is_ccd_enabled());
+        assert!(body.is_rotation_locked());
+        assert!(!body.is_sleeping());
+
+        let capsule = physics.get_colliders(other_entity)[0];
+        let capsule = physics.get_collider_desc(capsule).unwrap();
+        assert_eq!(capsule.shape().as_capsule().unwrap().half_height(), 2.0);
+        assert!((capsule.position_wrt_parent().unwrap().rotation.angle() - 1.5).abs() < 0.0001);
+        assert_eq!(capsule.friction(), 0.1);
+        assert_eq!(capsule.restitution(), 0.9);
+        assert_eq!(capsule.density(), Some(4.0));
+        assert_eq!(capsule.collision_groups(), InteractionGroups::new(2, 5));
+
+        let sleeping = world.spawn((Transform::default(),));
+        let schema = toml::from_str("body_type = \"dynamic\"\nsleeping = true").unwrap();
+        let rbh = build_ent_rigid_body(sleeping, &mut world, schema).unwrap();
+        assert!(world.physics().rigid_body(rbh).unwrap().is_sleeping());
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn invalid_collider_shapes_fail_to_load() {
+        use super::ent_rigid_body_loader::build_ent_rigid_body;
+
+        for collider in [
+            r#"{ shape = "triangle", points = [{ x = 0.0, y = 0.0 }, { x = 1.0, y = 0.0 }] }"#,
+            r#"{ shape = "convex_polygon", points = [{ x = 0.0, y = 0.0 }, { x = 1.0, y = 0.0 }] }"#,
+            r#"{ shape = "convex_polygon", points = [] }"#,
+            r#"{ shape = "convex_polygon", points = [{ x = 1.0, y = 1.0 }] }"#,
+            r#"{ shape = "convex_polygon", points = [{ x = 1.0, y = 1.0 }, { x = 1.0, y = 1.0 }, { x = 1.0, y = 1.0 }] }"#,
+            r#"{ shape = "heightfield", heights = [1.0] , scale = { x = 1.0, y = 1.0 } }"#,
+            r#"{ shape = "compound", shapes = [{ shape = "compound", shapes = [] }] }"#,
+            r#"{ shape = "polyline", points = [{ x = 0.0, y = 0.0 }, { x = 1.0, y = 0.0 }], indices = [[0, 2]] }"#,
+        ] {
+            let mut world = World::new();
+            let entity = world.spawn((Transform::default(),));
+            let schema = format!("body_type = \"fixed\"\ncolliders = [{}]", collider);
+
+            assert!(
+                build_ent_rigid_body(entity, &mut world, toml::from_str(&schema).unwrap()).is_err(),
+                "{}",
+                collider
+            );
+        }
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn misspelled_rigid_body_fields_fail_to_load() {
+        use super::ent_rigid_body_loader::EntRigidBodySchema;
+
+        for schema in [
+            "body_type = \"dynamic\"\ngravity = 0.5",
+            "body_type = \"fixed\"\ncolliders = [{ shape = \"ball\", radius = 1.0, frction = 0.0 }]",
+            "body_type = \"fixed\"\ncolliders = [{ shape = \"ball\", radius = 1.0, translation = { x = 1.0, z = 0.0 } }]",
+            "body_type = \"fixed\"\ncolliders = [{ shape = \"ball\", radius = 1.0, collision_groups = { memberships = 1, filters = 1 } }]",
+        ] {
+            assert!(
+                toml::from_str::<EntRigidBodySchema>(schema).is_err(),
+                "{}",
+                schema
+            );
+        }
+    }
+}
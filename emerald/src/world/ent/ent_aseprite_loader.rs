@@ -1,18 +1,23 @@
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, World};
+use crate::{Aseprite, AssetLoader, EmeraldError, World};
 
-use super::Vec2f32Schema;
+use super::{ColorSchema, Vec2f32Schema};
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct EntAsepriteSchema {
     pub aseprite: String,
+    /// When given, `aseprite` is treated as an exported sprite sheet and this as its animations json.
+    pub animations: Option<String>,
     pub offset: Option<Vec2f32Schema>,
     pub visible: Option<bool>,
     pub scale: Option<Vec2f32Schema>,
     pub default_animation: Option<AsepriteDefaultAnimationSchema>,
     pub z_index: Option<f32>,
+    pub rotation: Option<f32>,
+    pub color: Option<ColorSchema>,
+    pub centered: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -34,10 +39,15 @@ pub(crate) fn load_ent_aseprite<'a>(
     }
     let schema: EntAsepriteSchema = toml::from_str(&toml.to_string())?;
 
-    let mut aseprite = loader.aseprite(schema.aseprite)?;
+    let mut aseprite = match schema.animations {
+        Some(animations) => loader.aseprite_with_animations(schema.aseprite, animations)?,
+        None => loader.aseprite(schema.aseprite)?,
+    };
 
     aseprite.z_index = schema.z_index.unwrap_or(0.0);
     aseprite.visible = schema.visible.unwrap_or(true);
+    aseprite.rotation = schema.rotation.unwrap_or(0.0);
+    aseprite.centered = schema.centered.unwrap_or(true);
 
     if let Some(offset) = schema.offset {
         aseprite.offset.x = offset.x;
@@ -49,6 +59,10 @@ pub(crate) fn load_ent_aseprite<'a>(
         aseprite.scale.y = scale.y;
     }
 
+    if let Some(color) = schema.color {
+        aseprite.color = color.into();
+    }
+
     if let Some(default_animation_schema) = schema.default_animation {
         let looping = default_animation_schema.looping.unwrap_or(false);
         if looping {
@@ -62,3 +76,42 @@ pub(crate) fn load_ent_aseprite<'a>(
 
     Ok(())
 }
+
+pub(crate) fn save_ent_aseprite(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let aseprite = match world.get::<Aseprite>(entity) {
+        Ok(aseprite) => aseprite,
+        Err(_) => return Ok(None),
+    };
+
+    // The animation currently playing is saved as the default animation.
+    let default_animation = aseprite
+        .current_tag_index
+        .map(|_| AsepriteDefaultAnimationSchema {
+            name: aseprite.get_animation_name().to_string(),
+            looping: Some(aseprite.is_looping),
+        });
+
+    let schema = EntAsepriteSchema {
+        aseprite: aseprite.data.path.clone(),
+        animations: aseprite.data.animation_path.clone(),
+        offset: Some(Vec2f32Schema {
+            x: aseprite.offset.x,
+            y: aseprite.offset.y,
+        }),
+        visible: Some(aseprite.visible),
+        scale: Some(Vec2f32Schema {
+            x: aseprite.scale.x,
+            y: aseprite.scale.y,
+        }),
+        default_animation,
+        z_index: Some(aseprite.z_index),
+        rotation: Some(aseprite.rotation),
+        color: Some((&aseprite.color).into()),
+        centered: Some(aseprite.centered),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
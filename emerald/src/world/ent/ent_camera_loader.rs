@@ -0,0 +1,70 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{Camera, EmeraldError, World};
+
+use super::Vec2f32Schema;
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntCameraSchema {
+    pub offset: Option<Vec2f32Schema>,
+    pub centered: Option<bool>,
+    pub zoom: Option<f32>,
+
+    /// Makes this the active camera of the world once loaded.
+    pub active: Option<bool>,
+}
+
+pub(crate) fn load_ent_camera(
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load camera from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntCameraSchema = toml::from_str(&toml.to_string())?;
+    let mut camera = Camera {
+        centered: schema.centered.unwrap_or(true),
+        zoom: schema.zoom.unwrap_or(1.0),
+        ..Default::default()
+    };
+
+    if let Some(offset) = schema.offset {
+        camera.offset.x = offset.x;
+        camera.offset.y = offset.y;
+    }
+
+    world.insert_one(entity, camera)?;
+
+    if schema.active.unwrap_or(false) {
+        world.make_active_camera(entity)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn save_ent_camera(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let camera = match world.get::<Camera>(entity) {
+        Ok(camera) => *camera,
+        Err(_) => return Ok(None),
+    };
+
+    let schema = EntCameraSchema {
+        offset: Some(Vec2f32Schema {
+            x: camera.offset.x,
+            y: camera.offset.y,
+        }),
+        centered: Some(camera.centered),
+        zoom: Some(camera.zoom),
+        active: Some(camera.is_active),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
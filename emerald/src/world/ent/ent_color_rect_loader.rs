@@ -0,0 +1,72 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{ColorRect, EmeraldError, World};
+
+use super::{ColorSchema, Vec2f32Schema};
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntColorRectSchema {
+    pub color: ColorSchema,
+    pub width: u32,
+    pub height: u32,
+    pub offset: Option<Vec2f32Schema>,
+    pub visible: Option<bool>,
+    pub centered: Option<bool>,
+    pub rotation: Option<f32>,
+    pub z_index: Option<f32>,
+}
+
+pub(crate) fn load_ent_color_rect(
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load color_rect from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntColorRectSchema = toml::from_str(&toml.to_string())?;
+    let mut color_rect = ColorRect::new(schema.color.into(), schema.width, schema.height);
+    color_rect.visible = schema.visible.unwrap_or(true);
+    color_rect.centered = schema.centered.unwrap_or(true);
+    color_rect.rotation = schema.rotation.unwrap_or(0.0);
+    color_rect.z_index = schema.z_index.unwrap_or(0.0);
+
+    if let Some(offset) = schema.offset {
+        color_rect.offset.x = offset.x;
+        color_rect.offset.y = offset.y;
+    }
+
+    world.insert_one(entity, color_rect)?;
+
+    Ok(())
+}
+
+pub(crate) fn save_ent_color_rect(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let color_rect = match world.get::<ColorRect>(entity) {
+        Ok(color_rect) => *color_rect,
+        Err(_) => return Ok(None),
+    };
+
+    let schema = EntColorRectSchema {
+        color: (&color_rect.color).into(),
+        width: color_rect.width,
+        height: color_rect.height,
+        offset: Some(Vec2f32Schema {
+            x: color_rect.offset.x,
+            y: color_rect.offset.y,
+        }),
+        visible: Some(color_rect.visible),
+        centered: Some(color_rect.centered),
+        rotation: Some(color_rect.rotation),
+        z_index: Some(color_rect.z_index),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
@@ -0,0 +1,147 @@
+use hecs::{Component, Entity};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{EmeraldError, World};
+
+use super::BUILT_IN_SCHEMA_KEYS;
+
+type LoadFn = fn(&mut World, Entity, toml::Value) -> Result<(), EmeraldError>;
+type SaveFn = fn(&World, Entity) -> Result<Option<toml::Value>, EmeraldError>;
+
+/// A component type read from and written to ents under its own key.
+#[derive(Clone)]
+struct EntComponent {
+    key: String,
+    load: LoadFn,
+    save: SaveFn,
+}
+
+/// The custom components of the game's ent and world files, each stored under its own toml key.
+/// Components are registered once per game with `emd.loader().ent_components()`, are loaded by every
+/// `emd.loader().ent()` and `emd.loader().world()`, and are saved when the registry is given to
+/// [`EntSaveConfig`](super::EntSaveConfig).
+///
+/// Keys that are neither built in nor registered fail to load,
+/// unless a `custom_component_loader` is given to handle them.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct Health {
+///     max_hp: i64,
+/// }
+///
+/// emd.loader().ent_components().register::<Health>("health")?;
+/// let entity = emd.loader().ent(&mut world, EntLoadConfig::default(), "player.ent")?;
+///
+/// let config = EntSaveConfig {
+///     components: Some(emd.ent_components()),
+///     ..Default::default()
+/// };
+/// let toml = world.save_ent(entity, config)?;
+/// ```
+#[derive(Clone, Default)]
+pub struct EntComponentRegistry {
+    components: Vec<EntComponent>,
+}
+impl EntComponentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads and saves `T` under the key, replacing the component previously registered under it.
+    /// Keys of the built-in components can't be taken.
+    pub fn register<T: Component + Serialize + DeserializeOwned>(
+        &mut self,
+        key: &str,
+    ) -> Result<(), EmeraldError> {
+        if BUILT_IN_SCHEMA_KEYS.contains(&key) {
+            return Err(EmeraldError::new(format!(
+                "Unable to register an ent component under {:?}, the key is taken by a built-in component.",
+                key
+            )));
+        }
+
+        let component = EntComponent {
+            key: key.to_string(),
+            load: load_component::<T>,
+            save: save_component::<T>,
+        };
+
+        match self.components.iter_mut().find(|c| c.key == key) {
+            Some(existing) => *existing = component,
+            None => self.components.push(component),
+        }
+
+        Ok(())
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.components.iter().any(|c| c.key == key)
+    }
+
+    /// Keys of the registered components, in the order they were registered.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.components.iter().map(|c| c.key.as_str())
+    }
+
+    /// Inserts the component registered under the key into the entity,
+    /// returns the value back if nothing is registered under it.
+    pub(crate) fn load(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        key: &str,
+        value: toml::Value,
+    ) -> Result<Option<toml::Value>, EmeraldError> {
+        match self.components.iter().find(|c| c.key == key) {
+            Some(component) => {
+                (component.load)(world, entity, value).map_err(|e| {
+                    EmeraldError::new(format!(
+                        "Unable to load ent component {:?}: {}",
+                        key, e.message
+                    ))
+                })?;
+
+                Ok(None)
+            }
+            None => Ok(Some(value)),
+        }
+    }
+
+    /// Writes every registered component the entity has into the table.
+    pub(crate) fn save(
+        &self,
+        world: &World,
+        entity: Entity,
+        table: &mut toml::value::Table,
+    ) -> Result<(), EmeraldError> {
+        for component in &self.components {
+            if let Some(value) = (component.save)(world, entity)? {
+                table.insert(component.key.clone(), value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn load_component<T: Component + DeserializeOwned>(
+    world: &mut World,
+    entity: Entity,
+    value: toml::Value,
+) -> Result<(), EmeraldError> {
+    let component = value.try_into::<T>()?;
+    world.insert_one(entity, component)?;
+
+    Ok(())
+}
+
+fn save_component<T: Component + Serialize>(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    match world.get::<T>(entity) {
+        Ok(component) => Ok(Some(toml::Value::try_from(&*component)?)),
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,169 @@
+use fontdue::layout::{HorizontalAlign, VerticalAlign, WrapStyle};
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{AssetLoader, EmeraldError, Label, World};
+
+use super::{ColorSchema, Vec2f32Schema};
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntFontSchema {
+    pub path: String,
+    pub size: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntLabelSchema {
+    pub text: String,
+    pub font: EntFontSchema,
+    pub font_size: u16,
+    pub offset: Option<Vec2f32Schema>,
+    pub scale: Option<f32>,
+    pub z_index: Option<f32>,
+    pub color: Option<ColorSchema>,
+    pub centered: Option<bool>,
+    pub visible: Option<bool>,
+    pub visible_characters: Option<i64>,
+    pub horizontal_align: Option<String>,
+    pub vertical_align: Option<String>,
+    pub wrap_style: Option<String>,
+    pub max_width: Option<f32>,
+    pub max_height: Option<f32>,
+}
+
+pub(crate) fn load_ent_label<'a>(
+    loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load label from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntLabelSchema = toml::from_str(&toml.to_string())?;
+    let font_key = loader.font(schema.font.path, schema.font.size)?;
+    let mut label = Label::new(schema.text, font_key, schema.font_size);
+
+    if let Some(offset) = schema.offset {
+        label.offset.x = offset.x;
+        label.offset.y = offset.y;
+    }
+    if let Some(scale) = schema.scale {
+        label.scale = scale;
+    }
+    if let Some(z_index) = schema.z_index {
+        label.z_index = z_index;
+    }
+    if let Some(color) = schema.color {
+        label.color = color.into();
+    }
+    if let Some(centered) = schema.centered {
+        label.centered = centered;
+    }
+    if let Some(visible) = schema.visible {
+        label.visible = visible;
+    }
+    if let Some(visible_characters) = schema.visible_characters {
+        label.visible_characters = visible_characters;
+    }
+    if let Some(horizontal_align) = schema.horizontal_align {
+        label.horizontal_align = match horizontal_align.as_str() {
+            "left" => HorizontalAlign::Left,
+            "center" => HorizontalAlign::Center,
+            "right" => HorizontalAlign::Right,
+            _ => {
+                return Err(EmeraldError::new(format!(
+                    "{:?} does not match a valid horizontal alignment.",
+                    horizontal_align
+                )))
+            }
+        };
+    }
+    if let Some(vertical_align) = schema.vertical_align {
+        label.vertical_align = match vertical_align.as_str() {
+            "top" => VerticalAlign::Top,
+            "middle" => VerticalAlign::Middle,
+            "bottom" => VerticalAlign::Bottom,
+            _ => {
+                return Err(EmeraldError::new(format!(
+                    "{:?} does not match a valid vertical alignment.",
+                    vertical_align
+                )))
+            }
+        };
+    }
+    if let Some(wrap_style) = schema.wrap_style {
+        label.wrap_style = match wrap_style.as_str() {
+            "word" => WrapStyle::Word,
+            "letter" => WrapStyle::Letter,
+            _ => {
+                return Err(EmeraldError::new(format!(
+                    "{:?} does not match a valid wrap style.",
+                    wrap_style
+                )))
+            }
+        };
+    }
+
+    // Unlike the other optional fields, a missing max size means there is no limit.
+    label.max_width = schema.max_width;
+    label.max_height = schema.max_height;
+
+    world.insert_one(entity, label)?;
+
+    Ok(())
+}
+
+pub(crate) fn save_ent_label(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let label = match world.get::<Label>(entity) {
+        Ok(label) => label,
+        Err(_) => return Ok(None),
+    };
+
+    let horizontal_align = match label.horizontal_align {
+        HorizontalAlign::Left => "left",
+        HorizontalAlign::Center => "center",
+        HorizontalAlign::Right => "right",
+    };
+    let vertical_align = match label.vertical_align {
+        VerticalAlign::Top => "top",
+        VerticalAlign::Middle => "middle",
+        VerticalAlign::Bottom => "bottom",
+    };
+    let wrap_style = match label.wrap_style {
+        WrapStyle::Word => "word",
+        WrapStyle::Letter => "letter",
+    };
+
+    let schema = EntLabelSchema {
+        text: label.text.clone(),
+        font: EntFontSchema {
+            path: label.font_key.0.clone(),
+            size: label.font_key.1,
+        },
+        font_size: label.font_size,
+        offset: Some(Vec2f32Schema {
+            x: label.offset.x,
+            y: label.offset.y,
+        }),
+        scale: Some(label.scale),
+        z_index: Some(label.z_index),
+        color: Some((&label.color).into()),
+        centered: Some(label.centered),
+        visible: Some(label.visible),
+        visible_characters: Some(label.visible_characters),
+        horizontal_align: Some(horizontal_align.to_string()),
+        vertical_align: Some(vertical_align.to_string()),
+        wrap_style: Some(wrap_style.to_string()),
+        max_width: label.max_width,
+        max_height: label.max_height,
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
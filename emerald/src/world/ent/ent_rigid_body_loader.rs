@@ -1,43 +1,84 @@
 use hecs::Entity;
-use nalgebra::Vector2;
-use rapier2d::{
-    parry::shape::Cuboid,
-    prelude::{ColliderBuilder, ColliderHandle, RigidBodyBuilder, RigidBodyHandle, RigidBodyType},
+use nalgebra::{DVector, Isometry2, Point2, Vector2};
+use rapier2d::prelude::{
+    Collider, ColliderBuilder, ColliderHandle, InteractionGroups, RigidBodyBuilder,
+    RigidBodyHandle, RigidBodyType, Shape, SharedShape, TypedShape,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, World};
+use crate::{AssetLoader, CollisionLayers, EmeraldError, TilemapColliders, World};
 
-use super::Vec2f32Schema;
+use super::{InteractionGroupsSchema, Vec2f32Schema};
 
-#[derive(Deserialize, Serialize)]
+/// Geometry fields used by each shape:
+/// - `cuboid`: `half_width` and `half_height`.
+/// - `ball`: `radius`.
+/// - `capsule`: `half_height` and `radius`, standing along the y axis.
+/// - `convex_polygon`: the convex hull of the `points`.
+/// - `polyline`: the `points`, joined in order unless `indices` lists the segments.
+/// - `triangle`: three `points`.
+/// - `heightfield`: the `heights` of evenly spaced points from left to right,
+///   spread over a width of `scale.x` around the collider and multiplied by `scale.y`.
+/// - `compound`: the `shapes`, each placed by its own `translation` and `rotation`.
+///   Compounds can't hold polylines or other compounds.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct EntColliderSchema {
     pub shape: String,
     pub translation: Option<Vec2f32Schema>,
+    /// Radians.
+    pub rotation: Option<f32>,
     pub half_width: Option<f32>,
     pub half_height: Option<f32>,
     pub radius: Option<f32>,
+    pub points: Option<Vec<Vec2f32Schema>>,
+    pub indices: Option<Vec<[u32; 2]>>,
+    pub heights: Option<Vec<f32>>,
+    pub scale: Option<Vec2f32Schema>,
+    pub shapes: Option<Vec<EntColliderSchema>>,
     pub sensor: Option<bool>,
+    pub friction: Option<f32>,
+    pub restitution: Option<f32>,
+    pub density: Option<f32>,
+    /// Name of one of the world's collision layers.
+    pub layer: Option<String>,
+    /// Bit masks used instead of a `layer`.
+    pub collision_groups: Option<InteractionGroupsSchema>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct EntRigidBodySchema {
     pub body_type: String,
+    pub linear_velocity: Option<Vec2f32Schema>,
+    pub angular_velocity: Option<f32>,
+    pub linear_damping: Option<f32>,
+    pub angular_damping: Option<f32>,
+    pub gravity_scale: Option<f32>,
+    pub ccd_enabled: Option<bool>,
+    pub lock_rotations: Option<bool>,
+    pub sleeping: Option<bool>,
     pub colliders: Option<Vec<EntColliderSchema>>,
 }
 
-fn load_ent_collider(
-    rbh: RigidBodyHandle,
-    world: &mut World,
-    collider_schema: EntColliderSchema,
-) -> Result<ColliderHandle, EmeraldError> {
-    // Load collider attributes
-    let mut builder = match collider_schema.shape.as_str() {
+fn load_ent_shape(schema: &EntColliderSchema) -> Result<SharedShape, EmeraldError> {
+    let points = || -> Result<Vec<Point2<f32>>, EmeraldError> {
+        match &schema.points {
+            Some(points) => Ok(points
+                .iter()
+                .map(|point| Point2::new(point.x, point.y))
+                .collect()),
+            None => Err(EmeraldError::new(format!(
+                "{:?} colliders require points.",
+                schema.shape
+            ))),
+        }
+    };
+
+    let shape = match schema.shape.as_str() {
         "cuboid" => {
-            if let (Some(half_width), Some(half_height)) =
-                (collider_schema.half_width, collider_schema.half_height)
-            {
-                ColliderBuilder::cuboid(half_width, half_height)
+            if let (Some(half_width), Some(half_height)) = (schema.half_width, schema.half_height) {
+                SharedShape::cuboid(half_width, half_height)
             } else {
                 return Err(EmeraldError::new(
                     "Cuboid colliders expect both a half_width and half_height.",
@@ -45,12 +86,99 @@ fn load_ent_collider(
             }
         }
         "ball" => {
-            if let Some(radius) = collider_schema.radius {
-                ColliderBuilder::ball(radius)
+            if let Some(radius) = schema.radius {
+                SharedShape::ball(radius)
             } else {
                 return Err(EmeraldError::new("Ball colliders require a radius"));
             }
         }
+        "capsule" => {
+            if let (Some(half_height), Some(radius)) = (schema.half_height, schema.radius) {
+                SharedShape::capsule_y(half_height, radius)
+            } else {
+                return Err(EmeraldError::new(
+                    "Capsule colliders expect both a half_height and radius.",
+                ));
+            }
+        }
+        "convex_polygon" => {
+            // The hull of fewer than three distinct points panics instead of failing.
+            let points = points()?;
+            if points.len() < 3 || points.iter().all(|point| *point == points[0]) {
+                return Err(EmeraldError::new(
+                    "Convex polygon colliders require at least three distinct points.",
+                ));
+            }
+
+            match SharedShape::convex_hull(&points) {
+                Some(polygon) if polygon.as_convex_polygon().unwrap().points().len() >= 3 => {
+                    polygon
+                }
+                _ => {
+                    return Err(EmeraldError::new(
+                        "The points of a convex_polygon collider must not all be aligned.",
+                    ))
+                }
+            }
+        }
+        "polyline" => {
+            let points = points()?;
+            if points.len() < 2 {
+                return Err(EmeraldError::new(
+                    "Polyline colliders require at least two points.",
+                ));
+            }
+            if let Some(indices) = &schema.indices {
+                if indices
+                    .iter()
+                    .flatten()
+                    .any(|i| *i as usize >= points.len())
+                {
+                    return Err(EmeraldError::new(
+                        "The indices of a polyline collider must refer to its points.",
+                    ));
+                }
+            }
+            SharedShape::polyline(points, schema.indices.clone())
+        }
+        "triangle" => match points()?.as_slice() {
+            [a, b, c] => SharedShape::triangle(*a, *b, *c),
+            _ => {
+                return Err(EmeraldError::new(
+                    "Triangle colliders require exactly three points.",
+                ))
+            }
+        },
+        "heightfield" => match (&schema.heights, &schema.scale) {
+            (Some(heights), Some(scale)) if heights.len() >= 2 => SharedShape::heightfield(
+                DVector::from_vec(heights.clone()),
+                Vector2::new(scale.x, scale.y),
+            ),
+            _ => {
+                return Err(EmeraldError::new(
+                    "Heightfield colliders expect a scale and at least two heights.",
+                ))
+            }
+        },
+        "compound" => {
+            let mut shapes = Vec::new();
+            for child in schema.shapes.iter().flatten() {
+                let shape = load_ent_shape(child)?;
+                if shape.as_composite_shape().is_some() {
+                    return Err(EmeraldError::new(
+                        "Compound colliders can't hold polylines or other compounds.",
+                    ));
+                }
+                shapes.push((shape_position(child), shape));
+            }
+
+            if shapes.is_empty() {
+                return Err(EmeraldError::new(
+                    "Compound colliders require at least one shape.",
+                ));
+            }
+            SharedShape::compound(shapes)
+        }
         _ => {
             return Err(EmeraldError::new(
                 "Collider shape does not match an expected shape.",
@@ -58,14 +186,60 @@ fn load_ent_collider(
         }
     };
 
-    if let Some(translation_value) = collider_schema.translation {
-        builder = builder.translation(Vector2::new(translation_value.x, translation_value.y));
-    }
+    Ok(shape)
+}
+
+fn shape_position(schema: &EntColliderSchema) -> Isometry2<f32> {
+    let translation = schema
+        .translation
+        .as_ref()
+        .map(|translation| Vector2::new(translation.x, translation.y))
+        .unwrap_or_else(Vector2::zeros);
+
+    Isometry2::new(translation, schema.rotation.unwrap_or(0.0))
+}
+
+pub(crate) fn load_ent_collider(
+    rbh: RigidBodyHandle,
+    world: &mut World,
+    collider_schema: EntColliderSchema,
+) -> Result<ColliderHandle, EmeraldError> {
+    // Load collider attributes
+    let mut builder = ColliderBuilder::new(load_ent_shape(&collider_schema)?)
+        .position(shape_position(&collider_schema));
 
     if let Some(sensor) = collider_schema.sensor {
         builder = builder.sensor(sensor);
     }
 
+    if let Some(friction) = collider_schema.friction {
+        builder = builder.friction(friction);
+    }
+
+    if let Some(restitution) = collider_schema.restitution {
+        builder = builder.restitution(restitution);
+    }
+
+    if let Some(density) = collider_schema.density {
+        builder = builder.density(density);
+    }
+
+    match (collider_schema.layer, collider_schema.collision_groups) {
+        (Some(_), Some(_)) => {
+            return Err(EmeraldError::new(
+                "A collider can't have both a layer and collision_groups.",
+            ))
+        }
+        (Some(layer), None) => {
+            let groups = world.physics().collision_layers().groups(&layer)?;
+            builder = builder.collision_groups(groups);
+        }
+        (None, Some(groups)) => builder = builder.collision_groups(groups.into()),
+        (None, None) => {
+            builder = builder.collision_groups(world.physics().settings().collision_groups)
+        }
+    }
+
     Ok(world.physics().build_collider(rbh, builder))
 }
 
@@ -82,6 +256,14 @@ pub(crate) fn load_ent_rigid_body<'a>(
     }
     let schema: EntRigidBodySchema = toml::from_str(&toml.to_string())?;
 
+    build_ent_rigid_body(entity, world, schema)
+}
+
+pub(crate) fn build_ent_rigid_body(
+    entity: Entity,
+    world: &mut World,
+    schema: EntRigidBodySchema,
+) -> Result<RigidBodyHandle, EmeraldError> {
     let mut body_type = RigidBodyType::Dynamic;
     match schema.body_type.as_str() {
         "dynamic" => {}
@@ -96,7 +278,40 @@ pub(crate) fn load_ent_rigid_body<'a>(
         }
     }
 
-    let rigid_body_builder = RigidBodyBuilder::new(body_type);
+    let mut rigid_body_builder = RigidBodyBuilder::new(body_type);
+
+    if let Some(linear_velocity) = schema.linear_velocity {
+        rigid_body_builder =
+            rigid_body_builder.linvel(Vector2::new(linear_velocity.x, linear_velocity.y));
+    }
+
+    if let Some(angular_velocity) = schema.angular_velocity {
+        rigid_body_builder = rigid_body_builder.angvel(angular_velocity);
+    }
+
+    if let Some(linear_damping) = schema.linear_damping {
+        rigid_body_builder = rigid_body_builder.linear_damping(linear_damping);
+    }
+
+    if let Some(angular_damping) = schema.angular_damping {
+        rigid_body_builder = rigid_body_builder.angular_damping(angular_damping);
+    }
+
+    if let Some(gravity_scale) = schema.gravity_scale {
+        rigid_body_builder = rigid_body_builder.gravity_scale(gravity_scale);
+    }
+
+    if let Some(ccd_enabled) = schema.ccd_enabled {
+        rigid_body_builder = rigid_body_builder.ccd_enabled(ccd_enabled);
+    }
+
+    if schema.lock_rotations == Some(true) {
+        rigid_body_builder = rigid_body_builder.lock_rotations();
+    }
+
+    if let Some(sleeping) = schema.sleeping {
+        rigid_body_builder = rigid_body_builder.sleeping(sleeping);
+    }
 
     let rbh = world.physics().build_body(entity, rigid_body_builder)?;
     if let Some(collider_schemas) = schema.colliders {
@@ -107,3 +322,194 @@ pub(crate) fn load_ent_rigid_body<'a>(
 
     Ok(rbh)
 }
+
+/// Writes the geometry fields of the shape into the schema.
+fn save_ent_shape(shape: &dyn Shape, schema: &mut EntColliderSchema) -> Result<(), EmeraldError> {
+    let points = |points: &[Point2<f32>]| {
+        Some(
+            points
+                .iter()
+                .map(|point| Vec2f32Schema {
+                    x: point.x,
+                    y: point.y,
+                })
+                .collect(),
+        )
+    };
+
+    match shape.as_typed_shape() {
+        TypedShape::Cuboid(cuboid) => {
+            schema.shape = String::from("cuboid");
+            schema.half_width = Some(cuboid.half_extents.x);
+            schema.half_height = Some(cuboid.half_extents.y);
+        }
+        TypedShape::Ball(ball) => {
+            schema.shape = String::from("ball");
+            schema.radius = Some(ball.radius);
+        }
+        TypedShape::Capsule(capsule)
+            if capsule.segment.a.x == 0.0
+                && capsule.segment.b.x == 0.0
+                && capsule.segment.a.y == -capsule.segment.b.y =>
+        {
+            schema.shape = String::from("capsule");
+            schema.half_height = Some(capsule.half_height());
+            schema.radius = Some(capsule.radius);
+        }
+        TypedShape::ConvexPolygon(polygon) => {
+            schema.shape = String::from("convex_polygon");
+            schema.points = points(polygon.points());
+        }
+        TypedShape::Polyline(polyline) => {
+            schema.shape = String::from("polyline");
+            schema.points = points(polyline.vertices());
+
+            let chained = polyline
+                .indices()
+                .iter()
+                .enumerate()
+                .all(|(i, [a, b])| *a as usize == i && *b as usize == i + 1);
+            if !chained {
+                schema.indices = Some(polyline.indices().to_vec());
+            }
+        }
+        TypedShape::Triangle(triangle) => {
+            schema.shape = String::from("triangle");
+            schema.points = points(&[triangle.a, triangle.b, triangle.c]);
+        }
+        TypedShape::HeightField(heightfield) => {
+            schema.shape = String::from("heightfield");
+            schema.heights = Some(heightfield.heights().iter().copied().collect());
+            schema.scale = Some(Vec2f32Schema {
+                x: heightfield.scale().x,
+                y: heightfield.scale().y,
+            });
+        }
+        TypedShape::Compound(compound) => {
+            let mut shapes = Vec::new();
+            for (position, shape) in compound.shapes() {
+                let mut child = EntColliderSchema {
+                    translation: Some(Vec2f32Schema {
+                        x: position.translation.vector.x,
+                        y: position.translation.vector.y,
+                    }),
+                    rotation: Some(position.rotation.angle()),
+                    ..Default::default()
+                };
+                save_ent_shape(&**shape, &mut child)?;
+                shapes.push(child);
+            }
+
+            schema.shape = String::from("compound");
+            schema.shapes = Some(shapes);
+        }
+        _ => {
+            return Err(EmeraldError::new(format!(
+                "Unable to save collider of shape {:?}, it can't be described in an ent file.",
+                shape.shape_type()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn save_ent_collider(
+    collider: &Collider,
+    collision_layers: &CollisionLayers,
+    default_groups: InteractionGroups,
+) -> Result<EntColliderSchema, EmeraldError> {
+    let position = collider
+        .position_wrt_parent()
+        .copied()
+        .unwrap_or_else(Isometry2::identity);
+    let layer = collision_layers
+        .layer_of(collider.collision_groups())
+        .map(String::from);
+    // Colliders loaded without groups get the default ones of the world.
+    let collision_groups = match layer {
+        None if collider.collision_groups() != default_groups => {
+            Some((&collider.collision_groups()).into())
+        }
+        _ => None,
+    };
+
+    let mut schema = EntColliderSchema {
+        translation: Some(Vec2f32Schema {
+            x: position.translation.vector.x,
+            y: position.translation.vector.y,
+        }),
+        rotation: Some(position.rotation.angle()),
+        sensor: Some(collider.is_sensor()),
+        friction: Some(collider.friction()),
+        restitution: Some(collider.restitution()),
+        density: collider.density(),
+        layer,
+        collision_groups,
+        ..Default::default()
+    };
+    save_ent_shape(collider.shape(), &mut schema)?;
+
+    Ok(schema)
+}
+
+pub(crate) fn save_ent_rigid_body(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let rbh = match world.get::<RigidBodyHandle>(entity) {
+        Ok(rbh) => *rbh,
+        Err(_) => return Ok(None),
+    };
+
+    let body = match world.physics_engine.bodies.get(rbh) {
+        Some(body) => body,
+        None => return Ok(None),
+    };
+
+    let body_type = match body.body_type() {
+        RigidBodyType::Dynamic => "dynamic",
+        RigidBodyType::Fixed => "fixed",
+        RigidBodyType::KinematicVelocityBased => "kinematic_velocity_based",
+        RigidBodyType::KinematicPositionBased => "kinematic_position_based",
+    };
+
+    // The colliders of a tilemap are rebuilt from its tiles, so they aren't saved as plain colliders.
+    let tilemap_colliders = world.get::<TilemapColliders>(entity).ok();
+    let mut colliders = Vec::new();
+    for collider_handle in world.physics_engine.get_colliders(entity) {
+        if tilemap_colliders
+            .as_ref()
+            .map(|tilemap_colliders| tilemap_colliders.contains_collider(collider_handle))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        if let Some(collider) = world.physics_engine.colliders.get(collider_handle) {
+            colliders.push(save_ent_collider(
+                collider,
+                &world.physics_engine.collision_layers,
+                world.physics_engine.settings.collision_groups,
+            )?);
+        }
+    }
+
+    let schema = EntRigidBodySchema {
+        body_type: body_type.to_string(),
+        linear_velocity: Some(Vec2f32Schema {
+            x: body.linvel().x,
+            y: body.linvel().y,
+        }),
+        angular_velocity: Some(body.angvel()),
+        linear_damping: Some(body.linear_damping()),
+        angular_damping: Some(body.angular_damping()),
+        gravity_scale: Some(body.gravity_scale()),
+        ccd_enabled: Some(body.is_ccd_enabled()),
+        lock_rotations: Some(body.is_rotation_locked()),
+        sleeping: Some(body.is_sleeping()),
+        colliders: Some(colliders),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
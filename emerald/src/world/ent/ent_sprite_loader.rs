@@ -1,9 +1,9 @@
 use hecs::Entity;
 use serde::{Deserialize, Serialize};
 
-use crate::{AssetLoader, EmeraldError, World};
+use crate::{AssetLoader, EmeraldError, Sprite, World};
 
-use super::Vec2f32Schema;
+use super::{ColorSchema, RectangleSchema, Vec2f32Schema};
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct EntSpriteSchema {
@@ -12,6 +12,10 @@ pub(crate) struct EntSpriteSchema {
     pub visible: Option<bool>,
     pub scale: Option<Vec2f32Schema>,
     pub z_index: Option<f32>,
+    pub target: Option<RectangleSchema>,
+    pub rotation: Option<f32>,
+    pub color: Option<ColorSchema>,
+    pub centered: Option<bool>,
 }
 
 pub(crate) fn load_ent_sprite<'a>(
@@ -30,6 +34,8 @@ pub(crate) fn load_ent_sprite<'a>(
     let mut sprite = loader.sprite(schema.texture)?;
     sprite.z_index = schema.z_index.unwrap_or(0.0);
     sprite.visible = schema.visible.unwrap_or(true);
+    sprite.rotation = schema.rotation.unwrap_or(0.0);
+    sprite.centered = schema.centered.unwrap_or(true);
 
     if let Some(offset) = schema.offset {
         sprite.offset.x = offset.x;
@@ -39,8 +45,44 @@ pub(crate) fn load_ent_sprite<'a>(
         sprite.scale.x = scale.x;
         sprite.scale.y = scale.y;
     }
+    if let Some(target) = schema.target {
+        sprite.target = target.into();
+    }
+    if let Some(color) = schema.color {
+        sprite.color = color.into();
+    }
 
     world.insert_one(entity, sprite)?;
 
     Ok(())
 }
+
+pub(crate) fn save_ent_sprite(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let sprite = match world.get::<Sprite>(entity) {
+        Ok(sprite) => sprite,
+        Err(_) => return Ok(None),
+    };
+
+    let schema = EntSpriteSchema {
+        texture: sprite.texture_key.get_name(),
+        offset: Some(Vec2f32Schema {
+            x: sprite.offset.x,
+            y: sprite.offset.y,
+        }),
+        visible: Some(sprite.visible),
+        scale: Some(Vec2f32Schema {
+            x: sprite.scale.x,
+            y: sprite.scale.y,
+        }),
+        z_index: Some(sprite.z_index),
+        target: Some((&sprite.target).into()),
+        rotation: Some(sprite.rotation),
+        color: Some((&sprite.color).into()),
+        centered: Some(sprite.centered),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
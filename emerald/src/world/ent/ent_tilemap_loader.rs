@@ -0,0 +1,95 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::tilemap::Tilemap;
+use crate::{AssetLoader, EmeraldError, Vector2, World};
+
+use super::Vec2usizeSchema;
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntTilemapSchema {
+    pub tilesheet: String,
+    pub tile_size: Vec2usizeSchema,
+    pub width: usize,
+    pub height: usize,
+
+    /// Row-major tile ids, a negative id marks an empty tile.
+    pub tiles: Option<Vec<i64>>,
+    pub z_index: Option<f32>,
+    pub visible: Option<bool>,
+}
+
+pub(crate) fn load_ent_tilemap<'a>(
+    loader: &mut AssetLoader<'a>,
+    entity: Entity,
+    world: &mut World,
+    toml: &toml::Value,
+) -> Result<(), EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load tilemap from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntTilemapSchema = toml::from_str(&toml.to_string())?;
+    let tilesheet = loader.texture(schema.tilesheet)?;
+    let mut tilemap = Tilemap::new(
+        tilesheet,
+        Vector2::new(schema.tile_size.x, schema.tile_size.y),
+        schema.width,
+        schema.height,
+    );
+    tilemap.z_index = schema.z_index.unwrap_or(0.0);
+    tilemap.visible = schema.visible.unwrap_or(true);
+
+    if let Some(tiles) = schema.tiles {
+        if tiles.len() != tilemap.tiles.len() {
+            return Err(EmeraldError::new(format!(
+                "Tilemap of size {}x{} expects {} tiles, found {}.",
+                schema.width,
+                schema.height,
+                tilemap.tiles.len(),
+                tiles.len()
+            )));
+        }
+
+        for (tile, id) in tilemap.tiles.iter_mut().zip(tiles) {
+            *tile = if id < 0 { None } else { Some(id as usize) };
+        }
+    }
+
+    world.insert_one(entity, tilemap)?;
+
+    Ok(())
+}
+
+pub(crate) fn save_ent_tilemap(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let tilemap = match world.get::<Tilemap>(entity) {
+        Ok(tilemap) => tilemap,
+        Err(_) => return Ok(None),
+    };
+
+    let tiles = tilemap
+        .tiles
+        .iter()
+        .map(|tile| tile.map(|id| id as i64).unwrap_or(-1))
+        .collect();
+
+    let schema = EntTilemapSchema {
+        tilesheet: tilemap.tilesheet.get_name(),
+        tile_size: Vec2usizeSchema {
+            x: tilemap.tile_size.x,
+            y: tilemap.tile_size.y,
+        },
+        width: tilemap.width,
+        height: tilemap.height,
+        tiles: Some(tiles),
+        z_index: Some(tilemap.z_index),
+        visible: Some(tilemap.visible),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
@@ -0,0 +1,60 @@
+use hecs::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{EmeraldError, Scale, Transform, Translation, World};
+
+use super::Vec2f32Schema;
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct EntTransformSchema {
+    pub translation: Option<Vec2f32Schema>,
+    pub rotation: Option<f32>,
+    pub scale: Option<Vec2f32Schema>,
+}
+
+pub(crate) fn load_ent_transform(toml: &toml::Value) -> Result<Transform, EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load transform from a non-table toml value.",
+        ));
+    }
+
+    let schema: EntTransformSchema = toml::from_str(&toml.to_string())?;
+    let mut transform = Transform {
+        rotation: schema.rotation.unwrap_or(0.0),
+        ..Default::default()
+    };
+
+    if let Some(translation) = schema.translation {
+        transform.translation = Translation::new(translation.x, translation.y);
+    }
+    if let Some(scale) = schema.scale {
+        transform.scale = Scale::new(scale.x, scale.y);
+    }
+
+    Ok(transform)
+}
+
+pub(crate) fn save_ent_transform(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<toml::Value>, EmeraldError> {
+    let transform = match world.get::<Transform>(entity) {
+        Ok(transform) => *transform,
+        Err(_) => return Ok(None),
+    };
+
+    let schema = EntTransformSchema {
+        translation: Some(Vec2f32Schema {
+            x: transform.translation.x,
+            y: transform.translation.y,
+        }),
+        rotation: Some(transform.rotation),
+        scale: Some(Vec2f32Schema {
+            x: transform.scale.x,
+            y: transform.scale.y,
+        }),
+    };
+
+    Ok(Some(toml::Value::try_from(schema)?))
+}
@@ -1,11 +1,26 @@
+mod character;
 mod components;
 mod engine;
+mod events;
 mod handler;
 mod handler_ref;
+mod hooks;
+mod joints;
+mod layers;
+mod settings;
+mod snapshot;
+mod tilemap;
 mod types;
 
 pub use components::*;
 pub use engine::*;
+pub use events::*;
 pub use handler::*;
 pub use handler_ref::*;
+pub use hooks::*;
+pub use joints::*;
+pub use layers::*;
+pub use settings::*;
+pub(crate) use snapshot::*;
+pub use tilemap::*;
 pub use types::*;
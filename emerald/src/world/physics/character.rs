@@ -0,0 +1,246 @@
+use crate::core::components::transform::Transform;
+use crate::parent_global_transform;
+use crate::physics::*;
+
+use hecs::Entity;
+use rapier2d::parry::query::TOIStatus;
+use rapier2d::prelude::*;
+use std::collections::HashMap;
+
+/// Most times a character slides along a surface during a single move.
+const MAX_SLIDES: usize = 4;
+
+/// Moves shorter than this are skipped.
+const MIN_MOVE: f32 = 1.0e-4;
+
+/// Most one-way platforms a single cast can go through.
+const MAX_IGNORED_PLATFORMS: usize = 8;
+
+impl PhysicsEngine {
+    /// Moves every entity that has a `CharacterController` and a kinematic body
+    /// by its velocity for a step of `delta` seconds.
+    pub(crate) fn move_characters(&mut self, world: &mut hecs::World, delta: f32) {
+        let characters = world
+            .query::<(&CharacterController, &RigidBodyHandle)>()
+            .iter()
+            .map(|(entity, (_, body_handle))| (entity, *body_handle))
+            .collect::<Vec<(Entity, RigidBodyHandle)>>();
+
+        if characters.is_empty() {
+            return;
+        }
+
+        let one_way_platforms = self.hooked_surfaces.one_way_platforms.clone();
+
+        // Characters moved during the previous steps must be seen at their new positions.
+        self.update_query_pipeline();
+
+        for (entity, body_handle) in characters {
+            let parent_transform = parent_global_transform(world, entity);
+            if let Ok((controller, transform)) =
+                world.query_one_mut::<(&mut CharacterController, &mut Transform)>(entity)
+            {
+                // Characters move in world space, their transforms may be relative to a parent.
+                let mut global_transform = match &parent_transform {
+                    Some(parent_transform) => parent_transform.mul_transform(transform),
+                    None => *transform,
+                };
+                self.move_character(
+                    controller,
+                    &mut global_transform,
+                    body_handle,
+                    &one_way_platforms,
+                    delta,
+                );
+                transform.translation = match &parent_transform {
+                    Some(parent_transform) => {
+                        parent_transform
+                            .inverse_mul_transform(&global_transform)
+                            .translation
+                    }
+                    None => global_transform.translation,
+                };
+            }
+        }
+    }
+
+    fn move_character(
+        &mut self,
+        controller: &mut CharacterController,
+        transform: &mut Transform,
+        body_handle: RigidBodyHandle,
+        one_way_platforms: &HashMap<ColliderHandle, Vector<f32>>,
+        delta: f32,
+    ) {
+        let rotation = match self.bodies.get(body_handle) {
+            Some(body) if body.is_kinematic() => body.position().rotation,
+            _ => return,
+        };
+
+        // The first solid collider of the body is the one moved around.
+        let own_colliders = self
+            .body_colliders
+            .get(&body_handle)
+            .cloned()
+            .unwrap_or_default();
+        let (shape, shape_offset, groups) = match own_colliders
+            .iter()
+            .filter_map(|handle| self.colliders.get(*handle))
+            .find(|collider| !collider.is_sensor())
+        {
+            Some(collider) => (
+                collider.shared_shape().clone(),
+                collider
+                    .position_wrt_parent()
+                    .copied()
+                    .unwrap_or_else(Isometry::identity),
+                collider.collision_groups(),
+            ),
+            None => return,
+        };
+
+        let up = controller.up.try_normalize(f32::EPSILON);
+        let floor_cos = controller.max_slope_angle.cos();
+        let was_grounded = controller.is_grounded();
+
+        let mut translation = Vector::new(transform.translation.x, transform.translation.y);
+        let mut velocity = controller.velocity;
+        let mut remaining = velocity * delta;
+        let mut ground_normal = None;
+        let mut on_ceiling = false;
+        let mut on_wall = false;
+
+        for _ in 0..MAX_SLIDES {
+            let distance = remaining.norm();
+            if distance < MIN_MOVE {
+                break;
+            }
+
+            let position = Isometry::from_parts(translation.into(), rotation) * shape_offset;
+            let (toi, normal) = match self.cast_character(
+                &*shape,
+                &position,
+                &remaining,
+                groups,
+                &own_colliders,
+                one_way_platforms,
+            ) {
+                Some(hit) => hit,
+                None => {
+                    translation += remaining;
+                    break;
+                }
+            };
+
+            let direction = remaining / distance;
+            let travel = (toi * distance - controller.offset).max(0.0);
+            translation += direction * travel;
+            remaining -= direction * travel;
+
+            let normal_up = up.map(|up| normal.dot(&up)).unwrap_or(0.0);
+            if up.is_some() && normal_up >= floor_cos {
+                ground_normal = Some(normal);
+            } else if up.is_some() && normal_up <= -floor_cos {
+                on_ceiling = true;
+            } else {
+                on_wall = true;
+            }
+
+            // Slide along the surface.
+            remaining -= normal * remaining.dot(&normal).min(0.0);
+            velocity -= normal * velocity.dot(&normal).min(0.0);
+
+            // Slopes too steep to stand on can't be walked up.
+            if let Some(up) = up {
+                if on_wall && normal_up > 0.0 && remaining.dot(&up) > 0.0 {
+                    remaining -= up * remaining.dot(&up);
+                }
+            }
+        }
+
+        if let (Some(up), Some(snap_distance), None) =
+            (up, controller.snap_to_ground, ground_normal)
+        {
+            if was_grounded && velocity.dot(&up) <= 0.0 {
+                let position = Isometry::from_parts(translation.into(), rotation) * shape_offset;
+                let snap = -up * snap_distance;
+
+                if let Some((toi, normal)) = self.cast_character(
+                    &*shape,
+                    &position,
+                    &snap,
+                    groups,
+                    &own_colliders,
+                    one_way_platforms,
+                ) {
+                    if normal.dot(&up) >= floor_cos {
+                        translation -= up * (toi * snap_distance - controller.offset).max(0.0);
+                        ground_normal = Some(normal);
+                    }
+                }
+            }
+        }
+
+        controller.velocity = velocity;
+        controller.set_contacts(ground_normal, on_ceiling, on_wall);
+        transform.translation = translation.into();
+
+        if let Some(body) = self.bodies.get_mut(body_handle) {
+            body.set_next_kinematic_position(Isometry::from_parts(translation.into(), rotation));
+        }
+    }
+
+    /// Casts the character's shape along `motion`, skipping sensors, the character's own colliders
+    /// and the one-way platforms it goes through.
+    /// Returns the fraction of the motion before the hit and the normal of the surface that was hit.
+    fn cast_character(
+        &self,
+        shape: &dyn Shape,
+        position: &Isometry<f32>,
+        motion: &Vector<f32>,
+        groups: InteractionGroups,
+        own_colliders: &[ColliderHandle],
+        one_way_platforms: &HashMap<ColliderHandle, Vector<f32>>,
+    ) -> Option<(f32, Vector<f32>)> {
+        let mut ignored = Vec::new();
+
+        for _ in 0..=MAX_IGNORED_PLATFORMS {
+            let filter = |handle: ColliderHandle| {
+                !own_colliders.contains(&handle)
+                    && !ignored.contains(&handle)
+                    && self
+                        .colliders
+                        .get(handle)
+                        .map(|collider| !collider.is_sensor())
+                        .unwrap_or(false)
+            };
+
+            let (handle, hit) = self.query_pipeline.cast_shape(
+                &self.colliders,
+                position,
+                motion,
+                shape,
+                1.0,
+                groups,
+                Some(&filter),
+            )?;
+            // Casts return the normal on the collider that was hit in world space.
+            let normal = *hit.normal1;
+
+            if let Some(platform_up) = one_way_platforms.get(&handle) {
+                let lands_on_platform = hit.status != TOIStatus::Penetrating
+                    && motion.dot(platform_up) < 0.0
+                    && normal.dot(platform_up) > 0.0;
+
+                if !lands_on_platform {
+                    ignored.push(handle);
+                    continue;
+                }
+            }
+
+            return Some((hit.toi, normal));
+        }
+
+        None
+    }
+}
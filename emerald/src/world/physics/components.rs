@@ -1 +1,155 @@
+use crate::Vector2;
 
+use serde::{Deserialize, Serialize};
+
+/// Moves the kinematic position based body of an entity by its `velocity` before every physics step,
+/// sliding along the colliders in the way instead of going through them.
+/// Gravity isn't applied, add it to the velocity to make the character fall.
+///
+/// ```ignore
+/// let (player, _) = world.spawn_with_body(
+///     (Transform::default(), CharacterController::default()),
+///     RigidBodyBuilder::kinematic_position_based(),
+/// )?;
+/// ```
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CharacterController {
+    /// Translation per second.
+    /// The part of it going into a floor, wall or ceiling is removed when the character hits it.
+    pub velocity: Vector2<f32>,
+
+    /// Direction pointing away from the floor, `(0, 1)` for platformers.
+    /// Top-down games can use `(0, 0)` so every collider is a wall.
+    pub up: Vector2<f32>,
+
+    /// Steepest slope in radians that the character can stand on and walk up.
+    pub max_slope_angle: f32,
+
+    /// Gap kept between the character and the colliders it touches, so it doesn't get stuck in them.
+    pub offset: f32,
+
+    /// Farthest the character is pulled down to stay on the ground,
+    /// when walking down a slope or off a small ledge. Disabled with `None`.
+    pub snap_to_ground: Option<f32>,
+
+    grounded: bool,
+    on_ceiling: bool,
+    on_wall: bool,
+    ground_normal: Option<Vector2<f32>>,
+}
+impl CharacterController {
+    pub fn new(velocity: Vector2<f32>) -> Self {
+        CharacterController {
+            velocity,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the character stood on a floor at the end of its last move.
+    #[inline]
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Whether the character hit a ceiling during its last move.
+    #[inline]
+    pub fn is_on_ceiling(&self) -> bool {
+        self.on_ceiling
+    }
+
+    /// Whether the character hit a wall, or a slope too steep to walk up, during its last move.
+    #[inline]
+    pub fn is_on_wall(&self) -> bool {
+        self.on_wall
+    }
+
+    /// Normal of the floor the character stands on.
+    #[inline]
+    pub fn ground_normal(&self) -> Option<Vector2<f32>> {
+        self.ground_normal
+    }
+
+    #[inline]
+    pub(crate) fn set_contacts(
+        &mut self,
+        ground_normal: Option<Vector2<f32>>,
+        on_ceiling: bool,
+        on_wall: bool,
+    ) {
+        self.grounded = ground_normal.is_some();
+        self.ground_normal = ground_normal;
+        self.on_ceiling = on_ceiling;
+        self.on_wall = on_wall;
+    }
+}
+impl Default for CharacterController {
+    fn default() -> Self {
+        CharacterController {
+            velocity: Vector2::new(0.0, 0.0),
+            up: Vector2::new(0.0, 1.0),
+            max_slope_angle: std::f32::consts::FRAC_PI_4,
+            offset: 0.01,
+            snap_to_ground: Some(2.0),
+            grounded: false,
+            on_ceiling: false,
+            on_wall: false,
+            ground_normal: None,
+        }
+    }
+}
+
+/// Makes the colliders of an entity solid only for characters and bodies landing on them from the `up` side,
+/// they go through them from below and from the sides.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct OneWayPlatform {
+    pub up: Vector2<f32>,
+}
+impl Default for OneWayPlatform {
+    fn default() -> Self {
+        OneWayPlatform {
+            up: Vector2::new(0.0, 1.0),
+        }
+    }
+}
+
+/// Carries the bodies touching the colliders of an entity along its surface,
+/// at `speed` units per second in the direction of the entity's local x axis.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ConveyorBelt {
+    pub speed: f32,
+}
+impl ConveyorBelt {
+    pub fn new(speed: f32) -> Self {
+        ConveyorBelt { speed }
+    }
+}
+
+/// How the body of an entity and its `Transform` are kept in sync while stepping.
+/// Entities without a `PhysicsSync` use `PhysicsSync::TwoWay`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PhysicsSync {
+    /// The transform is written to the body before a step, and the body to the transform after it.
+    /// Changing the transform teleports the body.
+    #[default]
+    TwoWay,
+
+    /// Only the body is written to the transform, changes to the transform are overwritten.
+    PhysicsDriven,
+
+    /// Only the transform is written to the body, the transform doesn't move with the body.
+    TransformDriven,
+
+    /// The body and the transform are left alone.
+    None,
+}
+impl PhysicsSync {
+    #[inline]
+    pub(crate) fn writes_to_body(&self) -> bool {
+        matches!(self, PhysicsSync::TwoWay | PhysicsSync::TransformDriven)
+    }
+
+    #[inline]
+    pub(crate) fn writes_to_transform(&self) -> bool {
+        matches!(self, PhysicsSync::TwoWay | PhysicsSync::PhysicsDriven)
+    }
+}
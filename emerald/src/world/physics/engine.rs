@@ -1,13 +1,15 @@
 use crate::*;
 
-use crate::core::components::transform::Transform;
+use crate::core::components::transform::{Transform, Translation};
 
 use glam::Vec2;
 use rapier2d::prelude::*;
 
+use crate::physics::ContactHooks;
+
 use crate::crossbeam;
 use hecs::{Entity, World};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// A physics engine unique to a game world. This handles the RigidBodies of the game.
 pub struct PhysicsEngine {
@@ -19,19 +21,26 @@ pub struct PhysicsEngine {
     pub(crate) multibody_joints: MultibodyJointSet,
     pub(crate) island_manager: IslandManager,
     pipeline: PhysicsPipeline,
-    pub(crate) gravity: Vector2<f32>,
+    pub(crate) settings: PhysicsSettings,
+    pub(crate) collision_layers: CollisionLayers,
     pub(crate) ccd_solver: CCDSolver,
     pub(crate) integration_parameters: IntegrationParameters,
     pub(crate) event_handler: ChannelEventCollector,
     pub(crate) event_recv: crossbeam::channel::Receiver<CollisionEvent>,
 
-    entity_bodies: HashMap<Entity, RigidBodyHandle>,
-    body_entities: HashMap<RigidBodyHandle, Entity>,
-    body_colliders: HashMap<RigidBodyHandle, Vec<ColliderHandle>>,
-    collider_body: HashMap<ColliderHandle, RigidBodyHandle>,
-    entity_collisions: HashMap<Entity, Vec<Entity>>,
-    physics_hooks: Box<dyn PhysicsHooks>,
-    query_pipeline: QueryPipeline,
+    pub(crate) entity_bodies: HashMap<Entity, RigidBodyHandle>,
+    pub(crate) body_entities: HashMap<RigidBodyHandle, Entity>,
+    pub(crate) body_colliders: HashMap<RigidBodyHandle, Vec<ColliderHandle>>,
+    pub(crate) collider_body: HashMap<ColliderHandle, RigidBodyHandle>,
+    pub(crate) distance_joints:
+        BTreeMap<DistanceJointHandle, (RigidBodyHandle, RigidBodyHandle, DistanceJoint)>,
+    pub(crate) distance_joint_counter: u32,
+    pub(crate) entity_collisions: HashMap<Entity, Vec<Entity>>,
+    pub(crate) events: Vec<PhysicsEvent>,
+    pub(crate) contact_force_event_threshold: Option<f32>,
+    pub(crate) contact_hooks: Box<dyn ContactHooks>,
+    pub(crate) hooked_surfaces: HookedSurfaces,
+    pub(crate) query_pipeline: QueryPipeline,
 }
 
 impl PhysicsEngine {
@@ -48,7 +57,6 @@ impl PhysicsEngine {
         // Initialize the event collector.
         let (event_send, event_recv) = crossbeam::channel::unbounded();
         let event_handler = ChannelEventCollector::new(event_send);
-        let physics_hooks = Box::new(());
         let ccd_solver = CCDSolver::new();
         let query_pipeline = QueryPipeline::new();
 
@@ -60,7 +68,8 @@ impl PhysicsEngine {
             impulse_joints,
             multibody_joints,
             pipeline,
-            gravity: Vector2::new(0.0, 0.0),
+            settings: PhysicsSettings::default(),
+            collision_layers: CollisionLayers::new(),
             island_manager,
             ccd_solver,
             integration_parameters: IntegrationParameters::default(),
@@ -70,8 +79,13 @@ impl PhysicsEngine {
             body_entities: HashMap::new(),
             body_colliders: HashMap::new(),
             collider_body: HashMap::new(),
+            distance_joints: BTreeMap::new(),
+            distance_joint_counter: 0,
             entity_collisions: HashMap::new(),
-            physics_hooks,
+            events: Vec::new(),
+            contact_force_event_threshold: None,
+            contact_hooks: Box::new(()),
+            hooked_surfaces: HookedSurfaces::default(),
             query_pipeline,
         }
     }
@@ -81,8 +95,16 @@ impl PhysicsEngine {
         let dt = self.integration_parameters.dt;
         self.integration_parameters.dt = delta;
 
+        self.solve_distance_joints(delta);
+
+        let hooks = EntityHooks {
+            hooks: &*self.contact_hooks,
+            surfaces: &self.hooked_surfaces,
+            body_entities: &self.body_entities,
+        };
+
         self.pipeline.step(
-            &self.gravity,
+            &self.settings.gravity,
             &self.integration_parameters,
             &mut self.island_manager,
             &mut self.broad_phase,
@@ -92,11 +114,37 @@ impl PhysicsEngine {
             &mut self.impulse_joints,
             &mut self.multibody_joints,
             &mut self.ccd_solver,
-            &(*self.physics_hooks),
+            &hooks,
             &self.event_handler,
         );
 
         self.integration_parameters.dt = dt;
+
+        self.consume_contacts();
+        if let Some(threshold) = self.contact_force_event_threshold {
+            self.queue_contact_forces(delta, threshold);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_settings(&mut self, settings: PhysicsSettings) {
+        settings.apply(&mut self.integration_parameters);
+        self.settings = settings;
+    }
+
+    #[inline]
+    pub(crate) fn events(&self) -> &[PhysicsEvent] {
+        &self.events
+    }
+
+    #[inline]
+    pub(crate) fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    #[inline]
+    pub(crate) fn set_contact_force_event_threshold(&mut self, threshold: Option<f32>) {
+        self.contact_force_event_threshold = threshold;
     }
 
     #[inline]
@@ -106,54 +154,98 @@ impl PhysicsEngine {
     }
 
     #[inline]
-    pub(crate) fn consume_contacts(&mut self) {
-        let mut started_contacts = Vec::new();
-        let mut stopped_contacts = Vec::new();
-
-        while let Ok(contact_event) = self.event_recv.try_recv() {
-            match contact_event {
-                CollisionEvent::Started(h1, h2, _flags) => {
-                    if let (Some(collider_one), Some(collider_two)) =
-                        (self.colliders.get(h1), self.colliders.get(h2))
-                    {
-                        if let (Some(collider_parent_one), Some(collider_parent_two)) =
-                            (collider_one.parent(), collider_two.parent())
-                        {
-                            if let (Some(entity_one), Some(entity_two)) = (
-                                self.body_entities.get(&collider_parent_one),
-                                self.body_entities.get(&collider_parent_two),
-                            ) {
-                                started_contacts.push((*entity_one, *entity_two));
-                            }
-                        }
+    fn consume_contacts(&mut self) {
+        while let Ok(collision_event) = self.event_recv.try_recv() {
+            let (collider_one, collider_two) =
+                (collision_event.collider1(), collision_event.collider2());
+
+            if let (Some(entity_one), Some(entity_two)) = (
+                self.get_entity_from_collider(collider_one),
+                self.get_entity_from_collider(collider_two),
+            ) {
+                let collision = Collision {
+                    entity_one,
+                    entity_two,
+                    collider_one,
+                    collider_two,
+                    sensor: collision_event.sensor(),
+                };
+
+                match collision_event {
+                    CollisionEvent::Started(..) => {
+                        self.add_collision(entity_one, entity_two);
+                        self.events.push(PhysicsEvent::CollisionStarted(collision));
                     }
-                }
-                CollisionEvent::Stopped(h1, h2, _flags) => {
-                    if let (Some(collider_one), Some(collider_two)) =
-                        (self.colliders.get(h1), self.colliders.get(h2))
-                    {
-                        if let (Some(collider_parent_one), Some(collider_parent_two)) =
-                            (collider_one.parent(), collider_two.parent())
-                        {
-                            if let (Some(entity_one), Some(entity_two)) = (
-                                self.body_entities.get(&collider_parent_one),
-                                self.body_entities.get(&collider_parent_two),
-                            ) {
-                                stopped_contacts.push((*entity_one, *entity_two));
-                            }
-                        }
+                    CollisionEvent::Stopped(..) => {
+                        self.remove_collision(entity_one, entity_two);
+                        self.events.push(PhysicsEvent::CollisionStopped(collision));
                     }
                 }
             }
         }
+    }
 
-        for started_contact in started_contacts {
-            self.add_collision(started_contact.0, started_contact.1);
+    /// Queues a contact force event for every pair of colliders in contact that pushed
+    /// against each other harder than the threshold during a step that lasted `delta` seconds.
+    fn queue_contact_forces(&mut self, delta: f32, threshold: f32) {
+        if delta <= 0.0 {
+            return;
         }
 
-        for stopped_contact in stopped_contacts {
-            self.remove_collision(stopped_contact.0, stopped_contact.1);
+        let mut contact_forces = Vec::new();
+        for contact_pair in self.narrow_phase.contact_pairs() {
+            if !contact_pair.has_any_active_contact {
+                continue;
+            }
+
+            let (entity_one, entity_two, collider_position) = match (
+                self.get_entity_from_collider(contact_pair.collider1),
+                self.get_entity_from_collider(contact_pair.collider2),
+                self.colliders.get(contact_pair.collider1),
+            ) {
+                (Some(entity_one), Some(entity_two), Some(collider)) => {
+                    (entity_one, entity_two, *collider.position())
+                }
+                _ => continue,
+            };
+
+            let mut total_force = Vector2::zeros();
+            let mut contacts = Vec::new();
+            for manifold in &contact_pair.manifolds {
+                let normal = manifold.data.normal;
+                let shape_position = manifold
+                    .subshape_pos1
+                    .map(|subshape_position| collider_position * subshape_position)
+                    .unwrap_or(collider_position);
+
+                for point in &manifold.points {
+                    let world_point = shape_position * point.local_p1;
+                    total_force += normal * (point.data.impulse / delta);
+                    contacts.push(ContactPoint {
+                        point: Translation::new(world_point.x, world_point.y),
+                        normal,
+                        impulse: point.data.impulse,
+                    });
+                }
+            }
+
+            let total_force_magnitude = total_force.norm();
+            if total_force_magnitude < threshold {
+                continue;
+            }
+
+            contact_forces.push(PhysicsEvent::ContactForce(ContactForce {
+                entity_one,
+                entity_two,
+                collider_one: contact_pair.collider1,
+                collider_two: contact_pair.collider2,
+                total_force,
+                total_force_magnitude,
+                contacts,
+            }));
         }
+
+        self.events.extend(contact_forces);
     }
 
     #[inline]
@@ -203,42 +295,197 @@ impl PhysicsEngine {
     }
 
     #[inline]
-    pub fn cast_ray(&mut self, ray_cast_query: RayCastQuery<'_>) -> Option<Entity> {
-        if let Some((handle, _toi)) = self.query_pipeline.cast_ray(
+    pub fn cast_ray(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Option<RayCastHit>, EmeraldError> {
+        let groups = self.query_groups(ray_cast_query.interaction_groups, ray_cast_query.layers)?;
+
+        Ok(self
+            .query_pipeline
+            .cast_ray_and_get_normal(
+                &self.colliders,
+                &ray_cast_query.ray,
+                ray_cast_query.max_toi,
+                ray_cast_query.solid,
+                groups,
+                ray_cast_query.filter,
+            )
+            .and_then(|(handle, intersection)| {
+                self.ray_cast_hit(&ray_cast_query.ray, handle, intersection)
+            }))
+    }
+
+    /// Every hit along the ray, the closest first.
+    pub fn cast_ray_all(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Vec<RayCastHit>, EmeraldError> {
+        let groups = self.query_groups(ray_cast_query.interaction_groups, ray_cast_query.layers)?;
+        let mut hits = Vec::new();
+
+        self.query_pipeline.intersections_with_ray(
             &self.colliders,
             &ray_cast_query.ray,
             ray_cast_query.max_toi,
             ray_cast_query.solid,
-            ray_cast_query.interaction_groups,
+            groups,
             ray_cast_query.filter,
-        ) {
-            return self.get_entity_from_collider(handle);
+            |handle, intersection| {
+                hits.extend(self.ray_cast_hit(&ray_cast_query.ray, handle, intersection));
+                true
+            },
+        );
+        hits.sort_by(|a, b| a.toi.total_cmp(&b.toi));
+
+        Ok(hits)
+    }
+
+    /// The groups of a query, or the ones seeing its layers if it names any.
+    #[inline]
+    fn query_groups(
+        &self,
+        groups: InteractionGroups,
+        layers: &[&str],
+    ) -> Result<InteractionGroups, EmeraldError> {
+        if layers.is_empty() {
+            Ok(groups)
+        } else {
+            self.collision_layers.query_groups(layers)
         }
+    }
 
-        None
+    #[inline]
+    fn ray_cast_hit(
+        &self,
+        ray: &Ray,
+        collider: ColliderHandle,
+        intersection: RayIntersection,
+    ) -> Option<RayCastHit> {
+        let point = ray.point_at(intersection.toi);
+
+        self.get_entity_from_collider(collider)
+            .map(|entity| RayCastHit {
+                entity,
+                collider,
+                toi: intersection.toi,
+                point: Translation::new(point.x, point.y),
+                normal: intersection.normal,
+            })
     }
 
     #[inline]
     pub fn cast_shape(
-        &mut self,
+        &self,
         shape: &dyn Shape,
         shape_cast_query: ShapeCastQuery<'_>,
-    ) -> Option<Entity> {
+    ) -> Result<Option<ShapeCastHit>, EmeraldError> {
         let pos = Isometry::from(shape_cast_query.origin_translation);
+        let groups =
+            self.query_groups(shape_cast_query.interaction_groups, shape_cast_query.layers)?;
+
+        Ok(self
+            .query_pipeline
+            .cast_shape(
+                &self.colliders,
+                &pos,
+                &shape_cast_query.velocity,
+                shape,
+                shape_cast_query.max_toi,
+                groups,
+                shape_cast_query.filter,
+            )
+            .and_then(|(collider, hit)| {
+                // The first witness and normal are on the collider, already in world space.
+                self.get_entity_from_collider(collider)
+                    .map(|entity| ShapeCastHit {
+                        entity,
+                        collider,
+                        toi: hit.toi,
+                        point: Translation::new(hit.witness1.x, hit.witness1.y),
+                        normal: *hit.normal1,
+                    })
+            }))
+    }
+
+    /// Entities with a collider containing the point.
+    pub fn intersections_with_point(
+        &self,
+        point_query: PointQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        let groups = self.query_groups(point_query.interaction_groups, point_query.layers)?;
+        let mut colliders = Vec::new();
+
+        self.query_pipeline.intersections_with_point(
+            &self.colliders,
+            &Vec2::from(point_query.point).into(),
+            groups,
+            point_query.filter,
+            |handle| {
+                colliders.push(handle);
+                true
+            },
+        );
+
+        Ok(self.get_entities_from_colliders(colliders))
+    }
 
-        if let Some((handle, _hit)) = self.query_pipeline.cast_shape(
+    /// Entities with a collider overlapping the shape.
+    pub fn intersections_with_shape(
+        &self,
+        shape: &dyn Shape,
+        shape_query: ShapeQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        let groups = self.query_groups(shape_query.interaction_groups, shape_query.layers)?;
+        let mut colliders = Vec::new();
+
+        self.query_pipeline.intersections_with_shape(
             &self.colliders,
-            &pos,
-            &shape_cast_query.velocity,
+            &Isometry::from(shape_query.origin_translation),
             shape,
-            shape_cast_query.max_toi,
-            shape_cast_query.interaction_groups,
-            shape_cast_query.filter,
-        ) {
-            return self.get_entity_from_collider(handle);
+            groups,
+            shape_query.filter,
+            |handle| {
+                colliders.push(handle);
+                true
+            },
+        );
+
+        Ok(self.get_entities_from_colliders(colliders))
+    }
+
+    /// Entities with a collider whose bounding box overlaps the region, in world space.
+    /// Only bounding boxes are compared, use `intersections_with_shape` for exact overlaps.
+    pub fn intersections_with_aabb(&self, region: Rectangle) -> Vec<Entity> {
+        let aabb = AABB::new(
+            Point::new(region.x, region.y),
+            Point::new(region.x + region.width, region.y + region.height),
+        );
+        let mut colliders = Vec::new();
+
+        self.query_pipeline
+            .colliders_with_aabb_intersecting_aabb(&aabb, |handle| {
+                colliders.push(*handle);
+                true
+            });
+
+        self.get_entities_from_colliders(colliders)
+    }
+
+    /// Entities owning the colliders, each entity listed once.
+    #[inline]
+    fn get_entities_from_colliders(&self, colliders: Vec<ColliderHandle>) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        for entity in colliders
+            .into_iter()
+            .filter_map(|collider| self.get_entity_from_collider(collider))
+        {
+            if !entities.contains(&entity) {
+                entities.push(entity);
+            }
         }
 
-        None
+        entities
     }
 
     #[inline]
@@ -252,6 +499,215 @@ impl PhysicsEngine {
         None
     }
 
+    pub(crate) fn build_joint(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+        joint: Joint,
+    ) -> Result<JointHandle, EmeraldError> {
+        let (body_one, body_two) = match (
+            self.entity_bodies.get(&entity_one),
+            self.entity_bodies.get(&entity_two),
+        ) {
+            (Some(body_one), Some(body_two)) => (*body_one, *body_two),
+            _ => {
+                return Err(EmeraldError::new(format!(
+                    "Unable to join {:?} and {:?}, both entities need a rigid body.",
+                    entity_one, entity_two
+                )))
+            }
+        };
+
+        let handle = match joint {
+            Joint::Impulse(joint) => {
+                JointHandle::Impulse(self.impulse_joints.insert(body_one, body_two, joint))
+            }
+            Joint::Distance(joint) => {
+                let handle = DistanceJointHandle(self.distance_joint_counter);
+                self.distance_joint_counter += 1;
+                self.distance_joints
+                    .insert(handle, (body_one, body_two, joint));
+
+                JointHandle::Distance(handle)
+            }
+        };
+
+        Ok(handle)
+    }
+
+    pub(crate) fn remove_joint(&mut self, handle: JointHandle) -> Option<Joint> {
+        match handle {
+            JointHandle::Impulse(handle) => self
+                .impulse_joints
+                .remove(handle, &mut self.island_manager, &mut self.bodies, true)
+                .map(|joint| Joint::Impulse(joint.data)),
+            JointHandle::Distance(handle) => self
+                .distance_joints
+                .remove(&handle)
+                .map(|(_, _, joint)| Joint::Distance(joint)),
+        }
+    }
+
+    /// Handles of the joints attached to the body of this entity.
+    pub fn get_joints(&self, entity: Entity) -> Vec<JointHandle> {
+        match self.entity_bodies.get(&entity) {
+            Some(body) => self.get_body_joints(*body),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_body_joints(&self, body: RigidBodyHandle) -> Vec<JointHandle> {
+        let mut joints = self
+            .impulse_joints
+            .iter()
+            .filter(|(_, joint)| joint.body1 == body || joint.body2 == body)
+            .map(|(handle, _)| JointHandle::Impulse(handle))
+            .collect::<Vec<JointHandle>>();
+
+        joints.extend(
+            self.distance_joints
+                .iter()
+                .filter(|(_, (body_one, body_two, _))| *body_one == body || *body_two == body)
+                .map(|(handle, _)| JointHandle::Distance(*handle)),
+        );
+
+        joints
+    }
+
+    /// The entities joined by the joint, in the order they were given when building it.
+    pub fn get_joint_entities(&self, handle: JointHandle) -> Option<(Entity, Entity)> {
+        let (body_one, body_two) = match handle {
+            JointHandle::Impulse(handle) => self
+                .impulse_joints
+                .get(handle)
+                .map(|joint| (joint.body1, joint.body2))?,
+            JointHandle::Distance(handle) => self
+                .distance_joints
+                .get(&handle)
+                .map(|(body_one, body_two, _)| (*body_one, *body_two))?,
+        };
+
+        match (
+            self.body_entities.get(&body_one),
+            self.body_entities.get(&body_two),
+        ) {
+            (Some(entity_one), Some(entity_two)) => Some((*entity_one, *entity_two)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn impulse_joint_mut(
+        &mut self,
+        handle: ImpulseJointHandle,
+    ) -> Option<&mut ImpulseJoint> {
+        self.impulse_joints.get_mut(handle)
+    }
+
+    pub(crate) fn distance_joint_mut(
+        &mut self,
+        handle: DistanceJointHandle,
+    ) -> Option<&mut DistanceJoint> {
+        self.distance_joints
+            .get_mut(&handle)
+            .map(|(_, _, joint)| joint)
+    }
+
+    /// Every joint along with the entities it joins, used to carry joints over to another world.
+    pub(crate) fn get_entity_joints(&self) -> Vec<(Entity, Entity, Joint)> {
+        let impulse_joints = self
+            .impulse_joints
+            .iter()
+            .map(|(_, joint)| (joint.body1, joint.body2, Joint::Impulse(joint.data)));
+        let distance_joints = self
+            .distance_joints
+            .values()
+            .map(|(body_one, body_two, joint)| (*body_one, *body_two, Joint::Distance(*joint)));
+
+        impulse_joints
+            .chain(distance_joints)
+            .filter_map(|(body_one, body_two, joint)| {
+                match (
+                    self.body_entities.get(&body_one),
+                    self.body_entities.get(&body_two),
+                ) {
+                    (Some(entity_one), Some(entity_two)) => Some((*entity_one, *entity_two, joint)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Applies impulses at the anchors of every distance joint so that, once the step moves its bodies,
+    /// the anchors are within the lengths of the joint.
+    /// This runs before the step so contacts still stop the bodies, and is repeated as many times
+    /// as the velocity iterations of the settings so that chained joints settle together.
+    fn solve_distance_joints(&mut self, delta: f32) {
+        if delta <= 0.0 {
+            return;
+        }
+
+        for _ in 0..self.integration_parameters.max_velocity_iterations.max(1) {
+            for (body_one, body_two, joint) in self.distance_joints.values() {
+                let (one, two) = match (self.bodies.get(*body_one), self.bodies.get(*body_two)) {
+                    (Some(body_one), Some(body_two)) => (
+                        JointAnchor::new(
+                            body_one,
+                            joint.local_anchor1,
+                            self.settings.gravity,
+                            delta,
+                        ),
+                        JointAnchor::new(
+                            body_two,
+                            joint.local_anchor2,
+                            self.settings.gravity,
+                            delta,
+                        ),
+                    ),
+                    _ => continue,
+                };
+
+                let length = (two.point - one.point).norm();
+                let predicted_offset = two.predicted_point(delta) - one.predicted_point(delta);
+                let predicted_length = predicted_offset.norm();
+                if predicted_length <= f32::EPSILON {
+                    continue;
+                }
+
+                // Anchors already out of the lengths are brought back a share of the way.
+                let min_length = if length < joint.min_length {
+                    length + (joint.min_length - length) * DISTANCE_JOINT_CORRECTION
+                } else {
+                    joint.min_length
+                };
+                let max_length = if length > joint.max_length {
+                    length - (length - joint.max_length) * DISTANCE_JOINT_CORRECTION
+                } else {
+                    joint.max_length
+                };
+                let target_length = predicted_length.max(min_length).min(max_length);
+                if target_length == predicted_length {
+                    continue;
+                }
+
+                let direction = predicted_offset / predicted_length;
+                let inv_effective_mass =
+                    one.inv_effective_mass(&direction) + two.inv_effective_mass(&direction);
+                if inv_effective_mass <= 0.0 {
+                    continue;
+                }
+
+                let impulse =
+                    direction * ((target_length - predicted_length) / (delta * inv_effective_mass));
+                if let Some(body) = self.bodies.get_mut(*body_one) {
+                    body.apply_impulse_at_point(-impulse, one.point, true);
+                }
+                if let Some(body) = self.bodies.get_mut(*body_two) {
+                    body.apply_impulse_at_point(impulse, two.point, true);
+                }
+            }
+        }
+    }
+
     #[inline]
     pub(crate) fn add_body(
         &mut self,
@@ -298,10 +754,17 @@ impl PhysicsEngine {
 
             transform.clone()
         };
+        // Bodies live in world space, while the transform of a child is relative to its parent.
+        let transform = match parent_global_transform(world, entity) {
+            Some(parent_transform) => parent_transform.mul_transform(&transform),
+            None => transform,
+        };
 
-        let body = builder
-            .translation(Vec2::from(transform.translation).into())
-            .build();
+        let mut builder = builder.position(body_position(&transform));
+        if self.settings.ccd_enabled {
+            builder = builder.ccd_enabled(true);
+        }
+        let body = builder.build();
 
         self.add_body(entity, body, world)
     }
@@ -341,7 +804,7 @@ impl PhysicsEngine {
     }
 
     #[inline]
-    pub(crate) fn remove_body(&mut self, entity: Entity) -> Option<RigidBody> {
+    pub(crate) fn remove_body(&mut self, entity: Entity) -> Option<RemovedBody> {
         let mut body_entities = Vec::new();
         for e in self.entity_collisions.keys() {
             body_entities.push(*e);
@@ -351,9 +814,19 @@ impl PhysicsEngine {
             self.remove_collision(body_entity, entity);
         }
 
+        self.hooked_surfaces
+            .contact_materials
+            .retain(|(entity_one, entity_two), _| *entity_one != entity && *entity_two != entity);
+
         if let Some(body_handle) = self.entity_bodies.remove(&entity) {
             self.body_entities.remove(&body_handle);
 
+            // Impulse joints are removed along with the body.
+            let joints = self.get_body_joints(body_handle);
+            self.distance_joints.retain(|_, (body_one, body_two, _)| {
+                *body_one != body_handle && *body_two != body_handle
+            });
+
             if let Some(body) = self.bodies.remove(
                 body_handle,
                 &mut self.island_manager,
@@ -362,7 +835,7 @@ impl PhysicsEngine {
                 &mut self.multibody_joints,
                 true,
             ) {
-                return Some(body);
+                return Some(RemovedBody { body, joints });
             }
         }
 
@@ -403,17 +876,46 @@ impl PhysicsEngine {
         None
     }
 
+    /// Writes the transforms of entities to their bodies, before stepping.
+    /// The transforms of entities with a parent are made global first.
     #[inline]
     pub(crate) fn sync_physics_world_to_game_world(&mut self, world: &mut hecs::World) {
-        for (_id, (transform, rbh)) in world.query::<(&mut Transform, &RigidBodyHandle)>().iter() {
-            self.sync_physics_position_to_entity_position(transform, *rbh);
+        for (id, (transform, rbh, sync)) in world
+            .query::<(&Transform, &RigidBodyHandle, Option<&PhysicsSync>)>()
+            .iter()
+        {
+            if sync.copied().unwrap_or_default().writes_to_body() {
+                let global_transform = match parent_global_transform(world, id) {
+                    Some(parent_transform) => parent_transform.mul_transform(transform),
+                    None => *transform,
+                };
+                self.sync_physics_position_to_entity_position(&global_transform, *rbh);
+            }
         }
     }
 
+    /// Writes the positions of bodies to the transforms of their entities, after stepping.
+    /// Parents are written before their children, whose transforms are made relative to them.
     #[inline]
     pub(crate) fn sync_game_world_to_physics_world(&mut self, world: &mut hecs::World) {
-        for (_id, (transform, rbh)) in world.query::<(&mut Transform, &RigidBodyHandle)>().iter() {
-            self.sync_entity_position_to_physics_position(transform, *rbh);
+        let mut synced = world
+            .query::<(&RigidBodyHandle, Option<&PhysicsSync>)>()
+            .with::<Transform>()
+            .iter()
+            .filter(|(_, (_, sync))| sync.copied().unwrap_or_default().writes_to_transform())
+            .map(|(id, (rbh, _))| (ancestor_count(world, id), id, *rbh))
+            .collect::<Vec<(usize, Entity, RigidBodyHandle)>>();
+        synced.sort_by_key(|(depth, _, _)| *depth);
+
+        for (_, id, rbh) in synced {
+            let parent_transform = parent_global_transform(world, id);
+            if let Ok(mut transform) = world.get_mut::<Transform>(id) {
+                self.sync_entity_position_to_physics_position(
+                    &mut transform,
+                    parent_transform.as_ref(),
+                    rbh,
+                );
+            }
         }
     }
 
@@ -421,11 +923,20 @@ impl PhysicsEngine {
     fn sync_entity_position_to_physics_position(
         &mut self,
         transform: &mut Transform,
+        parent_transform: Option<&Transform>,
         body_handle: RigidBodyHandle,
     ) {
         if let Some(body_transform) = self.bodies.get(body_handle) {
-            let translation = body_transform.position().translation;
-            transform.translation = translation.into();
+            let position = body_transform.position();
+            let mut global_transform = Transform::from_translation(position.translation);
+            global_transform.rotation = position.rotation.angle();
+
+            let local_transform = match parent_transform {
+                Some(parent_transform) => parent_transform.inverse_mul_transform(&global_transform),
+                None => global_transform,
+            };
+            transform.translation = local_transform.translation;
+            transform.rotation = local_transform.rotation;
         }
     }
 
@@ -436,11 +947,90 @@ impl PhysicsEngine {
         body_handle: RigidBodyHandle,
     ) {
         if let Some(body) = self.bodies.get_mut(body_handle) {
+            let position = body_position(transform);
             if body.is_kinematic() {
-                body.set_next_kinematic_position(transform.translation.into())
+                body.set_next_kinematic_position(position)
             } else {
-                body.set_position(transform.translation.into(), false)
+                body.set_position(position, false)
             }
         }
     }
 }
+
+#[inline]
+fn ancestor_count(world: &hecs::World, entity: Entity) -> usize {
+    let mut count = 0;
+    let mut ancestor = world
+        .get::<Parent>(entity)
+        .ok()
+        .map(|parent| parent.entity());
+    while let Some(parent) = ancestor {
+        count += 1;
+        ancestor = world
+            .get::<Parent>(parent)
+            .ok()
+            .map(|parent| parent.entity());
+    }
+
+    count
+}
+
+#[inline]
+fn body_position(transform: &Transform) -> Isometry<f32> {
+    Isometry::new(Vec2::from(transform.translation).into(), transform.rotation)
+}
+
+/// The share of the stretch or compression of a distance joint corrected every step.
+/// Correcting all of it at once would pull bodies through the colliders in their way.
+const DISTANCE_JOINT_CORRECTION: f32 = 0.2;
+
+/// The anchor of a distance joint on one of its bodies.
+struct JointAnchor {
+    /// Where the anchor is, in world space.
+    point: Point<f32>,
+    /// The velocity of the anchor once gravity is applied during the step.
+    velocity: Vector<f32>,
+    /// From the center of mass of the body to the anchor.
+    lever_arm: Vector<f32>,
+    /// Zero for bodies that can't be moved by other bodies.
+    inv_mass: f32,
+    inv_inertia: f32,
+}
+impl JointAnchor {
+    fn new(body: &RigidBody, local_anchor: Point<f32>, gravity: Vector<f32>, delta: f32) -> Self {
+        let point = body.position() * local_anchor;
+        let center_of_mass = body.position() * body.mass_properties().local_com;
+        let velocity = body.velocity_at_point(&point);
+
+        let (inv_mass, inv_inertia, velocity) = if body.is_dynamic() {
+            let mass_properties = body.mass_properties();
+            (
+                mass_properties.inv_mass,
+                mass_properties.inv_principal_inertia_sqrt.powi(2),
+                velocity + gravity * (body.gravity_scale() * delta),
+            )
+        } else {
+            (0.0, 0.0, velocity)
+        };
+
+        JointAnchor {
+            point,
+            velocity,
+            lever_arm: point - center_of_mass,
+            inv_mass,
+            inv_inertia,
+        }
+    }
+
+    /// Where the anchor will be after a step at its current velocity.
+    #[inline]
+    fn predicted_point(&self, delta: f32) -> Point<f32> {
+        self.point + self.velocity * delta
+    }
+
+    /// How much the anchor speeds up along `direction` per unit of impulse applied along it.
+    #[inline]
+    fn inv_effective_mass(&self, direction: &Vector<f32>) -> f32 {
+        self.inv_mass + self.inv_inertia * self.lever_arm.perp(direction).powi(2)
+    }
+}
@@ -0,0 +1,57 @@
+use hecs::Entity;
+use rapier2d::prelude::ColliderHandle;
+
+use crate::transform::Translation;
+use crate::Vector2;
+
+/// Something that happened between the colliders of two entities during a physics step.
+/// Events are queued by every call to `world.physics().step()`, and replace the ones of the previous call.
+#[derive(Clone, Debug)]
+pub enum PhysicsEvent {
+    /// The colliders started touching during this step.
+    CollisionStarted(Collision),
+
+    /// The colliders stopped touching during this step.
+    CollisionStopped(Collision),
+
+    /// The colliders pushed against each other with a total force above
+    /// the threshold set by `world.physics().set_contact_force_event_threshold()`.
+    ContactForce(ContactForce),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collision {
+    pub entity_one: Entity,
+    pub entity_two: Entity,
+    pub collider_one: ColliderHandle,
+    pub collider_two: ColliderHandle,
+
+    /// Whether at least one of the colliders is a sensor.
+    pub sensor: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ContactForce {
+    pub entity_one: Entity,
+    pub entity_two: Entity,
+    pub collider_one: ColliderHandle,
+    pub collider_two: ColliderHandle,
+
+    /// Sum of the forces applied by the first collider on the second one.
+    pub total_force: Vector2<f32>,
+    pub total_force_magnitude: f32,
+
+    pub contacts: Vec<ContactPoint>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ContactPoint {
+    /// Where the colliders touch, in world space.
+    pub point: Translation,
+
+    /// Direction pointing from the first collider towards the second one.
+    pub normal: Vector2<f32>,
+
+    /// Impulse applied along the normal during the step.
+    pub impulse: f32,
+}
@@ -1,5 +1,6 @@
+use crate::physics::ContactHooks;
 use crate::physics::*;
-use crate::{EmeraldError, Vector2};
+use crate::{EmeraldError, Rectangle, Vector2};
 
 use hecs::Entity;
 use rapier2d::prelude::*;
@@ -33,6 +34,18 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine.build_collider(body_handle, desc)
     }
 
+    /// Builds merged colliders for the solid tiles of the `Tilemap` or `AutoTilemap` of this entity,
+    /// on a fixed body unless the entity already has a body.
+    /// The colliders follow the changes made to the tiles every time the physics steps.
+    pub fn build_tilemap_colliders(
+        &mut self,
+        entity: Entity,
+        tilemap_colliders: TilemapColliders,
+    ) -> Result<RigidBodyHandle, EmeraldError> {
+        self.physics_engine
+            .build_tilemap_colliders(entity, tilemap_colliders, self.world)
+    }
+
     /// Retrieves the entities with bodies that are touching the body of this entity.
     /// This includes:
     /// Collider <- Contact -> Collider
@@ -66,8 +79,8 @@ impl<'a> PhysicsHandler<'a> {
         None
     }
 
-    /// Remove physics body attached to this entity.
-    pub fn remove_body(&mut self, entity: Entity) -> Option<RigidBody> {
+    /// Remove physics body attached to this entity, along with the joints attached to it.
+    pub fn remove_body(&mut self, entity: Entity) -> Option<RemovedBody> {
         if let Some(body) = self.physics_engine.remove_body(entity) {
             if self.world.remove_one::<RigidBodyHandle>(entity).is_ok() {
                 return Some(body);
@@ -81,6 +94,56 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine.remove_collider(collider_handle)
     }
 
+    /// Joins the bodies of two entities.
+    /// Fails if either entity doesn't have a rigid body.
+    ///
+    /// ```ignore
+    /// let hinge = RevoluteJointBuilder::new()
+    ///     .local_anchor1(Point2::new(16.0, 0.0))
+    ///     .limits([-1.0, 1.0])
+    ///     .motor_velocity(2.0, 0.5);
+    /// world.physics().build_joint(door_frame, door, hinge)?;
+    /// world.physics().build_joint(anchor, lamp, DistanceJoint::rope(64.0))?;
+    /// ```
+    pub fn build_joint<J: Into<Joint>>(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+        joint: J,
+    ) -> Result<JointHandle, EmeraldError> {
+        self.physics_engine
+            .build_joint(entity_one, entity_two, joint.into())
+    }
+
+    pub fn remove_joint(&mut self, joint_handle: JointHandle) -> Option<Joint> {
+        self.physics_engine.remove_joint(joint_handle)
+    }
+
+    /// Retrieves the joints attached to the body of this entity.
+    pub fn get_joints(&self, entity: Entity) -> Vec<JointHandle> {
+        self.physics_engine.get_joints(entity)
+    }
+
+    /// Retrieves the entities joined by the joint.
+    pub fn get_joint_entities(&self, joint_handle: JointHandle) -> Option<(Entity, Entity)> {
+        self.physics_engine.get_joint_entities(joint_handle)
+    }
+
+    /// Gives access to a revolute, prismatic or fixed joint, to drive its motors or change its limits.
+    pub fn impulse_joint_mut(
+        &mut self,
+        joint_handle: ImpulseJointHandle,
+    ) -> Option<&mut ImpulseJoint> {
+        self.physics_engine.impulse_joint_mut(joint_handle)
+    }
+
+    pub fn distance_joint_mut(
+        &mut self,
+        joint_handle: DistanceJointHandle,
+    ) -> Option<&mut DistanceJoint> {
+        self.physics_engine.distance_joint_mut(joint_handle)
+    }
+
     pub fn rigid_body(&mut self, body_handle: RigidBodyHandle) -> Option<&RigidBody> {
         self.physics_engine.bodies.get(body_handle)
     }
@@ -93,20 +156,93 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine.bodies.len()
     }
 
-    /// Returns the first entity the ray hits if one exists.
-    pub fn cast_ray(&mut self, ray_cast_query: RayCastQuery<'_>) -> Option<Entity> {
+    /// Returns the first hit along the ray if one exists.
+    /// Fails if the query names a collision layer that doesn't exist, as do the other queries.
+    pub fn cast_ray(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Option<RayCastHit>, EmeraldError> {
         self.physics_engine.cast_ray(ray_cast_query)
     }
 
-    /// Returns the first entity the shape collides with if one exists.
+    /// Returns every hit along the ray, the closest first.
+    pub fn cast_ray_all(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Vec<RayCastHit>, EmeraldError> {
+        self.physics_engine.cast_ray_all(ray_cast_query)
+    }
+
+    /// Returns the first hit of the moving shape if one exists.
     pub fn cast_shape(
-        &mut self,
+        &self,
         shape: &dyn Shape,
         shape_cast_query: ShapeCastQuery<'_>,
-    ) -> Option<Entity> {
+    ) -> Result<Option<ShapeCastHit>, EmeraldError> {
         self.physics_engine.cast_shape(shape, shape_cast_query)
     }
 
+    /// Retrieves the entities with a collider containing the point.
+    pub fn intersections_with_point(
+        &self,
+        point_query: PointQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        self.physics_engine.intersections_with_point(point_query)
+    }
+
+    /// Retrieves the entities with a collider overlapping the shape.
+    pub fn intersections_with_shape(
+        &self,
+        shape: &dyn Shape,
+        shape_query: ShapeQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        self.physics_engine
+            .intersections_with_shape(shape, shape_query)
+    }
+
+    /// Retrieves the entities with a collider whose bounding box overlaps the region.
+    pub fn intersections_with_aabb(&self, region: Rectangle) -> Vec<Entity> {
+        self.physics_engine.intersections_with_aabb(region)
+    }
+
+    /// Collisions and contact forces that happened during the last call to `step`, in order.
+    pub fn events(&self) -> &[PhysicsEvent] {
+        self.physics_engine.events()
+    }
+
+    /// Queues a [`PhysicsEvent::ContactForce`] for every pair of touching colliders that push
+    /// against each other with a total force of at least `threshold` during a step.
+    /// Contact force events are disabled with `None`, the default.
+    pub fn set_contact_force_event_threshold(&mut self, threshold: Option<f32>) {
+        self.physics_engine
+            .set_contact_force_event_threshold(threshold);
+    }
+
+    /// Installs custom logic run by the physics engine while stepping, replacing the previous hooks.
+    pub fn set_contact_hooks<H: ContactHooks + 'static>(&mut self, hooks: H) {
+        self.physics_engine.set_contact_hooks(Box::new(hooks));
+    }
+
+    /// Overrides the friction and restitution of the contacts between the colliders of two entities.
+    pub fn set_contact_material(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+        material: ContactMaterial,
+    ) {
+        self.physics_engine
+            .set_contact_material(entity_one, entity_two, material);
+    }
+
+    pub fn remove_contact_material(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+    ) -> Option<ContactMaterial> {
+        self.physics_engine
+            .remove_contact_material(entity_one, entity_two)
+    }
+
     /// Steps the physics at 1/60 timestep
     pub fn step(&mut self, delta: f32) {
         self.step_n(1, delta);
@@ -117,18 +253,41 @@ impl<'a> PhysicsHandler<'a> {
         self.physics_engine
             .sync_physics_world_to_game_world(&mut self.world);
 
+        self.physics_engine.sync_tilemap_colliders(self.world);
+        self.physics_engine.sync_hooked_surfaces(self.world);
+        self.physics_engine.clear_events();
         for _ in 0..n {
+            self.physics_engine.move_characters(&mut self.world, delta);
             self.physics_engine.step(delta);
         }
 
         self.physics_engine
             .sync_game_world_to_physics_world(&mut self.world);
 
-        self.physics_engine.consume_contacts();
         self.physics_engine.update_query_pipeline();
     }
 
     pub fn set_gravity(&mut self, gravity: Vector2<f32>) {
-        self.physics_engine.gravity = gravity;
+        self.physics_engine.settings.gravity = gravity;
+    }
+
+    pub fn settings(&self) -> PhysicsSettings {
+        self.physics_engine.settings
+    }
+
+    /// Replaces the settings of the world.
+    /// Collision groups and continuous collision detection only apply to the bodies and colliders built afterwards.
+    pub fn set_settings(&mut self, settings: PhysicsSettings) {
+        self.physics_engine.set_settings(settings);
+    }
+
+    pub fn collision_layers(&self) -> &CollisionLayers {
+        &self.physics_engine.collision_layers
+    }
+
+    /// Replaces the collision layers of the world.
+    /// Colliders keep the collision groups they were built with.
+    pub fn set_collision_layers(&mut self, collision_layers: CollisionLayers) {
+        self.physics_engine.collision_layers = collision_layers;
     }
 }
@@ -1,6 +1,8 @@
 use crate::physics::*;
+use crate::{EmeraldError, Rectangle};
 
 use hecs::Entity;
+use rapier2d::prelude::Shape;
 
 pub struct PhysicsRefHandler<'a> {
     physics_engine: &'a PhysicsEngine,
@@ -28,4 +30,76 @@ impl<'a> PhysicsRefHandler<'a> {
     pub fn get_colliding_areas(&self, entity: Entity) -> Vec<Entity> {
         self.physics_engine.get_colliding_entities(entity)
     }
+
+    /// Retrieves the joints attached to the body of this entity.
+    pub fn get_joints(&self, entity: Entity) -> Vec<JointHandle> {
+        self.physics_engine.get_joints(entity)
+    }
+
+    /// Retrieves the entities joined by the joint.
+    pub fn get_joint_entities(&self, joint_handle: JointHandle) -> Option<(Entity, Entity)> {
+        self.physics_engine.get_joint_entities(joint_handle)
+    }
+
+    pub fn settings(&self) -> PhysicsSettings {
+        self.physics_engine.settings
+    }
+
+    pub fn collision_layers(&self) -> &CollisionLayers {
+        &self.physics_engine.collision_layers
+    }
+
+    /// Collisions and contact forces that happened during the last physics step.
+    pub fn events(&self) -> &[PhysicsEvent] {
+        self.physics_engine.events()
+    }
+
+    /// Returns the first hit along the ray if one exists.
+    /// Fails if the query names a collision layer that doesn't exist, as do the other queries.
+    pub fn cast_ray(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Option<RayCastHit>, EmeraldError> {
+        self.physics_engine.cast_ray(ray_cast_query)
+    }
+
+    /// Returns every hit along the ray, the closest first.
+    pub fn cast_ray_all(
+        &self,
+        ray_cast_query: RayCastQuery<'_>,
+    ) -> Result<Vec<RayCastHit>, EmeraldError> {
+        self.physics_engine.cast_ray_all(ray_cast_query)
+    }
+
+    /// Returns the first hit of the moving shape if one exists.
+    pub fn cast_shape(
+        &self,
+        shape: &dyn Shape,
+        shape_cast_query: ShapeCastQuery<'_>,
+    ) -> Result<Option<ShapeCastHit>, EmeraldError> {
+        self.physics_engine.cast_shape(shape, shape_cast_query)
+    }
+
+    /// Retrieves the entities with a collider containing the point.
+    pub fn intersections_with_point(
+        &self,
+        point_query: PointQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        self.physics_engine.intersections_with_point(point_query)
+    }
+
+    /// Retrieves the entities with a collider overlapping the shape.
+    pub fn intersections_with_shape(
+        &self,
+        shape: &dyn Shape,
+        shape_query: ShapeQuery<'_>,
+    ) -> Result<Vec<Entity>, EmeraldError> {
+        self.physics_engine
+            .intersections_with_shape(shape, shape_query)
+    }
+
+    /// Retrieves the entities with a collider whose bounding box overlaps the region.
+    pub fn intersections_with_aabb(&self, region: Rectangle) -> Vec<Entity> {
+        self.physics_engine.intersections_with_aabb(region)
+    }
 }
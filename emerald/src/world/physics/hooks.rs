@@ -0,0 +1,297 @@
+use crate::physics::*;
+use crate::Vector2;
+
+use hecs::Entity;
+use rapier2d::prelude::{
+    ActiveHooks, ColliderHandle, ColliderSet, ContactModificationContext, PairFilterContext,
+    RigidBodyHandle, SolverFlags,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Largest angle in radians between a one-way platform's up direction and a contact normal
+/// for the contact to be solid.
+const ONE_WAY_PLATFORM_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+
+/// The colliders of two entities that the physics engine is about to process together.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColliderPair {
+    pub entity_one: Entity,
+    pub entity_two: Entity,
+    pub collider_one: ColliderHandle,
+    pub collider_two: ColliderHandle,
+}
+
+/// Custom logic run by the physics engine during a step, installed with `world.physics().set_contact_hooks()`.
+///
+/// Rapier only calls the hooks for pairs where at least one collider has the matching flag,
+/// set with `ColliderBuilder::active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS)` for example.
+/// The colliders of entities with a `OneWayPlatform`, a `ConveyorBelt` or a `ContactMaterial`
+/// get `ActiveHooks::MODIFY_SOLVER_CONTACTS` automatically.
+pub trait ContactHooks: Send + Sync {
+    /// Decides whether contacts are computed between the colliders.
+    /// Returning `None` ignores the pair, `Some(SolverFlags::empty())` computes the contacts
+    /// without solving them.
+    fn filter_contact_pair(&self, _pair: &ColliderPair) -> Option<SolverFlags> {
+        Some(SolverFlags::default())
+    }
+
+    /// Decides whether the intersection of colliders, one of them being a sensor, is computed.
+    fn filter_intersection_pair(&self, _pair: &ColliderPair) -> bool {
+        true
+    }
+
+    /// Changes the contacts seen by the solver, after the built-in one-way platforms,
+    /// conveyor belts and contact materials were applied.
+    fn modify_solver_contacts(
+        &self,
+        _pair: &ColliderPair,
+        _context: &mut ContactModificationContext<'_>,
+    ) {
+    }
+}
+
+impl ContactHooks for () {}
+
+/// Overrides the friction and restitution of the contacts between the colliders of two entities,
+/// set with `world.physics().set_contact_material()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContactMaterial {
+    pub friction: Option<f32>,
+    pub restitution: Option<f32>,
+}
+impl ContactMaterial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.friction = Some(friction);
+        self
+    }
+
+    pub fn restitution(mut self, restitution: f32) -> Self {
+        self.restitution = Some(restitution);
+        self
+    }
+}
+
+/// Colliders with a built-in contact modification, gathered from the game world before stepping.
+#[derive(Default)]
+pub(crate) struct HookedSurfaces {
+    pub one_way_platforms: HashMap<ColliderHandle, Vector2<f32>>,
+    pub conveyor_belts: HashMap<ColliderHandle, f32>,
+    pub contact_materials: HashMap<(Entity, Entity), ContactMaterial>,
+    /// Colliders given `ActiveHooks::MODIFY_SOLVER_CONTACTS` for being a hooked surface,
+    /// which lose it again once they no longer are one.
+    pub flagged_colliders: HashSet<ColliderHandle>,
+}
+impl HookedSurfaces {
+    /// Entity pairs are stored in order so that either order finds the same material.
+    #[inline]
+    pub fn material_key(entity_one: Entity, entity_two: Entity) -> (Entity, Entity) {
+        if entity_one <= entity_two {
+            (entity_one, entity_two)
+        } else {
+            (entity_two, entity_one)
+        }
+    }
+
+    fn modify_solver_contacts(
+        &self,
+        context: &mut ContactModificationContext<'_>,
+        pair: Option<&ColliderPair>,
+    ) {
+        if let Some(pair) = pair {
+            let key = Self::material_key(pair.entity_one, pair.entity_two);
+            if let Some(material) = self.contact_materials.get(&key) {
+                for contact in context.solver_contacts.iter_mut() {
+                    if let Some(friction) = material.friction {
+                        contact.friction = friction;
+                    }
+                    if let Some(restitution) = material.restitution {
+                        contact.restitution = restitution;
+                    }
+                }
+            }
+        }
+
+        // The speed is relative to the first collider's surface.
+        let colliders = context.colliders;
+        for (collider, sign) in [(context.collider1, 1.0), (context.collider2, -1.0)] {
+            if let (Some(speed), Some(belt)) =
+                (self.conveyor_belts.get(&collider), colliders.get(collider))
+            {
+                let direction = belt.position().rotation * Vector2::x();
+                for contact in context.solver_contacts.iter_mut() {
+                    contact.tangent_velocity = direction * (speed * sign);
+                }
+            }
+        }
+
+        // Rapier expects the allowed normal in the space of the first collider, pointing out of it.
+        let (up, sign) = if let Some(up) = self.one_way_platforms.get(&context.collider1) {
+            (up, 1.0)
+        } else if let Some(up) = self.one_way_platforms.get(&context.collider2) {
+            (up, -1.0)
+        } else {
+            return;
+        };
+
+        if let Some(collider_one) = colliders.get(context.collider1) {
+            let allowed_local_n1 = collider_one
+                .position()
+                .rotation
+                .inverse_transform_vector(&(up * sign));
+            context.update_as_oneway_platform(&allowed_local_n1, ONE_WAY_PLATFORM_ANGLE);
+        }
+    }
+}
+
+/// Runs the built-in contact modifications and the user's hooks with entities instead of handles.
+pub(crate) struct EntityHooks<'a> {
+    pub hooks: &'a dyn ContactHooks,
+    pub surfaces: &'a HookedSurfaces,
+    pub body_entities: &'a HashMap<RigidBodyHandle, Entity>,
+}
+impl<'a> EntityHooks<'a> {
+    fn pair(
+        &self,
+        colliders: &ColliderSet,
+        collider_one: ColliderHandle,
+        collider_two: ColliderHandle,
+    ) -> Option<ColliderPair> {
+        let entity = |collider: ColliderHandle| {
+            colliders
+                .get(collider)
+                .and_then(|collider| collider.parent())
+                .and_then(|body| self.body_entities.get(&body))
+                .copied()
+        };
+
+        Some(ColliderPair {
+            entity_one: entity(collider_one)?,
+            entity_two: entity(collider_two)?,
+            collider_one,
+            collider_two,
+        })
+    }
+}
+impl<'a> rapier2d::pipeline::PhysicsHooks for EntityHooks<'a> {
+    fn filter_contact_pair(&self, context: &PairFilterContext<'_>) -> Option<SolverFlags> {
+        match self.pair(context.colliders, context.collider1, context.collider2) {
+            Some(pair) => self.hooks.filter_contact_pair(&pair),
+            None => Some(SolverFlags::default()),
+        }
+    }
+
+    fn filter_intersection_pair(&self, context: &PairFilterContext<'_>) -> bool {
+        match self.pair(context.colliders, context.collider1, context.collider2) {
+            Some(pair) => self.hooks.filter_intersection_pair(&pair),
+            None => true,
+        }
+    }
+
+    fn modify_solver_contacts(&self, context: &mut ContactModificationContext<'_>) {
+        let pair = self.pair(context.colliders, context.collider1, context.collider2);
+        self.surfaces.modify_solver_contacts(context, pair.as_ref());
+
+        if let Some(pair) = pair {
+            self.hooks.modify_solver_contacts(&pair, context);
+        }
+    }
+}
+
+impl PhysicsEngine {
+    /// Gathers the colliders of one-way platforms and conveyor belts, and makes sure rapier
+    /// calls the hooks for them and for the entities with a contact material.
+    pub(crate) fn sync_hooked_surfaces(&mut self, world: &hecs::World) {
+        let mut one_way_platforms = HashMap::new();
+        for (_, (platform, body_handle)) in
+            world.query::<(&OneWayPlatform, &RigidBodyHandle)>().iter()
+        {
+            for collider in self.body_colliders.get(body_handle).into_iter().flatten() {
+                one_way_platforms.insert(*collider, platform.up);
+            }
+        }
+
+        let mut conveyor_belts = HashMap::new();
+        for (_, (belt, body_handle)) in world.query::<(&ConveyorBelt, &RigidBodyHandle)>().iter() {
+            for collider in self.body_colliders.get(body_handle).into_iter().flatten() {
+                conveyor_belts.insert(*collider, belt.speed);
+            }
+        }
+
+        let mut hooked_colliders = one_way_platforms
+            .keys()
+            .chain(conveyor_belts.keys())
+            .copied()
+            .collect::<HashSet<ColliderHandle>>();
+        for (entity_one, entity_two) in self.hooked_surfaces.contact_materials.keys() {
+            for entity in [entity_one, entity_two] {
+                if let Some(body_handle) = self.entity_bodies.get(entity) {
+                    hooked_colliders
+                        .extend(self.body_colliders.get(body_handle).into_iter().flatten());
+                }
+            }
+        }
+
+        for handle in &hooked_colliders {
+            if let Some(collider) = self.colliders.get_mut(*handle) {
+                let active_hooks = collider.active_hooks();
+                if !active_hooks.contains(ActiveHooks::MODIFY_SOLVER_CONTACTS) {
+                    collider.set_active_hooks(active_hooks | ActiveHooks::MODIFY_SOLVER_CONTACTS);
+                    self.hooked_surfaces.flagged_colliders.insert(*handle);
+                }
+            }
+        }
+
+        // Colliders that had the flag before becoming hooked surfaces keep it.
+        for handle in self.hooked_surfaces.flagged_colliders.iter() {
+            if hooked_colliders.contains(handle) {
+                continue;
+            }
+
+            if let Some(collider) = self.colliders.get_mut(*handle) {
+                collider.set_active_hooks(
+                    collider.active_hooks() - ActiveHooks::MODIFY_SOLVER_CONTACTS,
+                );
+            }
+        }
+        self.hooked_surfaces
+            .flagged_colliders
+            .retain(|handle| hooked_colliders.contains(handle));
+
+        self.hooked_surfaces.one_way_platforms = one_way_platforms;
+        self.hooked_surfaces.conveyor_belts = conveyor_belts;
+    }
+
+    #[inline]
+    pub(crate) fn set_contact_hooks(&mut self, hooks: Box<dyn ContactHooks>) {
+        self.contact_hooks = hooks;
+    }
+
+    #[inline]
+    pub(crate) fn set_contact_material(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+        material: ContactMaterial,
+    ) {
+        self.hooked_surfaces.contact_materials.insert(
+            HookedSurfaces::material_key(entity_one, entity_two),
+            material,
+        );
+    }
+
+    #[inline]
+    pub(crate) fn remove_contact_material(
+        &mut self,
+        entity_one: Entity,
+        entity_two: Entity,
+    ) -> Option<ContactMaterial> {
+        self.hooked_surfaces
+            .contact_materials
+            .remove(&HookedSurfaces::material_key(entity_one, entity_two))
+    }
+}
@@ -0,0 +1,105 @@
+use nalgebra::Point2;
+use rapier2d::prelude::{
+    FixedJoint, FixedJointBuilder, GenericJoint, ImpulseJointHandle, PrismaticJoint,
+    PrismaticJointBuilder, RevoluteJoint, RevoluteJointBuilder, RigidBody,
+};
+use serde::{Deserialize, Serialize};
+
+/// Identifies a joint between the bodies of two entities.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JointHandle {
+    /// A revolute, prismatic, fixed or generic joint solved by rapier.
+    Impulse(ImpulseJointHandle),
+    Distance(DistanceJointHandle),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DistanceJointHandle(pub(crate) u32);
+
+/// Keeps the anchors of two bodies between a minimum and a maximum distance of each other,
+/// for ropes, chains and rods. The bodies are free to rotate around their anchors.
+///
+/// Distance joints are solved by emerald rather than rapier, with impulses applied at the anchors
+/// before every physics step. Contacts are solved after them, so a body held by a joint
+/// stops against walls instead of being pulled through them.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct DistanceJoint {
+    /// Anchor on the first body, in the body's local space.
+    pub local_anchor1: Point2<f32>,
+    /// Anchor on the second body, in the body's local space.
+    pub local_anchor2: Point2<f32>,
+    pub min_length: f32,
+    pub max_length: f32,
+}
+impl DistanceJoint {
+    pub fn new(min_length: f32, max_length: f32) -> Self {
+        DistanceJoint {
+            local_anchor1: Point2::origin(),
+            local_anchor2: Point2::origin(),
+            min_length,
+            max_length,
+        }
+    }
+
+    /// A joint that only keeps the anchors from getting further than `length` apart.
+    pub fn rope(length: f32) -> Self {
+        Self::new(0.0, length)
+    }
+
+    /// A joint that keeps the anchors exactly `length` apart.
+    pub fn rod(length: f32) -> Self {
+        Self::new(length, length)
+    }
+
+    pub fn local_anchor1(mut self, anchor1: Point2<f32>) -> Self {
+        self.local_anchor1 = anchor1;
+        self
+    }
+
+    pub fn local_anchor2(mut self, anchor2: Point2<f32>) -> Self {
+        self.local_anchor2 = anchor2;
+        self
+    }
+}
+
+/// Description of a joint, built from a rapier joint or builder such as
+/// `RevoluteJointBuilder::new().motor_velocity(1.0, 0.5).limits([-1.0, 1.0])`, or a [`DistanceJoint`].
+#[derive(Clone, Copy, Debug)]
+pub enum Joint {
+    Impulse(GenericJoint),
+    Distance(DistanceJoint),
+}
+
+impl From<DistanceJoint> for Joint {
+    fn from(joint: DistanceJoint) -> Self {
+        Joint::Distance(joint)
+    }
+}
+
+macro_rules! impl_joint_from_impulse_joint {
+    ($($joint_type:ty),*) => {
+        $(
+            impl From<$joint_type> for Joint {
+                fn from(joint: $joint_type) -> Self {
+                    Joint::Impulse(joint.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_joint_from_impulse_joint!(
+    GenericJoint,
+    RevoluteJoint,
+    RevoluteJointBuilder,
+    PrismaticJoint,
+    PrismaticJointBuilder,
+    FixedJoint,
+    FixedJointBuilder
+);
+
+/// A rigid body removed from the physics world, along with the joints that were attached to it.
+pub struct RemovedBody {
+    pub body: RigidBody,
+    pub joints: Vec<JointHandle>,
+}
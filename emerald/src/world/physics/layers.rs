@@ -0,0 +1,182 @@
+use crate::EmeraldError;
+
+use rapier2d::prelude::InteractionGroups;
+use serde::{Deserialize, Serialize};
+
+/// Most layers a world can have, one per bit of `InteractionGroups`.
+pub const MAX_COLLISION_LAYERS: usize = 32;
+
+/// Named collision layers and which of them collide with each other, set with
+/// `world.physics().set_collision_layers()` or loaded from the `[[physics.layers]]` of a world file.
+///
+/// Every layer takes a bit of `InteractionGroups` in the order it was added,
+/// and collides with every layer until told otherwise.
+///
+/// ```ignore
+/// let mut layers = CollisionLayers::new();
+/// layers.add_layer("player")?;
+/// layers.add_layer("enemy")?;
+/// layers.add_layer("terrain")?;
+/// layers.set_collides("enemy", "enemy", false)?;
+/// world.physics().set_collision_layers(layers);
+///
+/// let groups = world.physics().collision_layers().groups("player")?;
+/// world.physics().build_collider(body, ColliderBuilder::ball(8.0).collision_groups(groups));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CollisionLayers {
+    layers: Vec<CollisionLayer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CollisionLayer {
+    name: String,
+    filter: u32,
+}
+
+impl CollisionLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer that collides with every layer.
+    pub fn add_layer(&mut self, name: &str) -> Result<(), EmeraldError> {
+        if self.index(name).is_some() {
+            return Err(EmeraldError::new(format!(
+                "Collision layer {:?} already exists.",
+                name
+            )));
+        }
+        if self.layers.len() == MAX_COLLISION_LAYERS {
+            return Err(EmeraldError::new(format!(
+                "Unable to add collision layer {:?}, there can't be more than {} layers.",
+                name, MAX_COLLISION_LAYERS
+            )));
+        }
+
+        self.layers.push(CollisionLayer {
+            name: name.to_string(),
+            filter: u32::MAX,
+        });
+
+        Ok(())
+    }
+
+    /// Makes the colliders of both layers collide with each other or go through each other.
+    pub fn set_collides(
+        &mut self,
+        layer_one: &str,
+        layer_two: &str,
+        collides: bool,
+    ) -> Result<(), EmeraldError> {
+        let one = self.existing_index(layer_one)?;
+        let two = self.existing_index(layer_two)?;
+
+        for (layer, other) in [(one, two), (two, one)] {
+            if collides {
+                self.layers[layer].filter |= 1 << other;
+            } else {
+                self.layers[layer].filter &= !(1 << other);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes a layer collide only with the given layers.
+    /// The other layers still need to collide with it for their colliders to meet.
+    pub fn set_collides_with(&mut self, layer: &str, others: &[&str]) -> Result<(), EmeraldError> {
+        let index = self.existing_index(layer)?;
+        self.layers[index].filter = self.bits(others)?;
+
+        Ok(())
+    }
+
+    /// Whether the colliders of both layers collide, `false` if either layer doesn't exist.
+    pub fn collides(&self, layer_one: &str, layer_two: &str) -> bool {
+        match (self.index(layer_one), self.index(layer_two)) {
+            (Some(one), Some(two)) => {
+                self.layers[one].filter & (1 << two) != 0
+                    && self.layers[two].filter & (1 << one) != 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Collision groups of a collider in the layer.
+    pub fn groups(&self, layer: &str) -> Result<InteractionGroups, EmeraldError> {
+        let index = self.existing_index(layer)?;
+
+        Ok(InteractionGroups::new(
+            1 << index,
+            self.layers[index].filter,
+        ))
+    }
+
+    /// Collision groups of a query that only sees the colliders of the given layers.
+    /// Fails if one of the layers doesn't exist.
+    pub fn query_groups(&self, layers: &[&str]) -> Result<InteractionGroups, EmeraldError> {
+        let mut filter = 0;
+        for layer in layers {
+            filter |= 1 << self.existing_index(layer)?;
+        }
+
+        Ok(InteractionGroups::new(u32::MAX, filter))
+    }
+
+    /// Name of the layer whose colliders have exactly these collision groups.
+    pub fn layer_of(&self, groups: InteractionGroups) -> Option<&str> {
+        self.layers
+            .iter()
+            .enumerate()
+            .find(|(index, layer)| {
+                groups.memberships == 1 << index && groups.filter == layer.filter
+            })
+            .map(|(_, layer)| layer.name.as_str())
+    }
+
+    /// Names of the layers, in the order of their bits.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|layer| layer.name.as_str())
+    }
+
+    /// Names of the layers the layer collides with, `None` if it collides with every layer.
+    pub fn collides_with(&self, layer: &str) -> Option<Vec<&str>> {
+        let filter = self.layers[self.index(layer)?].filter;
+        let all = self.bits_of_every_layer();
+        if filter & all == all {
+            return None;
+        }
+
+        Some(
+            self.names()
+                .enumerate()
+                .filter(|(index, _)| filter & (1 << index) != 0)
+                .map(|(_, name)| name)
+                .collect(),
+        )
+    }
+
+    fn bits(&self, layers: &[&str]) -> Result<u32, EmeraldError> {
+        let mut bits = 0;
+        for layer in layers {
+            bits |= 1 << self.existing_index(layer)?;
+        }
+
+        Ok(bits)
+    }
+
+    fn bits_of_every_layer(&self) -> u32 {
+        (0..self.layers.len()).fold(0, |bits, index| bits | 1 << index)
+    }
+
+    fn index(&self, layer: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.name == layer)
+    }
+
+    fn existing_index(&self, layer: &str) -> Result<usize, EmeraldError> {
+        self.index(layer).ok_or_else(|| {
+            EmeraldError::new(format!("Collision layer {:?} does not exist.", layer))
+        })
+    }
+}
@@ -0,0 +1,63 @@
+use crate::Vector2;
+
+use rapier2d::prelude::{IntegrationParameters, InteractionGroups};
+use serde::{Deserialize, Serialize};
+
+/// Tuning of the physics of a world, set with `world.physics().set_settings()`
+/// or loaded from the `[physics]` table of a world file.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsSettings {
+    /// Acceleration applied to every dynamic body, in units per second squared.
+    pub gravity: Vector2<f32>,
+
+    /// Solver iterations per step, more make stacks and joints stiffer at the cost of speed.
+    pub velocity_iterations: usize,
+    pub friction_iterations: usize,
+    pub stabilization_iterations: usize,
+
+    /// Enables continuous collision detection on every body built in the world,
+    /// so fast bodies don't go through thin colliders.
+    pub ccd_enabled: bool,
+    pub max_ccd_substeps: usize,
+
+    /// Units of the world in a meter. Only the length tolerances of the solver are scaled by it,
+    /// the `allowed_linear_error` and `prediction_distance` of rapier's `IntegrationParameters`.
+    /// Gravity and the shapes, positions and velocities of bodies are always in world units.
+    /// Worlds measured in pixels should use the size in pixels of their objects' real world counterparts,
+    /// around 50 to 100 for characters drawn a meter or two tall.
+    pub pixels_per_meter: f32,
+
+    /// Collision groups of the colliders loaded from ent files and of tilemap colliders
+    /// that don't set their own. Colliders built with `build_collider` keep the groups of their builder.
+    pub collision_groups: InteractionGroups,
+}
+impl PhysicsSettings {
+    pub(crate) fn apply(&self, integration_parameters: &mut IntegrationParameters) {
+        let defaults = IntegrationParameters::default();
+
+        integration_parameters.max_velocity_iterations = self.velocity_iterations;
+        integration_parameters.max_velocity_friction_iterations = self.friction_iterations;
+        integration_parameters.max_stabilization_iterations = self.stabilization_iterations;
+        integration_parameters.max_ccd_substeps = self.max_ccd_substeps;
+        integration_parameters.allowed_linear_error =
+            defaults.allowed_linear_error * self.pixels_per_meter;
+        integration_parameters.prediction_distance =
+            defaults.prediction_distance * self.pixels_per_meter;
+    }
+}
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        let defaults = IntegrationParameters::default();
+
+        PhysicsSettings {
+            gravity: Vector2::new(0.0, 0.0),
+            velocity_iterations: defaults.max_velocity_iterations,
+            friction_iterations: defaults.max_velocity_friction_iterations,
+            stabilization_iterations: defaults.max_stabilization_iterations,
+            ccd_enabled: false,
+            max_ccd_substeps: defaults.max_ccd_substeps,
+            pixels_per_meter: 1.0,
+            collision_groups: InteractionGroups::all(),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::physics::*;
+use crate::world::snapshot::{entity_bits, entity_from_bits};
+use crate::EmeraldError;
+
+use hecs::Entity;
+use rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Everything the physics engine needs to resume a simulation, captured by `World::snapshot()`.
+/// Entities are stored as their bits so that the state can be serialized.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PhysicsSnapshot {
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    island_manager: IslandManager,
+    ccd_solver: CCDSolver,
+    integration_parameters: IntegrationParameters,
+    settings: PhysicsSettings,
+    collision_layers: CollisionLayers,
+    contact_force_event_threshold: Option<f32>,
+
+    entity_bodies: Vec<(u64, RigidBodyHandle)>,
+    body_colliders: Vec<(RigidBodyHandle, Vec<ColliderHandle>)>,
+    distance_joints: Vec<(
+        DistanceJointHandle,
+        RigidBodyHandle,
+        RigidBodyHandle,
+        DistanceJoint,
+    )>,
+    distance_joint_counter: u32,
+    entity_collisions: Vec<(u64, Vec<u64>)>,
+    contact_materials: Vec<(u64, u64, ContactMaterial)>,
+    flagged_colliders: Vec<ColliderHandle>,
+}
+impl PhysicsEngine {
+    pub(crate) fn snapshot(&self) -> PhysicsSnapshot {
+        PhysicsSnapshot {
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            impulse_joints: self.impulse_joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+            island_manager: self.island_manager.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            integration_parameters: self.integration_parameters,
+            settings: self.settings,
+            collision_layers: self.collision_layers.clone(),
+            contact_force_event_threshold: self.contact_force_event_threshold,
+            entity_bodies: self
+                .entity_bodies
+                .iter()
+                .map(|(entity, body)| (entity_bits(*entity), *body))
+                .collect(),
+            body_colliders: self
+                .body_colliders
+                .iter()
+                .map(|(body, colliders)| (*body, colliders.clone()))
+                .collect(),
+            distance_joints: self
+                .distance_joints
+                .iter()
+                .map(|(handle, (body_one, body_two, joint))| {
+                    (*handle, *body_one, *body_two, *joint)
+                })
+                .collect(),
+            distance_joint_counter: self.distance_joint_counter,
+            entity_collisions: self
+                .entity_collisions
+                .iter()
+                .map(|(entity, others)| {
+                    (
+                        entity_bits(*entity),
+                        others.iter().copied().map(entity_bits).collect(),
+                    )
+                })
+                .collect(),
+            contact_materials: self
+                .hooked_surfaces
+                .contact_materials
+                .iter()
+                .map(|((entity_one, entity_two), material)| {
+                    (
+                        entity_bits(*entity_one),
+                        entity_bits(*entity_two),
+                        *material,
+                    )
+                })
+                .collect(),
+            flagged_colliders: self
+                .hooked_surfaces
+                .flagged_colliders
+                .iter()
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Replaces the whole simulation with the decoded snapshot.
+    /// The contact hooks are kept, pending events and collision events of the current simulation are dropped.
+    pub(crate) fn restore(&mut self, decoded: DecodedPhysicsSnapshot<'_>) {
+        let snapshot = decoded.snapshot;
+
+        self.bodies = snapshot.bodies.clone();
+        self.colliders = snapshot.colliders.clone();
+        self.broad_phase = snapshot.broad_phase.clone();
+        self.narrow_phase = snapshot.narrow_phase.clone();
+        self.impulse_joints = snapshot.impulse_joints.clone();
+        self.multibody_joints = snapshot.multibody_joints.clone();
+        self.island_manager = snapshot.island_manager.clone();
+        self.ccd_solver = snapshot.ccd_solver.clone();
+        self.integration_parameters = snapshot.integration_parameters;
+        self.settings = snapshot.settings;
+        self.collision_layers = snapshot.collision_layers.clone();
+        self.contact_force_event_threshold = snapshot.contact_force_event_threshold;
+        self.entity_bodies = decoded.entity_bodies;
+        self.body_entities = decoded.body_entities;
+        self.body_colliders = decoded.body_colliders;
+        self.collider_body = decoded.collider_body;
+        self.distance_joints = snapshot
+            .distance_joints
+            .iter()
+            .map(|(handle, body_one, body_two, joint)| (*handle, (*body_one, *body_two, *joint)))
+            .collect();
+        self.distance_joint_counter = snapshot.distance_joint_counter;
+        self.entity_collisions = decoded.entity_collisions;
+        self.hooked_surfaces.contact_materials = decoded.contact_materials;
+        self.hooked_surfaces.flagged_colliders =
+            snapshot.flagged_colliders.iter().copied().collect();
+
+        while self.event_recv.try_recv().is_ok() {}
+        self.events.clear();
+        self.update_query_pipeline();
+    }
+}
+
+/// The lookups of a `PhysicsSnapshot` rebuilt from its entity bits,
+/// ready to be restored without anything left to fail.
+pub(crate) struct DecodedPhysicsSnapshot<'a> {
+    snapshot: &'a PhysicsSnapshot,
+    entity_bodies: HashMap<Entity, RigidBodyHandle>,
+    body_entities: HashMap<RigidBodyHandle, Entity>,
+    body_colliders: HashMap<RigidBodyHandle, Vec<ColliderHandle>>,
+    collider_body: HashMap<ColliderHandle, RigidBodyHandle>,
+    entity_collisions: HashMap<Entity, Vec<Entity>>,
+    contact_materials: HashMap<(Entity, Entity), ContactMaterial>,
+}
+impl PhysicsSnapshot {
+    pub(crate) fn decode(&self) -> Result<DecodedPhysicsSnapshot<'_>, EmeraldError> {
+        let mut entity_bodies = HashMap::new();
+        let mut body_entities = HashMap::new();
+        for (bits, body) in &self.entity_bodies {
+            let entity = entity_from_bits(*bits)?;
+            entity_bodies.insert(entity, *body);
+            body_entities.insert(*body, entity);
+        }
+
+        let mut body_colliders = HashMap::new();
+        let mut collider_body = HashMap::new();
+        for (body, colliders) in &self.body_colliders {
+            for collider in colliders {
+                collider_body.insert(*collider, *body);
+            }
+            body_colliders.insert(*body, colliders.clone());
+        }
+
+        let mut entity_collisions = HashMap::new();
+        for (bits, others) in &self.entity_collisions {
+            let others = others
+                .iter()
+                .map(|bits| entity_from_bits(*bits))
+                .collect::<Result<Vec<Entity>, EmeraldError>>()?;
+            entity_collisions.insert(entity_from_bits(*bits)?, others);
+        }
+
+        let mut contact_materials = HashMap::new();
+        for (entity_one, entity_two, material) in &self.contact_materials {
+            contact_materials.insert(
+                (
+                    entity_from_bits(*entity_one)?,
+                    entity_from_bits(*entity_two)?,
+                ),
+                *material,
+            );
+        }
+
+        Ok(DecodedPhysicsSnapshot {
+            snapshot: self,
+            entity_bodies,
+            body_entities,
+            body_colliders,
+            collider_body,
+            entity_collisions,
+            contact_materials,
+        })
+    }
+}
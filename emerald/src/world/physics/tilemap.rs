@@ -0,0 +1,506 @@
+use crate::autotilemap::AutoTilemap;
+use crate::physics::*;
+use crate::tilemap::{TileId, Tilemap};
+use crate::EmeraldError;
+
+use hecs::Entity;
+use nalgebra::Point2;
+use rapier2d::prelude::{
+    ActiveCollisionTypes, ActiveEvents, ActiveHooks, CoefficientCombineRule, ColliderBuilder,
+    ColliderHandle, InteractionGroups, Isometry, MassProperties, RigidBodyBuilder, RigidBodyHandle,
+    SharedShape,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+
+const DEFAULT_CHUNK_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileColliderShape {
+    /// Solid tiles are merged into as few rectangles as possible.
+    Rectangles,
+
+    /// Only the edges between solid and empty tiles get a collider, merged into long segments.
+    /// Cheaper than rectangles for large solid areas, but bodies can tunnel into the tiles.
+    Outline,
+}
+
+/// Colliders of the solid tiles of the `Tilemap` or `AutoTilemap` of an entity,
+/// built with `world.physics().build_tilemap_colliders()`.
+///
+/// The tilemap is split into square chunks of tiles, and the colliders of a chunk are only
+/// rebuilt when the solidity of one of its tiles changes.
+/// Ents saved from the entity leave these colliders out, build them again once the ent is loaded.
+///
+/// ```ignore
+/// world.physics().build_tilemap_colliders(
+///     level,
+///     TilemapColliders::new()
+///         .solid_tiles(vec![0, 1, 2])
+///         .collider(ColliderBuilder::cuboid(0.0, 0.0).friction(0.0)),
+/// )?;
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TilemapColliders {
+    solid_tiles: Option<HashSet<TileId>>,
+    shape: TileColliderShape,
+    chunk_size: usize,
+    #[serde(
+        serialize_with = "serialize_collider",
+        deserialize_with = "deserialize_collider"
+    )]
+    collider: ColliderBuilder,
+    collision_groups: Option<InteractionGroups>,
+
+    revision: Option<u64>,
+    size: (usize, usize),
+    solid: Vec<bool>,
+    #[serde(
+        serialize_with = "serialize_chunks",
+        deserialize_with = "deserialize_chunks"
+    )]
+    chunks: HashMap<(usize, usize), Vec<ColliderHandle>>,
+}
+impl TilemapColliders {
+    pub fn new() -> Self {
+        TilemapColliders {
+            solid_tiles: None,
+            shape: TileColliderShape::Rectangles,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            collider: ColliderBuilder::cuboid(0.0, 0.0),
+            collision_groups: None,
+            revision: None,
+            size: (0, 0),
+            solid: Vec::new(),
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Only the tiles with these ids are solid. Every tile is solid by default.
+    pub fn solid_tiles<T: IntoIterator<Item = TileId>>(mut self, tile_ids: T) -> Self {
+        self.solid_tiles = Some(tile_ids.into_iter().collect());
+        self
+    }
+
+    pub fn shape(mut self, shape: TileColliderShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Width and height in tiles of the chunks rebuilt together, defaults to 16.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Template for the colliders of the tiles, for their friction, density and so on.
+    /// Its shape, position and collision groups are replaced.
+    pub fn collider(mut self, collider: ColliderBuilder) -> Self {
+        self.collider = collider;
+        self
+    }
+
+    /// Collision groups of the colliders of the tiles,
+    /// the `collision_groups` of the world's `PhysicsSettings` when not set.
+    pub fn collision_groups(mut self, collision_groups: InteractionGroups) -> Self {
+        self.collision_groups = Some(collision_groups);
+        self
+    }
+
+    #[inline]
+    pub fn is_solid(&self, tile_id: TileId) -> bool {
+        match &self.solid_tiles {
+            Some(solid_tiles) => solid_tiles.contains(&tile_id),
+            None => true,
+        }
+    }
+
+    /// Amount of colliders built for the tilemap.
+    pub fn collider_count(&self) -> usize {
+        self.chunks.values().map(|colliders| colliders.len()).sum()
+    }
+
+    /// Whether the collider is one of the colliders built for the tiles.
+    pub(crate) fn contains_collider(&self, collider: ColliderHandle) -> bool {
+        self.chunks
+            .values()
+            .any(|colliders| colliders.contains(&collider))
+    }
+
+    /// Points the chunks at the new handles of their colliders, after they moved to another physics engine.
+    pub(crate) fn remap_colliders(&mut self, handles: &HashMap<ColliderHandle, ColliderHandle>) {
+        for colliders in self.chunks.values_mut() {
+            *colliders = colliders
+                .iter()
+                .filter_map(|collider| handles.get(collider).copied())
+                .collect();
+        }
+    }
+
+    fn is_solid_at(&self, x: isize, y: isize) -> bool {
+        let (width, height) = self.size;
+        if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            return false;
+        }
+
+        self.solid[y as usize * width + x as usize]
+    }
+
+    /// Rebuilds the colliders of the chunks with tiles that changed since the last update.
+    fn update(
+        &mut self,
+        tilemap: &Tilemap,
+        body: RigidBodyHandle,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        if self.revision == Some(tilemap.revision) {
+            return;
+        }
+
+        let size = (tilemap.width, tilemap.height);
+        let solid = tilemap
+            .tiles
+            .iter()
+            .map(|tile| tile.map(|tile_id| self.is_solid(tile_id)).unwrap_or(false))
+            .collect::<Vec<bool>>();
+
+        let mut dirty_chunks = HashSet::new();
+        if size != self.size {
+            dirty_chunks.extend(self.chunks.keys().copied());
+            for y in (0..size.1).step_by(self.chunk_size) {
+                for x in (0..size.0).step_by(self.chunk_size) {
+                    dirty_chunks.insert((x / self.chunk_size, y / self.chunk_size));
+                }
+            }
+        } else {
+            for (index, (old, new)) in self.solid.iter().zip(solid.iter()).enumerate() {
+                if old == new {
+                    continue;
+                }
+
+                let (x, y) = (index % size.0, index / size.0);
+                dirty_chunks.insert((x / self.chunk_size, y / self.chunk_size));
+
+                // The edges of an outline depend on the neighbouring tiles.
+                if self.shape == TileColliderShape::Outline {
+                    for (nx, ny) in [
+                        (x.wrapping_sub(1), y),
+                        (x + 1, y),
+                        (x, y.wrapping_sub(1)),
+                        (x, y + 1),
+                    ] {
+                        if nx < size.0 && ny < size.1 {
+                            dirty_chunks.insert((nx / self.chunk_size, ny / self.chunk_size));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.revision = Some(tilemap.revision);
+        self.size = size;
+        self.solid = solid;
+
+        let tile_size = (tilemap.tile_size.x as f32, tilemap.tile_size.y as f32);
+        for chunk in dirty_chunks {
+            for collider in self.chunks.remove(&chunk).unwrap_or_default() {
+                physics_engine.remove_collider(collider);
+            }
+
+            let shapes = match self.shape {
+                TileColliderShape::Rectangles => self.chunk_rectangles(chunk, tile_size),
+                TileColliderShape::Outline => self.chunk_outline(chunk, tile_size),
+            };
+            let colliders = shapes
+                .into_iter()
+                .map(|(shape, position)| {
+                    let mut builder = self.collider.clone();
+                    builder.shape = shape;
+                    builder.position = position;
+                    builder.collision_groups = self
+                        .collision_groups
+                        .unwrap_or(physics_engine.settings.collision_groups);
+
+                    physics_engine.build_collider(body, builder)
+                })
+                .collect::<Vec<ColliderHandle>>();
+
+            if !colliders.is_empty() {
+                self.chunks.insert(chunk, colliders);
+            }
+        }
+    }
+
+    /// Tiles covered by a chunk, as ranges of columns and rows.
+    fn chunk_bounds(&self, (chunk_x, chunk_y): (usize, usize)) -> (usize, usize, usize, usize) {
+        let x0 = chunk_x * self.chunk_size;
+        let y0 = chunk_y * self.chunk_size;
+
+        (
+            x0,
+            y0,
+            (x0 + self.chunk_size).min(self.size.0),
+            (y0 + self.chunk_size).min(self.size.1),
+        )
+    }
+
+    /// Greedily grows rectangles of solid tiles, first along rows then along columns.
+    fn chunk_rectangles(
+        &self,
+        chunk: (usize, usize),
+        (tile_width, tile_height): (f32, f32),
+    ) -> Vec<(SharedShape, Isometry<f32>)> {
+        let (x0, y0, x1, y1) = self.chunk_bounds(chunk);
+        let chunk_width = x1.saturating_sub(x0);
+        let mut covered = vec![false; chunk_width * y1.saturating_sub(y0)];
+        let is_free = |covered: &Vec<bool>, x: usize, y: usize| {
+            self.is_solid_at(x as isize, y as isize) && !covered[(y - y0) * chunk_width + x - x0]
+        };
+
+        let mut rectangles = Vec::new();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if !is_free(&covered, x, y) {
+                    continue;
+                }
+
+                let mut width = 1;
+                while x + width < x1 && is_free(&covered, x + width, y) {
+                    width += 1;
+                }
+
+                let mut height = 1;
+                while y + height < y1 && (x..x + width).all(|x| is_free(&covered, x, y + height)) {
+                    height += 1;
+                }
+
+                for covered_y in y..y + height {
+                    for covered_x in x..x + width {
+                        covered[(covered_y - y0) * chunk_width + covered_x - x0] = true;
+                    }
+                }
+
+                let half_extents = (
+                    width as f32 * tile_width / 2.0,
+                    height as f32 * tile_height / 2.0,
+                );
+                rectangles.push((
+                    SharedShape::cuboid(half_extents.0, half_extents.1),
+                    Isometry::translation(
+                        x as f32 * tile_width + half_extents.0,
+                        y as f32 * tile_height + half_extents.1,
+                    ),
+                ));
+            }
+        }
+
+        rectangles
+    }
+
+    /// Segments along the edges between the solid tiles of a chunk and empty tiles,
+    /// merged while they follow the same line.
+    fn chunk_outline(
+        &self,
+        chunk: (usize, usize),
+        (tile_width, tile_height): (f32, f32),
+    ) -> Vec<(SharedShape, Isometry<f32>)> {
+        let (x0, y0, x1, y1) = self.chunk_bounds(chunk);
+        let mut segments = Vec::new();
+
+        // Runs of edges along rows, below then above the tiles.
+        for y in y0..y1 {
+            for offset in [-1, 1] {
+                let mut start = None;
+                for x in x0..=x1 {
+                    let is_edge = x < x1
+                        && self.is_solid_at(x as isize, y as isize)
+                        && !self.is_solid_at(x as isize, y as isize + offset);
+                    match (is_edge, start) {
+                        (true, None) => start = Some(x),
+                        (false, Some(start_x)) => {
+                            let edge_y = if offset < 0 { y } else { y + 1 };
+                            segments.push(((start_x, edge_y), (x, edge_y)));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Runs of edges along columns, left then right of the tiles.
+        for x in x0..x1 {
+            for offset in [-1, 1] {
+                let mut start = None;
+                for y in y0..=y1 {
+                    let is_edge = y < y1
+                        && self.is_solid_at(x as isize, y as isize)
+                        && !self.is_solid_at(x as isize + offset, y as isize);
+                    match (is_edge, start) {
+                        (true, None) => start = Some(y),
+                        (false, Some(start_y)) => {
+                            let edge_x = if offset < 0 { x } else { x + 1 };
+                            segments.push(((edge_x, start_y), (edge_x, y)));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if segments.is_empty() {
+            return Vec::new();
+        }
+
+        let point =
+            |(x, y): (usize, usize)| Point2::new(x as f32 * tile_width, y as f32 * tile_height);
+        let mut vertices = Vec::with_capacity(segments.len() * 2);
+        let mut indices = Vec::with_capacity(segments.len());
+        for (start, end) in segments {
+            indices.push([vertices.len() as u32, vertices.len() as u32 + 1]);
+            vertices.push(point(start));
+            vertices.push(point(end));
+        }
+
+        vec![(
+            SharedShape::polyline(vertices, Some(indices)),
+            Isometry::identity(),
+        )]
+    }
+}
+impl Default for TilemapColliders {
+    fn default() -> Self {
+        TilemapColliders::new()
+    }
+}
+
+impl PhysicsEngine {
+    /// Gives the entity a fixed body if it doesn't have one, and builds the colliders of its tilemap.
+    pub(crate) fn build_tilemap_colliders(
+        &mut self,
+        entity: Entity,
+        tilemap_colliders: TilemapColliders,
+        world: &mut hecs::World,
+    ) -> Result<RigidBodyHandle, EmeraldError> {
+        if world.get::<Tilemap>(entity).is_err() && world.get::<AutoTilemap>(entity).is_err() {
+            return Err(EmeraldError::new(
+                "Unable to build tilemap colliders for an entity without a tilemap",
+            ));
+        }
+
+        let body = world.get::<RigidBodyHandle>(entity).map(|body| *body);
+        let body = match body {
+            Ok(body) => body,
+            Err(_) => self.build_body(entity, RigidBodyBuilder::fixed(), world)?,
+        };
+
+        if let Ok(previous) = world.remove_one::<TilemapColliders>(entity) {
+            for collider in previous.chunks.into_values().flatten() {
+                self.remove_collider(collider);
+            }
+        }
+
+        world.insert_one(entity, tilemap_colliders)?;
+        self.sync_tilemap_colliders(world);
+
+        Ok(body)
+    }
+
+    /// Rebuilds the colliders of the tilemaps that changed.
+    pub(crate) fn sync_tilemap_colliders(&mut self, world: &mut hecs::World) {
+        for (_, (tilemap_colliders, body, tilemap, autotilemap)) in world
+            .query::<(
+                &mut TilemapColliders,
+                &RigidBodyHandle,
+                Option<&Tilemap>,
+                Option<&AutoTilemap>,
+            )>()
+            .iter()
+        {
+            if let Some(tilemap) =
+                tilemap.or_else(|| autotilemap.map(|autotilemap| &autotilemap.tilemap))
+            {
+                tilemap_colliders.update(tilemap, *body, self);
+            }
+        }
+    }
+}
+
+/// Chunks are serialized as a list, since formats like toml only allow string keys.
+fn serialize_chunks<S: Serializer>(
+    chunks: &HashMap<(usize, usize), Vec<ColliderHandle>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(chunks.iter())
+}
+
+fn deserialize_chunks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<(usize, usize), Vec<ColliderHandle>>, D::Error> {
+    let chunks = Vec::<((usize, usize), Vec<ColliderHandle>)>::deserialize(deserializer)?;
+    Ok(chunks.into_iter().collect())
+}
+
+/// What is kept of the collider template of the tiles, whose shape, position and collision groups are replaced.
+/// The template isn't serialized as is, its shape and `u128` user data don't fit formats like toml.
+#[derive(Serialize, Deserialize)]
+struct ColliderTemplate {
+    density: Option<f32>,
+    mass_properties: Option<MassProperties>,
+    friction: f32,
+    friction_combine_rule: CoefficientCombineRule,
+    restitution: f32,
+    restitution_combine_rule: CoefficientCombineRule,
+    is_sensor: bool,
+    active_collision_types: ActiveCollisionTypes,
+    active_hooks: ActiveHooks,
+    active_events: ActiveEvents,
+    user_data: String,
+    solver_groups: InteractionGroups,
+}
+
+fn serialize_collider<S: Serializer>(
+    collider: &ColliderBuilder,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    ColliderTemplate {
+        density: collider.density,
+        mass_properties: collider.mass_properties,
+        friction: collider.friction,
+        friction_combine_rule: collider.friction_combine_rule,
+        restitution: collider.restitution,
+        restitution_combine_rule: collider.restitution_combine_rule,
+        is_sensor: collider.is_sensor,
+        active_collision_types: collider.active_collision_types,
+        active_hooks: collider.active_hooks,
+        active_events: collider.active_events,
+        user_data: collider.user_data.to_string(),
+        solver_groups: collider.solver_groups,
+    }
+    .serialize(serializer)
+}
+
+fn deserialize_collider<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<ColliderBuilder, D::Error> {
+    let template = ColliderTemplate::deserialize(deserializer)?;
+    let mut collider = ColliderBuilder::cuboid(0.0, 0.0);
+    collider.density = template.density;
+    collider.mass_properties = template.mass_properties;
+    collider.friction = template.friction;
+    collider.friction_combine_rule = template.friction_combine_rule;
+    collider.restitution = template.restitution;
+    collider.restitution_combine_rule = template.restitution_combine_rule;
+    collider.is_sensor = template.is_sensor;
+    collider.active_collision_types = template.active_collision_types;
+    collider.active_hooks = template.active_hooks;
+    collider.active_events = template.active_events;
+    collider.user_data = template
+        .user_data
+        .parse()
+        .map_err(serde::de::Error::custom)?;
+    collider.solver_groups = template.solver_groups;
+
+    Ok(collider)
+}
@@ -1,3 +1,4 @@
+use hecs::Entity;
 use nalgebra::{Point2, Vector2};
 use rapier2d::{
     parry::query::Ray,
@@ -18,10 +19,13 @@ use crate::transform::Translation;
 /// - `filter`: a more fine-grained filter. A collider is taken into account by this query if
 ///             its `contact_group` is compatible with the `query_groups`, and if this `filter`
 ///             is either `None` or returns `true`.
+/// - `layers`: names of the collision layers the query sees, replacing the `interaction_groups` when not empty.
+///   The query fails if one of them doesn't exist.
 #[derive(Clone)]
 pub struct RayCastQuery<'a> {
     pub ray: Ray,
     pub interaction_groups: InteractionGroups,
+    pub layers: &'a [&'a str],
     pub max_toi: f32,
     pub filter: Option<&'a dyn Fn(ColliderHandle) -> bool>,
     pub solid: bool,
@@ -34,6 +38,7 @@ impl<'a> Default for RayCastQuery<'a> {
             filter: None,
             solid: true,
             interaction_groups: InteractionGroups::all(),
+            layers: &[],
         }
     }
 }
@@ -45,6 +50,9 @@ pub struct ShapeCastQuery<'a> {
     pub velocity: Vector2<f32>,
     pub max_toi: f32,
     pub interaction_groups: InteractionGroups,
+    /// Names of the collision layers the cast sees, replacing the `interaction_groups` when not empty.
+    /// The cast fails if one of them doesn't exist.
+    pub layers: &'a [&'a str],
     pub filter: Option<&'a dyn Fn(ColliderHandle) -> bool>,
 }
 impl<'a> Default for ShapeCastQuery<'a> {
@@ -55,6 +63,76 @@ impl<'a> Default for ShapeCastQuery<'a> {
             max_toi: 4.0,
             filter: None,
             interaction_groups: InteractionGroups::all(),
+            layers: &[],
         }
     }
 }
+
+/// # Parameters
+/// - `point`: the point to test, in world space.
+/// - `interaction_groups`: the interaction groups which will be tested against the collider's `contact_group`
+///   to determine if it should be taken into account by this query.
+/// - `filter`: a more fine-grained filter, see [`RayCastQuery`].
+/// - `layers`: names of the collision layers the query sees, replacing the `interaction_groups` when not empty.
+///   The query fails if one of them doesn't exist.
+#[derive(Clone)]
+pub struct PointQuery<'a> {
+    pub point: Translation,
+    pub interaction_groups: InteractionGroups,
+    pub layers: &'a [&'a str],
+    pub filter: Option<&'a dyn Fn(ColliderHandle) -> bool>,
+}
+impl<'a> Default for PointQuery<'a> {
+    fn default() -> PointQuery<'a> {
+        PointQuery {
+            point: Translation::new(0.0, 0.0),
+            interaction_groups: InteractionGroups::all(),
+            layers: &[],
+            filter: None,
+        }
+    }
+}
+
+pub struct ShapeQuery<'a> {
+    /// Where the shape is placed in world space.
+    pub origin_translation: Translation,
+    pub interaction_groups: InteractionGroups,
+    /// Names of the collision layers the query sees, replacing the `interaction_groups` when not empty.
+    /// The query fails if one of them doesn't exist.
+    pub layers: &'a [&'a str],
+    pub filter: Option<&'a dyn Fn(ColliderHandle) -> bool>,
+}
+impl<'a> Default for ShapeQuery<'a> {
+    fn default() -> ShapeQuery<'a> {
+        ShapeQuery {
+            origin_translation: Translation::new(0.0, 0.0),
+            interaction_groups: InteractionGroups::all(),
+            layers: &[],
+            filter: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayCastHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    /// Time of impact, the hit is at `ray.origin + ray.dir * toi`.
+    pub toi: f32,
+    /// Where the ray hits the collider, in world space.
+    pub point: Translation,
+    /// Normal of the collider's surface at the hit point.
+    pub normal: Vector2<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ShapeCastHit {
+    pub entity: Entity,
+    pub collider: ColliderHandle,
+    /// Time of impact, the shape touches the collider once moved by `velocity * toi`.
+    pub toi: f32,
+    /// Where the shape touches the collider at the time of impact, in world space.
+    pub point: Translation,
+    /// Normal of the collider's surface at the point of contact.
+    pub normal: Vector2<f32>,
+}
@@ -0,0 +1,296 @@
+use crate::tilemap::Tilemap;
+use crate::{Children, EmeraldError, Parent, PreviousTransform, Transform};
+
+#[cfg(feature = "physics")]
+use crate::world::physics::*;
+#[cfg(feature = "physics")]
+use rapier2d::prelude::RigidBodyHandle;
+
+use hecs::{Component, Entity};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// The entities of a world, their registered components and the whole state of the physics,
+/// captured with `world.snapshot()` and put back with `world.restore()`.
+///
+/// Restoring a snapshot brings back the entities under the same ids, so components holding
+/// entities stay valid, and resumes the simulation exactly where it was.
+/// Snapshots are serializable, to be kept in memory for rewinds and rollbacks or written to save files
+/// with a binary format such as bincode.
+///
+/// ```ignore
+/// world.register_snapshot_component::<Health>("health");
+/// let snapshot = world.snapshot()?;
+///
+/// // Later on, or in a world with the same components registered.
+/// world.restore(&snapshot)?;
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    entities: Vec<u64>,
+    #[serde(with = "component_documents")]
+    components: BTreeMap<String, Vec<(u64, toml::Value)>>,
+    #[cfg(feature = "physics")]
+    physics: PhysicsSnapshot,
+}
+
+type CaptureFn = fn(&hecs::World) -> Result<Vec<(u64, toml::Value)>, EmeraldError>;
+type DecodeFn =
+    fn(&[(u64, toml::Value)], &HashSet<Entity>) -> Result<ApplyComponents, EmeraldError>;
+
+/// Puts the decoded components of a snapshot in the world, it can no longer fail.
+type ApplyComponents = Box<dyn FnOnce(&mut hecs::World)>;
+
+/// A component type included in the snapshots of a world.
+#[derive(Clone)]
+pub(crate) struct SnapshotComponent {
+    name: String,
+    capture: CaptureFn,
+    decode: DecodeFn,
+}
+
+/// The component types included in the snapshots of a world, the engine's own components
+/// are registered in every world.
+#[derive(Clone)]
+pub(crate) struct SnapshotRegistry {
+    components: Vec<SnapshotComponent>,
+}
+impl SnapshotRegistry {
+    pub fn register<T: Component + Serialize + DeserializeOwned>(&mut self, name: &str) {
+        let component = SnapshotComponent {
+            name: name.to_string(),
+            capture: capture_component::<T>,
+            decode: decode_component::<T>,
+        };
+
+        match self.components.iter_mut().find(|c| c.name == name) {
+            Some(existing) => *existing = component,
+            None => self.components.push(component),
+        }
+    }
+}
+impl Default for SnapshotRegistry {
+    fn default() -> Self {
+        let mut registry = SnapshotRegistry {
+            components: Vec::new(),
+        };
+
+        registry.register::<Transform>("transform");
+        registry.register::<PreviousTransform>("previous_transform");
+        registry.register::<Parent>("parent");
+        registry.register::<Children>("children");
+        registry.register::<Tilemap>("tilemap");
+
+        #[cfg(feature = "physics")]
+        {
+            registry.register::<RigidBodyHandle>("rigid_body");
+            registry.register::<PhysicsSync>("physics_sync");
+            registry.register::<CharacterController>("character_controller");
+            registry.register::<OneWayPlatform>("one_way_platform");
+            registry.register::<ConveyorBelt>("conveyor_belt");
+            registry.register::<TilemapColliders>("tilemap_colliders");
+        }
+
+        registry
+    }
+}
+
+pub(crate) fn snapshot(
+    registry: &SnapshotRegistry,
+    world: &crate::World,
+) -> Result<WorldSnapshot, EmeraldError> {
+    let entities = world
+        .inner
+        .iter()
+        .map(|entity_ref| entity_bits(entity_ref.entity()))
+        .collect();
+
+    let mut components = BTreeMap::new();
+    for component in &registry.components {
+        components.insert(component.name.clone(), (component.capture)(&world.inner)?);
+    }
+
+    Ok(WorldSnapshot {
+        entities,
+        components,
+        #[cfg(feature = "physics")]
+        physics: world.physics_engine.snapshot(),
+    })
+}
+
+pub(crate) fn restore(
+    registry: &SnapshotRegistry,
+    world: &mut crate::World,
+    snapshot: &WorldSnapshot,
+) -> Result<(), EmeraldError> {
+    // Everything is decoded before the world is touched, so that a failing restore leaves it as it was.
+    let entities = snapshot
+        .entities
+        .iter()
+        .map(|bits| entity_from_bits(*bits))
+        .collect::<Result<HashSet<Entity>, EmeraldError>>()?;
+
+    for name in snapshot.components.keys() {
+        if !registry.components.iter().any(|c| &c.name == name) {
+            return Err(EmeraldError::new(format!(
+                "Snapshot holds the component {:?} that isn't registered in this world",
+                name
+            )));
+        }
+    }
+
+    let mut decoded_components = Vec::new();
+    for component in &registry.components {
+        if let Some(values) = snapshot.components.get(&component.name) {
+            decoded_components.push((component.decode)(values, &entities)?);
+        }
+    }
+
+    #[cfg(feature = "physics")]
+    let decoded_physics = snapshot.physics.decode()?;
+
+    let spawned_since = world
+        .inner
+        .iter()
+        .map(|entity_ref| entity_ref.entity())
+        .filter(|entity| !entities.contains(entity))
+        .collect::<Vec<Entity>>();
+    for entity in spawned_since {
+        // Only the entity itself goes, its children may be entities of the snapshot.
+        #[cfg(feature = "physics")]
+        world.physics_engine.remove_body(entity);
+        world.inner.despawn(entity).ok();
+    }
+
+    for entity in &entities {
+        if !world.inner.contains(*entity) {
+            world.inner.spawn_at(*entity, ());
+        }
+    }
+
+    for apply in decoded_components {
+        apply(&mut world.inner);
+    }
+
+    #[cfg(feature = "physics")]
+    world.physics_engine.restore(decoded_physics);
+
+    Ok(())
+}
+
+fn capture_component<T: Component + Serialize>(
+    world: &hecs::World,
+) -> Result<Vec<(u64, toml::Value)>, EmeraldError> {
+    let mut values = Vec::new();
+    for (entity, component) in world.query::<&T>().iter() {
+        values.push((entity_bits(entity), toml::Value::try_from(component)?));
+    }
+
+    Ok(values)
+}
+
+fn decode_component<T: Component + DeserializeOwned>(
+    values: &[(u64, toml::Value)],
+    entities: &HashSet<Entity>,
+) -> Result<ApplyComponents, EmeraldError> {
+    let mut restored = Vec::with_capacity(values.len());
+    for (bits, value) in values {
+        let entity = entity_from_bits(*bits)?;
+        if !entities.contains(&entity) {
+            return Err(EmeraldError::new(format!(
+                "Entity {:?} is missing from the snapshot",
+                entity
+            )));
+        }
+
+        restored.push((entity, value.clone().try_into::<T>()?));
+    }
+
+    Ok(Box::new(move |world: &mut hecs::World| {
+        let holders = restored
+            .iter()
+            .map(|(entity, _)| *entity)
+            .collect::<HashSet<Entity>>();
+
+        let removed = world
+            .query::<&T>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .filter(|entity| !holders.contains(entity))
+            .collect::<Vec<Entity>>();
+        for entity in removed {
+            world.remove_one::<T>(entity).ok();
+        }
+
+        // Every entity of the snapshot has been spawned by then.
+        for (entity, component) in restored {
+            world.insert_one(entity, component).ok();
+        }
+    }))
+}
+
+/// Serializes the captured components as toml documents,
+/// so that formats which can't deserialize a `toml::Value` on their own, like bincode, can hold them.
+mod component_documents {
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct ComponentDocument {
+        value: toml::Value,
+    }
+
+    pub fn serialize<S: Serializer>(
+        components: &BTreeMap<String, Vec<(u64, toml::Value)>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut documents = BTreeMap::new();
+        for (name, values) in components {
+            let mut component_documents = Vec::with_capacity(values.len());
+            for (bits, value) in values {
+                let document = ComponentDocument {
+                    value: value.clone(),
+                };
+                component_documents.push((
+                    *bits,
+                    toml::to_string(&document).map_err(ser::Error::custom)?,
+                ));
+            }
+
+            documents.insert(name, component_documents);
+        }
+
+        documents.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Vec<(u64, toml::Value)>>, D::Error> {
+        let documents = BTreeMap::<String, Vec<(u64, String)>>::deserialize(deserializer)?;
+
+        let mut components = BTreeMap::new();
+        for (name, component_documents) in documents {
+            let mut values = Vec::with_capacity(component_documents.len());
+            for (bits, document) in component_documents {
+                let document: ComponentDocument =
+                    toml::from_str(&document).map_err(de::Error::custom)?;
+                values.push((bits, document.value));
+            }
+
+            components.insert(name, values);
+        }
+
+        Ok(components)
+    }
+}
+
+#[inline]
+pub(crate) fn entity_bits(entity: Entity) -> u64 {
+    entity.to_bits().get()
+}
+
+#[inline]
+pub(crate) fn entity_from_bits(bits: u64) -> Result<Entity, EmeraldError> {
+    Entity::from_bits(bits)
+        .ok_or_else(|| EmeraldError::new(format!("Snapshot holds an invalid entity {}", bits)))
+}
@@ -0,0 +1,523 @@
+use hecs::Entity;
+
+use crate::ent::ent_camera_loader::load_ent_camera;
+use crate::ent::ent_transform_loader::load_ent_transform;
+use crate::ent::{
+    load_ent_from_toml, save_ent_to_table, CustomComponentLoader, CustomComponentSaver,
+    EntComponentRegistry, EntLoadConfig, EntSaveConfig, CAMERA_SCHEMA_KEY, TRANSFORM_SCHEMA_KEY,
+};
+use crate::{AssetLoader, EmeraldError, Transform, World};
+
+#[cfg(feature = "physics")]
+use crate::ent::{InteractionGroupsSchema, Vec2f32Schema};
+#[cfg(feature = "physics")]
+use crate::{CollisionLayers, PhysicsSettings};
+#[cfg(feature = "physics")]
+use serde::{Deserialize, Serialize};
+
+const ENTITIES_SCHEMA_KEY: &str = "entities";
+
+#[cfg(feature = "physics")]
+const PHYSICS_SCHEMA_KEY: &str = "physics";
+
+/// Key of the collision layers within the physics table.
+#[cfg(feature = "physics")]
+const LAYERS_SCHEMA_KEY: &str = "layers";
+
+/// Key of an entity entry that references an `.ent` file instead of listing its components inline.
+const ENT_PATH_SCHEMA_KEY: &str = "ent";
+
+/// Key of an entity entry holding the index of its parent within the entities of the world.
+const PARENT_SCHEMA_KEY: &str = "parent";
+
+#[derive(Default)]
+pub struct WorldLoadConfig<'a> {
+    /// Passed on to every entity of the world, see [`EntLoadConfig`].
+    pub custom_component_loader: Option<&'a CustomComponentLoader<'a>>,
+}
+
+#[derive(Default)]
+pub struct WorldSaveConfig<'a> {
+    /// Custom components written for every entity of the world, see [`EntSaveConfig`].
+    pub components: Option<&'a EntComponentRegistry>,
+    /// Passed on to every entity of the world, see [`EntSaveConfig`].
+    pub custom_component_saver: Option<&'a CustomComponentSaver<'a>>,
+}
+
+/// Settings missing from the schema keep their current value.
+#[cfg(feature = "physics")]
+#[derive(Deserialize, Serialize)]
+pub(crate) struct PhysicsSettingsSchema {
+    pub gravity: Option<Vec2f32Schema>,
+    pub velocity_iterations: Option<usize>,
+    pub friction_iterations: Option<usize>,
+    pub stabilization_iterations: Option<usize>,
+    pub ccd_enabled: Option<bool>,
+    pub max_ccd_substeps: Option<usize>,
+    pub pixels_per_meter: Option<f32>,
+    pub collision_groups: Option<InteractionGroupsSchema>,
+}
+
+/// A layer collides with every layer unless `collides_with` lists them.
+#[cfg(feature = "physics")]
+#[derive(Deserialize, Serialize)]
+pub(crate) struct CollisionLayerSchema {
+    pub name: String,
+    pub collides_with: Option<Vec<String>>,
+}
+
+/// Loads a world file into a fresh world.
+///
+/// ```toml
+/// [camera]
+/// zoom = 2.0
+/// transform = { translation = { x = 0.0, y = 0.0 } }
+///
+/// [physics]
+/// gravity = { x = 0.0, y = -9.8 }
+/// velocity_iterations = 8
+/// ccd_enabled = true
+/// pixels_per_meter = 64.0
+///
+/// # Named collision layers, the enemies only collide with the player and the terrain.
+/// [[physics.layers]]
+/// name = "player"
+///
+/// [[physics.layers]]
+/// name = "enemy"
+/// collides_with = ["player", "terrain"]
+///
+/// [[physics.layers]]
+/// name = "terrain"
+///
+/// # An entity referencing an ent file, with a transform of its own.
+/// [[entities]]
+/// ent = "bunny.ent"
+/// transform = { translation = { x = 100.0, y = 20.0 } }
+///
+/// # An entity described inline, using the same schema as an ent file.
+/// # It is attached to the first entity of the list.
+/// [[entities]]
+/// parent = 0
+/// color_rect = { color = { r = 255, g = 0, b = 0 }, width = 32, height = 8 }
+/// ```
+pub(crate) fn load_wrld(
+    loader: &mut AssetLoader<'_>,
+    toml: String,
+    config: WorldLoadConfig<'_>,
+) -> Result<World, EmeraldError> {
+    let mut toml = toml.parse::<toml::Value>()?;
+    let table = match toml.as_table_mut() {
+        Some(table) => table,
+        None => return Err(EmeraldError::new("A world file must be a toml table.")),
+    };
+
+    let mut world = World::new();
+
+    #[cfg(feature = "physics")]
+    if let Some(physics_value) = table.remove(PHYSICS_SCHEMA_KEY) {
+        load_wrld_physics(&mut world, &physics_value)?;
+    }
+
+    if let Some(entities_value) = table.remove(ENTITIES_SCHEMA_KEY) {
+        let entity_values = match entities_value {
+            toml::Value::Array(entity_values) => entity_values,
+            _ => {
+                return Err(EmeraldError::new(
+                    "The entities of a world must be an array of tables.",
+                ))
+            }
+        };
+
+        let mut entities = Vec::with_capacity(entity_values.len());
+        let mut parents = Vec::new();
+        for mut entity_value in entity_values {
+            let parent_index = entity_value
+                .as_table_mut()
+                .and_then(|entity_table| entity_table.remove(PARENT_SCHEMA_KEY));
+            if let Some(parent_index) = parent_index {
+                parents.push((entities.len(), parent_index));
+            }
+
+            entities.push(load_wrld_entity(loader, &mut world, entity_value, &config)?);
+        }
+
+        for (child_index, parent_index) in parents {
+            let parent = parent_index
+                .as_integer()
+                .and_then(|parent_index| entities.get(parent_index as usize));
+            match parent {
+                Some(parent) => world.set_parent(entities[child_index], *parent)?,
+                None => {
+                    return Err(EmeraldError::new(format!(
+                        "{} is not the index of an entity in this world.",
+                        parent_index
+                    )))
+                }
+            }
+        }
+    }
+
+    if let Some(camera_value) = table.remove(CAMERA_SCHEMA_KEY) {
+        load_wrld_camera(&mut world, camera_value)?;
+    }
+
+    Ok(world)
+}
+
+fn load_wrld_entity(
+    loader: &mut AssetLoader<'_>,
+    world: &mut World,
+    mut entity_value: toml::Value,
+    config: &WorldLoadConfig<'_>,
+) -> Result<Entity, EmeraldError> {
+    let entity_table = match entity_value.as_table_mut() {
+        Some(entity_table) => entity_table,
+        None => {
+            return Err(EmeraldError::new(
+                "Cannot load a world entity from a non-table toml value.",
+            ))
+        }
+    };
+
+    // Components listed next to an ent path replace the ones from the ent file.
+    if let Some(path_value) = entity_table.remove(ENT_PATH_SCHEMA_KEY) {
+        let path = match path_value.as_str() {
+            Some(path) => path,
+            None => {
+                return Err(EmeraldError::new(
+                    "The ent path of an entity must be a string.",
+                ))
+            }
+        };
+
+        let mut ent_value = loader.string(path)?.parse::<toml::Value>()?;
+        if let Some(ent_table) = ent_value.as_table_mut() {
+            for (key, value) in std::mem::take(entity_table) {
+                ent_table.insert(key, value);
+            }
+        }
+
+        entity_value = ent_value;
+    }
+
+    let ent_config = EntLoadConfig {
+        transform: Transform::default(),
+        custom_component_loader: config.custom_component_loader,
+    };
+
+    load_ent_from_toml(loader, world, entity_value, ent_config)
+}
+
+fn load_wrld_camera(world: &mut World, mut camera_value: toml::Value) -> Result<(), EmeraldError> {
+    let mut transform = Transform::default();
+    if let Some(camera_table) = camera_value.as_table_mut() {
+        if let Some(transform_value) = camera_table.remove(TRANSFORM_SCHEMA_KEY) {
+            transform = load_ent_transform(&transform_value)?;
+        }
+    }
+
+    let entity = world.spawn((transform,));
+    load_ent_camera(entity, world, &camera_value)?;
+    world.make_active_camera(entity)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "physics")]
+fn load_wrld_physics(world: &mut World, toml: &toml::Value) -> Result<(), EmeraldError> {
+    let settings = load_physics_settings(toml, world.physics().settings())?;
+    world.physics().set_settings(settings);
+
+    if let Some(layers_value) = toml.get(LAYERS_SCHEMA_KEY) {
+        let collision_layers = load_collision_layers(layers_value)?;
+        world.physics().set_collision_layers(collision_layers);
+    }
+
+    Ok(())
+}
+
+/// Loads collision layers from an array of layer tables.
+#[cfg(feature = "physics")]
+pub(crate) fn load_collision_layers(toml: &toml::Value) -> Result<CollisionLayers, EmeraldError> {
+    let schemas: Vec<CollisionLayerSchema> = toml.clone().try_into()?;
+
+    // Every layer must exist before they can reference each other.
+    let mut collision_layers = CollisionLayers::new();
+    for schema in &schemas {
+        collision_layers.add_layer(&schema.name)?;
+    }
+
+    for schema in &schemas {
+        if let Some(collides_with) = &schema.collides_with {
+            let others = collides_with
+                .iter()
+                .map(|other| other.as_str())
+                .collect::<Vec<&str>>();
+            collision_layers.set_collides_with(&schema.name, &others)?;
+        }
+    }
+
+    Ok(collision_layers)
+}
+
+#[cfg(feature = "physics")]
+pub(crate) fn save_collision_layers(
+    collision_layers: &CollisionLayers,
+) -> Result<toml::Value, EmeraldError> {
+    let schemas = collision_layers
+        .names()
+        .map(|name| CollisionLayerSchema {
+            name: name.to_string(),
+            collides_with: collision_layers
+                .collides_with(name)
+                .map(|others| others.into_iter().map(String::from).collect()),
+        })
+        .collect::<Vec<CollisionLayerSchema>>();
+
+    Ok(toml::Value::try_from(schemas)?)
+}
+
+/// Overrides the given settings with the ones found in the toml table.
+#[cfg(feature = "physics")]
+pub(crate) fn load_physics_settings(
+    toml: &toml::Value,
+    mut settings: PhysicsSettings,
+) -> Result<PhysicsSettings, EmeraldError> {
+    if !toml.is_table() {
+        return Err(EmeraldError::new(
+            "Cannot load physics from a non-table toml value.",
+        ));
+    }
+
+    let schema: PhysicsSettingsSchema = toml::from_str(&toml.to_string())?;
+    if let Some(gravity) = schema.gravity {
+        settings.gravity = crate::Vector2::new(gravity.x, gravity.y);
+    }
+    if let Some(velocity_iterations) = schema.velocity_iterations {
+        settings.velocity_iterations = velocity_iterations;
+    }
+    if let Some(friction_iterations) = schema.friction_iterations {
+        settings.friction_iterations = friction_iterations;
+    }
+    if let Some(stabilization_iterations) = schema.stabilization_iterations {
+        settings.stabilization_iterations = stabilization_iterations;
+    }
+    if let Some(ccd_enabled) = schema.ccd_enabled {
+        settings.ccd_enabled = ccd_enabled;
+    }
+    if let Some(max_ccd_substeps) = schema.max_ccd_substeps {
+        settings.max_ccd_substeps = max_ccd_substeps;
+    }
+    if let Some(pixels_per_meter) = schema.pixels_per_meter {
+        settings.pixels_per_meter = pixels_per_meter;
+    }
+    if let Some(collision_groups) = schema.collision_groups {
+        settings.collision_groups = collision_groups.into();
+    }
+
+    Ok(settings)
+}
+
+#[cfg(feature = "physics")]
+pub(crate) fn save_physics_settings(
+    settings: &PhysicsSettings,
+) -> Result<toml::Value, EmeraldError> {
+    let schema = PhysicsSettingsSchema {
+        gravity: Some(Vec2f32Schema {
+            x: settings.gravity.x,
+            y: settings.gravity.y,
+        }),
+        velocity_iterations: Some(settings.velocity_iterations),
+        friction_iterations: Some(settings.friction_iterations),
+        stabilization_iterations: Some(settings.stabilization_iterations),
+        ccd_enabled: Some(settings.ccd_enabled),
+        max_ccd_substeps: Some(settings.max_ccd_substeps),
+        pixels_per_meter: Some(settings.pixels_per_meter),
+        collision_groups: Some((&settings.collision_groups).into()),
+    };
+
+    Ok(toml::Value::try_from(schema)?)
+}
+
+/// Serializes every entity of the world inline, the active camera is saved as a regular entity.
+pub(crate) fn save_wrld(
+    world: &World,
+    config: WorldSaveConfig<'_>,
+) -> Result<String, EmeraldError> {
+    let ent_config = EntSaveConfig {
+        components: config.components,
+        custom_component_saver: config.custom_component_saver,
+    };
+
+    let world_entities = world
+        .inner
+        .iter()
+        .map(|entity_ref| entity_ref.entity())
+        .collect::<Vec<Entity>>();
+
+    let mut entities = Vec::with_capacity(world_entities.len());
+    for entity in &world_entities {
+        let mut entity_table = save_ent_to_table(world, *entity, &ent_config)?;
+
+        if let Some(parent) = world.get_parent(*entity) {
+            if let Some(parent_index) = world_entities.iter().position(|e| *e == parent) {
+                entity_table.insert(
+                    PARENT_SCHEMA_KEY.to_string(),
+                    toml::Value::Integer(parent_index as i64),
+                );
+            }
+        }
+
+        entities.push(toml::Value::Table(entity_table));
+    }
+
+    let mut table = toml::value::Table::new();
+
+    #[cfg(feature = "physics")]
+    {
+        let mut physics_value = save_physics_settings(&world.physics_engine.settings)?;
+        let collision_layers = &world.physics_engine.collision_layers;
+        if let (Some(physics_table), Some(_)) = (
+            physics_value.as_table_mut(),
+            collision_layers.names().next(),
+        ) {
+            physics_table.insert(
+                LAYERS_SCHEMA_KEY.to_string(),
+                save_collision_layers(collision_layers)?,
+            );
+        }
+        table.insert(PHYSICS_SCHEMA_KEY.to_string(), physics_value);
+    }
+
+    table.insert(
+        ENTITIES_SCHEMA_KEY.to_string(),
+        toml::Value::Array(entities),
+    );
+
+    let toml = toml::to_string(&toml::Value::Table(table))?;
+
+    Ok(toml)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Camera, ColorRect, Transform, World, WHITE};
+
+    use super::WorldSaveConfig;
+
+    #[test]
+    fn save_writes_every_entity() {
+        let mut world = World::new();
+        let camera = world.spawn((Transform::default(), Camera::default()));
+        world.make_active_camera(camera).unwrap();
+        for _ in 0..3 {
+            world.spawn((Transform::default(), ColorRect::new(WHITE, 4, 4)));
+        }
+
+        let saved = world.save(WorldSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        let entities = toml["entities"].as_array().unwrap();
+
+        assert_eq!(entities.len(), 4);
+        assert_eq!(
+            entities
+                .iter()
+                .filter(|entity| entity.get("color_rect").is_some())
+                .count(),
+            3
+        );
+        assert!(entities
+            .iter()
+            .any(|entity| entity.get("camera").and_then(|c| c.get("active"))
+                == Some(&toml::Value::Boolean(true))));
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn physics_settings_round_trip_through_toml() {
+        use super::{load_physics_settings, save_physics_settings};
+        use crate::{InteractionGroups, PhysicsSettings, Vector2};
+
+        let toml = r#"
+            gravity = { x = 0.0, y = -400.0 }
+            velocity_iterations = 8
+            ccd_enabled = true
+            pixels_per_meter = 64.0
+            collision_groups = { memberships = 1, filter = 6 }
+        "#
+        .parse::<toml::Value>()
+        .unwrap();
+        let settings = load_physics_settings(&toml, PhysicsSettings::default()).unwrap();
+
+        assert_eq!(settings.gravity, Vector2::new(0.0, -400.0));
+        assert_eq!(settings.velocity_iterations, 8);
+        assert!(settings.ccd_enabled);
+        assert_eq!(settings.pixels_per_meter, 64.0);
+        assert_eq!(settings.collision_groups, InteractionGroups::new(1, 6));
+        assert_eq!(
+            settings.friction_iterations,
+            PhysicsSettings::default().friction_iterations
+        );
+
+        let saved = save_physics_settings(&settings).unwrap();
+        let loaded = load_physics_settings(&saved, PhysicsSettings::default()).unwrap();
+        assert_eq!(loaded, settings);
+
+        let mut world = World::new();
+        world.physics().set_settings(settings);
+        let saved = world.save(WorldSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        assert_eq!(toml["physics"]["velocity_iterations"].as_integer(), Some(8));
+    }
+
+    #[cfg(feature = "physics")]
+    #[test]
+    fn collision_layers_round_trip_through_toml() {
+        use super::{load_collision_layers, save_collision_layers};
+
+        let toml = r#"
+            [[layers]]
+            name = "player"
+
+            [[layers]]
+            name = "enemy"
+            collides_with = ["player", "terrain"]
+
+            [[layers]]
+            name = "terrain"
+        "#
+        .parse::<toml::Value>()
+        .unwrap();
+        let layers = load_collision_layers(&toml["layers"]).unwrap();
+
+        assert_eq!(
+            layers.names().collect::<Vec<&str>>(),
+            vec!["player", "enemy", "terrain"]
+        );
+        assert!(layers.collides("enemy", "player"));
+        assert!(!layers.collides("enemy", "enemy"));
+        assert!(layers.collides("player", "player"));
+        assert_eq!(layers.collides_with("player"), None);
+
+        let saved = save_collision_layers(&layers).unwrap();
+        assert_eq!(load_collision_layers(&saved).unwrap(), layers);
+
+        let mut world = World::new();
+        world.physics().set_collision_layers(layers);
+        let saved = world.save(WorldSaveConfig::default()).unwrap();
+        let toml = saved.parse::<toml::Value>().unwrap();
+        assert_eq!(
+            toml["physics"]["layers"][1]["collides_with"]
+                .as_array()
+                .map(|others| others.len()),
+            Some(2)
+        );
+
+        let unknown = r#"[{ name = "enemy", collides_with = ["ghost"] }]"#;
+        let unknown = format!("layers = {}", unknown)
+            .parse::<toml::Value>()
+            .unwrap();
+        assert!(load_collision_layers(&unknown["layers"]).is_err());
+    }
+}